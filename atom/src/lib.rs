@@ -66,16 +66,22 @@ extern crate lv2_units as units;
 
 pub mod chunk;
 pub mod object;
+pub mod patch;
+pub mod rdf;
 pub mod scalar;
 pub mod sequence;
 pub mod space;
 pub mod string;
 pub mod tuple;
+pub mod value;
 pub mod vector;
 
 #[cfg(feature = "lv2-core")]
 pub mod port;
 
+#[cfg(feature = "serde")]
+pub mod serialization;
+
 /// Prelude of `lv2_atom` for wildcard usage.
 pub mod prelude {
     use crate::*;
@@ -83,13 +89,15 @@ pub mod prelude {
     pub use crate::{Atom, AtomURIDCollection, UnidentifiedAtom};
     pub use chunk::Chunk;
     pub use object::{Object, ObjectHeader, PropertyHeader};
+    pub use patch::{PatchMessage, PatchURIDCollection};
     pub use port::AtomPort;
     pub use scalar::{AtomURID, Bool, Double, Float, Int, Long};
-    pub use sequence::{Sequence, TimeStamp, TimeStampURID};
+    pub use sequence::{split_at_events, Sequence, TimeStamp, TimeStampURID};
     pub use space::{FramedMutSpace, MutSpace, Space};
-    pub use string::{Literal, LiteralInfo, String};
+    pub use string::{AtomURI, Literal, LiteralInfo, Path, String};
     pub use tuple::Tuple;
-    pub use vector::Vector;
+    pub use value::{AtomValue, ValueURIDCollection};
+    pub use vector::{Sound, Vector};
 }
 
 use space::*;
@@ -113,6 +121,10 @@ pub struct AtomURIDCollection {
     pub string: URID<string::String>,
     pub tuple: URID<tuple::Tuple>,
     pub sequence: URID<sequence::Sequence>,
+    pub uri: URID<string::AtomURI>,
+    pub path: URID<string::Path>,
+    pub sound: URID<vector::Sound>,
+    pub resource: URID<object::Resource>,
 }
 
 impl AtomURIDCollection {
@@ -201,6 +213,11 @@ impl<'a> UnidentifiedAtom<'a> {
             .and_then(|body| A::read(body, parameter))
     }
 
+    /// Retrieve the space that contains the header and the body of the atom.
+    pub fn space(self) -> Space<'a> {
+        self.space
+    }
+
     /// Retrieve the type URID of the atom.
     ///
     /// This can be used to identify atoms without actually reading them.
@@ -165,6 +165,39 @@ where
     }
 }
 
+/// Alias of `Object` for objects with an id, used by older hosts.
+///
+/// Like the [`Blank`](struct.Blank.html), a resource is deprecated: The [specification recommends](https://lv2plug.in/ns/ext/atom/atom.html#Resource) to use an [`Object`](struct.Object.html) with an id instead. It's included in this library to support hosts that still use it.
+///
+/// If you want to read an object, you should also support `Resource`s, but if you want to write an object, you should always use `Object`.
+pub struct Resource;
+
+unsafe impl UriBound for Resource {
+    const URI: &'static [u8] = sys::LV2_ATOM__Resource;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for Resource
+where
+    'a: 'b,
+{
+    type ReadParameter = <Object as Atom<'a, 'b>>::ReadParameter;
+    type ReadHandle = <Object as Atom<'a, 'b>>::ReadHandle;
+    type WriteParameter = <Object as Atom<'a, 'b>>::WriteParameter;
+    type WriteHandle = <Object as Atom<'a, 'b>>::WriteHandle;
+
+    #[allow(clippy::unit_arg)]
+    fn read(body: Space<'a>, parameter: Self::ReadParameter) -> Option<Self::ReadHandle> {
+        Object::read(body, parameter)
+    }
+
+    fn init(
+        frame: FramedMutSpace<'a, 'b>,
+        parameter: Self::WriteParameter,
+    ) -> Option<Self::WriteHandle> {
+        Object::init(frame, parameter)
+    }
+}
+
 /// An iterator over all properties in an object.
 ///
 /// Each iteration item is the header of the property, as well as the space occupied by the value atom. You can use normal `read` methods on the returned space.
@@ -186,7 +219,7 @@ impl<'a> Iterator for ObjectReader<'a> {
 ///
 /// This handle is a safeguard to assure that a object is always a series of properties.
 pub struct ObjectWriter<'a, 'b> {
-    frame: FramedMutSpace<'a, 'b>,
+    pub(crate) frame: FramedMutSpace<'a, 'b>,
 }
 
 impl<'a, 'b> ObjectWriter<'a, 'b> {
@@ -254,7 +287,11 @@ impl Property {
     /// Write out the header of a property atom.
     ///
     /// This method simply writes out the content of the header to the space and returns `Some(())` if it's successful.
-    fn write_header(space: &mut dyn MutSpace, key: URID, context: Option<URID>) -> Option<()> {
+    pub(crate) fn write_header(
+        space: &mut dyn MutSpace,
+        key: URID,
+        context: Option<URID>,
+    ) -> Option<()> {
         space.write(&key.get(), true)?;
         space.write(&context.map(|urid| urid.get()).unwrap_or(0), false)?;
         Some(())
@@ -263,6 +300,7 @@ impl Property {
 
 #[cfg(test)]
 mod tests {
+    use crate::object::Resource;
     use crate::prelude::*;
     use crate::space::*;
     use std::mem::size_of;
@@ -378,4 +416,53 @@ mod tests {
             assert_eq!(atom.read::<Float>(urids.float, ()).unwrap(), second_value);
         }
     }
+
+    #[test]
+    fn test_resource() {
+        let map = HashURIDMapper::new();
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let resource_id = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:my-resource\0").unwrap())
+            .unwrap();
+        let resource_type = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:my-type\0").unwrap())
+            .unwrap();
+        let key = map
+            .map_uri(Uri::from_bytes_with_nul(b"urn:value-a\0").unwrap())
+            .unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.resource,
+                    ObjectHeader {
+                        id: Some(resource_id),
+                        otype: resource_type,
+                    },
+                )
+                .unwrap();
+            writer.init(key, None, urids.int, 17).unwrap();
+        }
+
+        // reading
+        {
+            let space = Space::from_slice(raw_space.as_ref());
+            assert!(space.split_atom_body(urids.object).is_none());
+            let (body, _) = space.split_atom_body(urids.resource).unwrap();
+
+            let (header, mut iter) = Resource::read(body, ()).unwrap();
+            assert_eq!(header.id, Some(resource_id));
+            assert_eq!(header.otype, resource_type);
+
+            let (header, atom) = iter.next().unwrap();
+            assert_eq!(header.key, key);
+            assert_eq!(atom.read(urids.int, ()), Some(17));
+            assert!(iter.next().is_none());
+        }
+    }
 }
@@ -0,0 +1,548 @@
+//! Messages to access and manipulate properties of plugins and other resources.
+//!
+//! The [LV2 Patch Specification](http://lv2plug.in/ns/ext/patch/patch.html) defines messages, which are [`Object`](../object/struct.Object.html) atoms with a special type, to get and set properties of a subject. Hosts use them to implement parameters of plugins: A host sends a `patch:Set` message to change a parameter and a `patch:Get` message to ask for its current value, which the plugin answers with a `patch:Set` message too.
+//!
+//! Messages are classified with [`PatchMessage::read`](enum.PatchMessage.html#method.read) and `patch:Set` messages are written with [`SequenceWriter::init_patch_set`](../sequence/struct.SequenceWriter.html#method.init_patch_set).
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_units::prelude::*;
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::patch::*;
+//! use urid::*;
+//!
+//! #[uri("urn:my-plugin:gain")]
+//! struct Gain;
+//!
+//! #[derive(PortCollection)]
+//! struct MyPorts {
+//!     control: InputPort<AtomPort>,
+//!     notify: OutputPort<AtomPort>,
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! struct MyURIDs {
+//!     atom: AtomURIDCollection,
+//!     patch: PatchURIDCollection,
+//!     units: UnitURIDCollection,
+//!     gain: URID<Gain>,
+//! }
+//!
+//! /// Something like a plugin's run method.
+//! fn run(ports: &mut MyPorts, urids: &MyURIDs, gain: &mut f32) {
+//!     let input_sequence = ports.control.read(urids.atom.sequence, urids.units.beat).unwrap();
+//!     let mut output_sequence = ports.notify.init(
+//!         urids.atom.sequence,
+//!         TimeStampURID::Frames(urids.units.frame)
+//!     ).unwrap();
+//!
+//!     for (timestamp, atom) in input_sequence {
+//!         match PatchMessage::read(atom, &urids.patch) {
+//!             // The host sets the gain.
+//!             Some(PatchMessage::Set { property, value, .. }) if property == urids.gain => {
+//!                 if let Some(value) = value.read(urids.atom.float, ()) {
+//!                     *gain = value;
+//!                 }
+//!             }
+//!             // The host asks for the gain.
+//!             Some(PatchMessage::Get { property: Some(property), .. }) if property == urids.gain => {
+//!                 output_sequence
+//!                     .write_patch_set(timestamp, &urids.patch, None, urids.gain, urids.atom.float, *gain)
+//!                     .unwrap();
+//!             }
+//!             _ => (),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! # Specification
+//!
+//! [http://lv2plug.in/ns/ext/patch/patch.html](http://lv2plug.in/ns/ext/patch/patch.html)
+use crate::object::{ObjectHeader, ObjectReader, ObjectWriter};
+use crate::scalar::ScalarAtom;
+use crate::sequence::{SequenceWriter, TimeStamp};
+use crate::*;
+use urid::*;
+
+/// A request for the description of the subject, or the value of a single property.
+pub struct GetClass;
+unsafe impl UriBound for GetClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Get;
+}
+
+/// A request to set properties of the subject, replacing all previous values of these properties.
+pub struct SetClass;
+unsafe impl UriBound for SetClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Set;
+}
+
+/// A request to replace the subject with the body of the message.
+pub struct PutClass;
+unsafe impl UriBound for PutClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Put;
+}
+
+/// A request to add and remove properties of the subject.
+pub struct PatchClass;
+unsafe impl UriBound for PatchClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Patch;
+}
+
+/// A request to delete the subject.
+pub struct DeleteClass;
+unsafe impl UriBound for DeleteClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Delete;
+}
+
+/// A request to copy the subject to the destination.
+pub struct CopyClass;
+unsafe impl UriBound for CopyClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Copy;
+}
+
+/// A request to move the subject to the destination.
+pub struct MoveClass;
+unsafe impl UriBound for MoveClass {
+    const URI: &'static [u8] = sys::LV2_PATCH__Move;
+}
+
+/// The subject of a message; The plugin itself if not present.
+pub struct Subject;
+unsafe impl UriBound for Subject {
+    const URI: &'static [u8] = sys::LV2_PATCH__subject;
+}
+
+/// The property that is accessed by a message.
+pub struct Property;
+unsafe impl UriBound for Property {
+    const URI: &'static [u8] = sys::LV2_PATCH__property;
+}
+
+/// The value of a property.
+pub struct Value;
+unsafe impl UriBound for Value {
+    const URI: &'static [u8] = sys::LV2_PATCH__value;
+}
+
+/// The properties and values of a `patch:Put` or `patch:Set` message.
+pub struct Body;
+unsafe impl UriBound for Body {
+    const URI: &'static [u8] = sys::LV2_PATCH__body;
+}
+
+/// The properties to add in a `patch:Patch` message.
+pub struct Add;
+unsafe impl UriBound for Add {
+    const URI: &'static [u8] = sys::LV2_PATCH__add;
+}
+
+/// The properties to remove in a `patch:Patch` message.
+pub struct Remove;
+unsafe impl UriBound for Remove {
+    const URI: &'static [u8] = sys::LV2_PATCH__remove;
+}
+
+/// The destination of a `patch:Copy` or `patch:Move` message.
+pub struct Destination;
+unsafe impl UriBound for Destination {
+    const URI: &'static [u8] = sys::LV2_PATCH__destination;
+}
+
+/// The number of a message, used to associate responses with requests.
+pub struct SequenceNumber;
+unsafe impl UriBound for SequenceNumber {
+    const URI: &'static [u8] = sys::LV2_PATCH__sequenceNumber;
+}
+
+/// A URID cache containing all patch classes and properties, as well as the atom types required to read and write messages.
+#[derive(Clone, URIDCollection)]
+pub struct PatchURIDCollection {
+    pub atom: AtomURIDCollection,
+    pub get_class: URID<GetClass>,
+    pub set_class: URID<SetClass>,
+    pub put_class: URID<PutClass>,
+    pub patch_class: URID<PatchClass>,
+    pub delete_class: URID<DeleteClass>,
+    pub copy_class: URID<CopyClass>,
+    pub move_class: URID<MoveClass>,
+    pub subject: URID<Subject>,
+    pub property: URID<Property>,
+    pub value: URID<Value>,
+    pub body: URID<Body>,
+    pub add: URID<Add>,
+    pub remove: URID<Remove>,
+    pub destination: URID<Destination>,
+    pub sequence_number: URID<SequenceNumber>,
+}
+
+/// A classified patch message.
+///
+/// The subject of every message is `None` if the message is addressed to the receiver itself, which is the usual case for plugins.
+pub enum PatchMessage<'a> {
+    /// A request for the value of a property, or of all properties if `property` is `None`.
+    Get {
+        subject: Option<URID>,
+        property: Option<URID>,
+    },
+    /// A request to set a property to a value.
+    Set {
+        subject: Option<URID>,
+        property: URID,
+        value: UnidentifiedAtom<'a>,
+    },
+    /// A request to replace the subject with the properties of the body.
+    Put {
+        subject: Option<URID>,
+        body: ObjectReader<'a>,
+    },
+    /// A request to remove and add properties of the subject.
+    Patch {
+        subject: Option<URID>,
+        add: Option<ObjectReader<'a>>,
+        remove: Option<ObjectReader<'a>>,
+    },
+    /// A request to delete the subject.
+    Delete { subject: Option<URID> },
+    /// A request to copy the subject to the destination.
+    Copy {
+        subject: Option<URID>,
+        destination: URID,
+    },
+    /// A request to move the subject to the destination.
+    Move {
+        subject: Option<URID>,
+        destination: URID,
+    },
+}
+
+impl<'a> PatchMessage<'a> {
+    /// Try to read a patch message from an atom.
+    ///
+    /// The atom has to be an `Object`, `Blank` or `Resource` with the type of a patch message. `None` is returned if the atom isn't a patch message or if required properties of the message are missing.
+    pub fn read(atom: UnidentifiedAtom<'a>, urids: &PatchURIDCollection) -> Option<Self> {
+        let (header, reader) = read_object(atom, urids)?;
+        Self::from_object(&header, reader, urids)
+    }
+
+    /// Classify an already read object as a patch message.
+    ///
+    /// `None` is returned if the object isn't a patch message or if required properties of the message are missing.
+    pub fn from_object(
+        header: &ObjectHeader,
+        reader: ObjectReader<'a>,
+        urids: &PatchURIDCollection,
+    ) -> Option<Self> {
+        let mut subject: Option<URID> = None;
+        let mut property: Option<URID> = None;
+        let mut value: Option<UnidentifiedAtom<'a>> = None;
+        let mut body: Option<UnidentifiedAtom<'a>> = None;
+        let mut add: Option<UnidentifiedAtom<'a>> = None;
+        let mut remove: Option<UnidentifiedAtom<'a>> = None;
+        let mut destination: Option<URID> = None;
+
+        for (property_header, atom) in reader {
+            let key = property_header.key;
+            if key == urids.subject {
+                subject = atom.read(urids.atom.urid, ());
+            } else if key == urids.property {
+                property = atom.read(urids.atom.urid, ());
+            } else if key == urids.value {
+                value = Some(atom);
+            } else if key == urids.body {
+                body = Some(atom);
+            } else if key == urids.add {
+                add = Some(atom);
+            } else if key == urids.remove {
+                remove = Some(atom);
+            } else if key == urids.destination {
+                destination = atom.read(urids.atom.urid, ());
+            }
+        }
+
+        let otype = header.otype;
+        if otype == urids.get_class {
+            Some(PatchMessage::Get { subject, property })
+        } else if otype == urids.set_class {
+            Some(PatchMessage::Set {
+                subject,
+                property: property?,
+                value: value?,
+            })
+        } else if otype == urids.put_class {
+            Some(PatchMessage::Put {
+                subject,
+                body: read_object(body?, urids)?.1,
+            })
+        } else if otype == urids.patch_class {
+            Some(PatchMessage::Patch {
+                subject,
+                add: add
+                    .and_then(|atom| read_object(atom, urids))
+                    .map(|(_, reader)| reader),
+                remove: remove
+                    .and_then(|atom| read_object(atom, urids))
+                    .map(|(_, reader)| reader),
+            })
+        } else if otype == urids.delete_class {
+            Some(PatchMessage::Delete { subject })
+        } else if otype == urids.copy_class {
+            Some(PatchMessage::Copy {
+                subject,
+                destination: destination?,
+            })
+        } else if otype == urids.move_class {
+            Some(PatchMessage::Move {
+                subject,
+                destination: destination?,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Read an atom that is either an `Object`, a `Blank` or a `Resource`.
+fn read_object<'a>(
+    atom: UnidentifiedAtom<'a>,
+    urids: &PatchURIDCollection,
+) -> Option<(ObjectHeader, ObjectReader<'a>)> {
+    atom.read(urids.atom.object, ())
+        .or_else(|| atom.read(urids.atom.blank, ()))
+        .or_else(|| atom.read(urids.atom.resource, ()))
+}
+
+/// Writing handle for the value of a `patch:Set` message.
+///
+/// The subject and the property of the message have already been written; The value has to be initialized with [`init`](#method.init).
+pub struct PatchSetWriter<'a, 'b> {
+    writer: ObjectWriter<'a, 'b>,
+    value_key: URID<Value>,
+}
+
+impl<'a, 'b> PatchSetWriter<'a, 'b> {
+    /// Initialize the value of the property.
+    ///
+    /// This method should only be called once, since a `patch:Set` message only contains a single value.
+    pub fn init<'c, A: Atom<'a, 'c>>(
+        &'c mut self,
+        urid: URID<A>,
+        parameter: A::WriteParameter,
+    ) -> Option<A::WriteHandle> {
+        self.writer.init(self.value_key, None, urid, parameter)
+    }
+}
+
+impl<'a, 'b> SequenceWriter<'a, 'b> {
+    /// Initialize a `patch:Set` message event.
+    ///
+    /// The subject and the property of the message are written immediately and the returned writer is used to initialize the value. If `subject` is `None`, the message is addressed to the receiver itself.
+    ///
+    /// The time stamp has to be measured in the unit of the sequence. If the time stamp is measured in the wrong unit, is younger than the last written time stamp or space is insufficient, this method returns `None`.
+    pub fn init_patch_set<'c, P: ?Sized>(
+        &'c mut self,
+        stamp: TimeStamp,
+        urids: &PatchURIDCollection,
+        subject: Option<URID>,
+        property: URID<P>,
+    ) -> Option<PatchSetWriter<'a, 'c>> {
+        let mut writer = self.init(
+            stamp,
+            urids.atom.object,
+            ObjectHeader {
+                id: None,
+                otype: urids.set_class.into_general(),
+            },
+        )?;
+        if let Some(subject) = subject {
+            writer.init(urids.subject, None, urids.atom.urid, subject)?;
+        }
+        writer.init(
+            urids.property,
+            None,
+            urids.atom.urid,
+            property.into_general(),
+        )?;
+        Some(PatchSetWriter {
+            writer,
+            value_key: urids.value,
+        })
+    }
+
+    /// Write a `patch:Set` message event with a scalar value.
+    ///
+    /// This is a shortcut for [`init_patch_set`](#method.init_patch_set) followed by initializing the value, which is the common reply to a `patch:Get` message.
+    pub fn write_patch_set<P: ?Sized, A: ScalarAtom>(
+        &mut self,
+        stamp: TimeStamp,
+        urids: &PatchURIDCollection,
+        subject: Option<URID>,
+        property: URID<P>,
+        urid: URID<A>,
+        value: A::InternalType,
+    ) -> Option<()> {
+        self.init_patch_set(stamp, urids, subject, property)?
+            .init(urid, value)
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::patch::*;
+    use crate::prelude::*;
+    use crate::space::*;
+    use units::prelude::*;
+
+    #[uri("urn:my-plugin:gain")]
+    struct Gain;
+
+    #[uri("urn:my-plugin:sample")]
+    struct Sample;
+
+    #[test]
+    fn test_patch_set() {
+        let map = HashURIDMapper::new();
+        let urids: PatchURIDCollection = map.populate_collection().unwrap();
+        let units: UnitURIDCollection = map.populate_collection().unwrap();
+        let gain: URID<Gain> = map.map_type().unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.atom.sequence, TimeStampURID::Frames(units.frame))
+                .unwrap();
+            writer
+                .write_patch_set(
+                    TimeStamp::Frames(0),
+                    &urids,
+                    None,
+                    gain,
+                    urids.atom.float,
+                    0.5,
+                )
+                .unwrap();
+        }
+
+        // reading
+        let space = Space::from_slice(raw_space.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+        let mut sequence = Sequence::read(body, units.beat).unwrap();
+        let (stamp, atom) = sequence.next().unwrap();
+        assert_eq!(Some(0), stamp.as_frames());
+        assert!(sequence.next().is_none());
+
+        match PatchMessage::read(atom, &urids).unwrap() {
+            PatchMessage::Set {
+                subject,
+                property,
+                value,
+            } => {
+                assert_eq!(None, subject);
+                assert_eq!(gain, property);
+                assert_eq!(Some(0.5), value.read(urids.atom.float, ()));
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_patch_get_and_copy() {
+        let map = HashURIDMapper::new();
+        let urids: PatchURIDCollection = map.populate_collection().unwrap();
+        let gain: URID<Gain> = map.map_type().unwrap();
+        let sample: URID<Sample> = map.map_type().unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+
+        // A get message without a subject.
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.get_class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(urids.property, None, urids.atom.urid, gain.into_general())
+                .unwrap();
+        }
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        match PatchMessage::read(atom, &urids).unwrap() {
+            PatchMessage::Get { subject, property } => {
+                assert_eq!(None, subject);
+                assert_eq!(Some(gain.into_general()), property);
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        // A get message from a host that still writes resources.
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.resource,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.get_class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(urids.subject, None, urids.atom.urid, sample.into_general())
+                .unwrap();
+        }
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        match PatchMessage::read(atom, &urids).unwrap() {
+            PatchMessage::Get { subject, property } => {
+                assert_eq!(Some(sample.into_general()), subject);
+                assert_eq!(None, property);
+            }
+            _ => panic!("Invalid message"),
+        }
+
+        // A copy message without destination is invalid.
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.copy_class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(urids.subject, None, urids.atom.urid, sample.into_general())
+                .unwrap();
+        }
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        assert!(PatchMessage::read(atom, &urids).is_none());
+
+        // Any other object is not a patch message.
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: sample.into_general(),
+                    },
+                )
+                .unwrap();
+        }
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        assert!(PatchMessage::read(atom, &urids).is_none());
+    }
+}
@@ -24,9 +24,10 @@
 //! }
 //! ```
 use crate::space::*;
-use lv2_core::port::PortType;
+use lv2_core::port::{PortInfo, PortType};
 use std::ffi::c_void;
 use std::ptr::NonNull;
+use urid::Uri;
 use urid::URID;
 
 /// A handle to read atoms from a port.
@@ -114,6 +115,12 @@ impl PortType for AtomPort {
         let space = RootMutSpace::from_atom(pointer.cast().as_mut());
         PortWriter::new(space)
     }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        let uri = |uri| Uri::from_bytes_with_nul(uri).unwrap();
+        info.class(uri(sys::LV2_ATOM__AtomPort))
+            .statement(uri(sys::LV2_ATOM__bufferType), uri(sys::LV2_ATOM__Sequence))
+    }
 }
 
 #[cfg(test)]
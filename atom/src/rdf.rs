@@ -0,0 +1,2028 @@
+//! Conversion of atoms to and from text.
+//!
+//! Atoms are RDF data, and this module renders them as [Turtle](https://www.w3.org/TR/turtle/) in the same shape as [sratom](https://drobilla.net/software/sratom.html), the library most hosts use for this purpose. This is useful to debug the messages of a plugin or to store presets and state in a human-readable form. There is also a JSON equivalent, which follows the conventions of JSON-LD with full URIs.
+//!
+//! A Turtle document describes a single atom as the `rdf:value` of an anonymous node, and a JSON document is the node of the atom itself:
+//!
+//! * Booleans, numbers and strings are literals, with the XML schema datatypes `xsd:boolean`, `xsd:int`, `xsd:long`, `xsd:float` and `xsd:decimal`. Doubles that aren't finite are written as `xsd:double`.
+//! * URIDs are written as the URIs they are mapped to, absolute paths as `file://` URIs. URI atoms and relative paths are literals with the datatypes `atom:URI` and `atom:Path`, chunks are `xsd:base64Binary` literals and MIDI events are hexadecimal `midi:MidiEvent` literals.
+//! * Objects are nodes with their type and properties. Objects with an id are written as separate descriptions of this id.
+//! * Tuples, vectors, sounds and sequences are nodes of the respective type, with their elements in an `rdf:value` list. Vectors also have an `atom:childType` and the events of sequences are nodes with an `atom:frameTime` or `atom:beatTime` and an `rdf:value`.
+//! * Atoms of other types are nodes of their type, with their body as an `xsd:base64Binary` literal in `rdf:value`.
+//!
+//! When the text is parsed, these rules are applied in reverse. Since some information isn't part of the text, a few conversions are lossy: Empty sequences are always read with frame time stamps and URIDs of `file://` URIs are read as paths.
+//!
+//! The conversion allocates memory and maps URIs and therefore isn't real-time safe.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::rdf::*;
+//! use lv2_atom::space::*;
+//! use lv2_atom::value::*;
+//! use lv2_atom::UnidentifiedAtom;
+//! use urid::*;
+//!
+//! let map = HashURIDMapper::new();
+//! let urids: ValueURIDCollection = map.populate_collection().unwrap();
+//!
+//! let mut buffer = AtomBuffer::new();
+//! let value = AtomValue::Tuple(vec![AtomValue::Int(42), AtomValue::Double(0.5)]);
+//! value.write(&mut VecMutSpace::new(&mut buffer), &urids).unwrap();
+//!
+//! // Rendering the atom.
+//! let atom = UnidentifiedAtom::new(buffer.space());
+//! let text = to_turtle(atom, &map, &urids).unwrap();
+//! assert!(text.contains("\"42\"^^xsd:int"));
+//!
+//! // Parsing it again.
+//! let mut parsed = AtomBuffer::new();
+//! from_turtle(&text, &mut VecMutSpace::new(&mut parsed), &map, &urids).unwrap();
+//! assert_eq!(buffer.as_bytes(), parsed.as_bytes());
+//! ```
+use crate::sequence::{TimeStamp, TimeStampUnit};
+use crate::space::*;
+use crate::string::LiteralInfo;
+use crate::value::*;
+use crate::UnidentifiedAtom;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
+use std::fmt;
+use std::path::PathBuf;
+use urid::*;
+
+const ATOM: &str = "http://lv2plug.in/ns/ext/atom#";
+const MIDI: &str = "http://lv2plug.in/ns/ext/midi#";
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// The namespace of the language URIs of literals.
+const LEXVO: &str = "http://lexvo.org/id/iso639-1/";
+
+/// The prefixes of Turtle documents.
+const PREFIXES: [(&str, &str); 4] = [("atom", ATOM), ("midi", MIDI), ("rdf", RDF), ("xsd", XSD)];
+
+/// The maximal nesting depth of parsed documents.
+const MAX_DEPTH: usize = 256;
+
+/// Errors that may occur while converting atoms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The text isn't valid Turtle or JSON.
+    Syntax { line: usize, message: String },
+    /// A URI couldn't be mapped, or a URID couldn't be unmapped.
+    Mapping(String),
+    /// The text doesn't describe an atom, or the atom can't be described as text.
+    Invalid(String),
+    /// The atom is malformed or the space is insufficient.
+    Atom,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Syntax { line, message } => {
+                write!(f, "syntax error in line {}: {}", line, message)
+            }
+            Error::Mapping(uri) => write!(f, "failed to map or unmap {}", uri),
+            Error::Invalid(message) => write!(f, "{}", message),
+            Error::Atom => write!(f, "the atom is malformed or the space is insufficient"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Render an atom value as Turtle.
+pub fn value_to_turtle<U: Unmap + ?Sized>(value: &AtomValue, unmap: &U) -> Result<String, Error> {
+    Ok(TurtleWriter::write(&to_node(value, unmap)?))
+}
+
+/// Read an atom and render it as Turtle.
+pub fn to_turtle<U: Unmap + ?Sized>(
+    atom: UnidentifiedAtom,
+    unmap: &U,
+    urids: &ValueURIDCollection,
+) -> Result<String, Error> {
+    value_to_turtle(&AtomValue::read(atom, urids).ok_or(Error::Atom)?, unmap)
+}
+
+/// Parse an atom value from Turtle.
+pub fn value_from_turtle<M: Map + ?Sized>(text: &str, map: &M) -> Result<AtomValue, Error> {
+    from_node(&TurtleParser::new(text).parse()?, map)
+}
+
+/// Parse an atom from Turtle and write it to a space.
+pub fn from_turtle<'a, M: Map + ?Sized>(
+    text: &str,
+    space: &mut (dyn MutSpace<'a> + '_),
+    map: &M,
+    urids: &ValueURIDCollection,
+) -> Result<(), Error> {
+    value_from_turtle(text, map)?
+        .write(space, urids)
+        .ok_or(Error::Atom)
+}
+
+/// Render an atom value as JSON.
+pub fn value_to_json<U: Unmap + ?Sized>(value: &AtomValue, unmap: &U) -> Result<String, Error> {
+    let mut text = String::new();
+    write_json(&node_to_json(&to_node(value, unmap)?), 0, &mut text);
+    text.push('\n');
+    Ok(text)
+}
+
+/// Read an atom and render it as JSON.
+pub fn to_json<U: Unmap + ?Sized>(
+    atom: UnidentifiedAtom,
+    unmap: &U,
+    urids: &ValueURIDCollection,
+) -> Result<String, Error> {
+    value_to_json(&AtomValue::read(atom, urids).ok_or(Error::Atom)?, unmap)
+}
+
+/// Parse an atom value from JSON.
+pub fn value_from_json<M: Map + ?Sized>(text: &str, map: &M) -> Result<AtomValue, Error> {
+    let json = JsonParser::new(text).parse()?;
+    from_node(&json_to_node(&json, 0)?, map)
+}
+
+/// Parse an atom from JSON and write it to a space.
+pub fn from_json<'a, M: Map + ?Sized>(
+    text: &str,
+    space: &mut (dyn MutSpace<'a> + '_),
+    map: &M,
+    urids: &ValueURIDCollection,
+) -> Result<(), Error> {
+    value_from_json(text, map)?
+        .write(space, urids)
+        .ok_or(Error::Atom)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Invalid(message.to_owned())
+}
+
+fn atom_uri(name: &str) -> String {
+    format!("{}{}", ATOM, name)
+}
+
+fn rdf_uri(name: &str) -> String {
+    format!("{}{}", RDF, name)
+}
+
+fn xsd_uri(name: &str) -> String {
+    format!("{}{}", XSD, name)
+}
+
+/// An RDF node with all of its descendants.
+///
+/// This is the common representation of Turtle and JSON documents.
+#[derive(Clone, PartialEq, Debug)]
+enum Node {
+    Iri(String),
+    Literal(Literal),
+    List(Vec<Node>),
+    Resource(Resource),
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+struct Literal {
+    text: String,
+    datatype: Option<String>,
+    language: Option<String>,
+}
+
+/// A node with properties, either anonymous or with an id.
+#[derive(Clone, PartialEq, Debug, Default)]
+struct Resource {
+    id: Option<String>,
+    properties: Vec<(String, Node)>,
+}
+
+impl Node {
+    fn typed(text: String, datatype: String) -> Self {
+        Node::Literal(Literal {
+            text,
+            datatype: Some(datatype),
+            language: None,
+        })
+    }
+
+    fn resource(otype: &str, properties: Vec<(String, Node)>) -> Self {
+        let mut resource = Resource {
+            id: None,
+            properties: vec![(rdf_uri("type"), Node::Iri(atom_uri(otype)))],
+        };
+        resource.properties.extend(properties);
+        Node::Resource(resource)
+    }
+}
+
+impl Resource {
+    fn get(&self, predicate: &str) -> Option<&Node> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key == predicate)
+            .map(|(_, node)| node)
+    }
+
+    fn otype(&self) -> Option<&str> {
+        match self.get(&rdf_uri("type")) {
+            Some(Node::Iri(otype)) => Some(otype),
+            _ => None,
+        }
+    }
+
+    fn list(&self) -> Result<&[Node], Error> {
+        match self.get(&rdf_uri("value")) {
+            Some(Node::List(nodes)) => Ok(nodes),
+            _ => Err(invalid("a container without an rdf:value list")),
+        }
+    }
+}
+
+fn unmap_urid<U: Unmap + ?Sized>(unmap: &U, urid: URID) -> Result<String, Error> {
+    unmap
+        .unmap(urid)
+        .and_then(|uri| uri.to_str().ok())
+        .map(str::to_owned)
+        .ok_or_else(|| Error::Mapping(format!("URID {}", urid.get())))
+}
+
+fn map_uri<M: Map + ?Sized>(map: &M, uri: &str) -> Result<URID, Error> {
+    map.map_str(uri)
+        .ok_or_else(|| Error::Mapping(uri.to_owned()))
+}
+
+/// The XML schema representation of special floating-point values.
+fn special_float(value: f64) -> Option<&'static str> {
+    if value.is_nan() {
+        Some("NaN")
+    } else if value == f64::INFINITY {
+        Some("INF")
+    } else if value == f64::NEG_INFINITY {
+        Some("-INF")
+    } else {
+        None
+    }
+}
+
+fn parse_float<T: std::str::FromStr + From<f32>>(text: &str) -> Option<T> {
+    match text {
+        "NaN" => Some(T::from(f32::NAN)),
+        "INF" | "+INF" => Some(T::from(f32::INFINITY)),
+        "-INF" => Some(T::from(f32::NEG_INFINITY)),
+        _ => text.parse().ok(),
+    }
+}
+
+fn float_node(value: f32) -> Node {
+    let text = special_float(value.into())
+        .map(str::to_owned)
+        .unwrap_or_else(|| value.to_string());
+    Node::typed(text, xsd_uri("float"))
+}
+
+fn double_node(value: f64) -> Node {
+    match special_float(value) {
+        Some(text) => Node::typed(text.to_owned(), xsd_uri("double")),
+        None => {
+            let mut text = value.to_string();
+            if !text.contains('.') {
+                text.push_str(".0");
+            }
+            Node::typed(text, xsd_uri("decimal"))
+        }
+    }
+}
+
+fn to_node<U: Unmap + ?Sized>(value: &AtomValue, unmap: &U) -> Result<Node, Error> {
+    Ok(match value {
+        AtomValue::Bool(value) => Node::typed(value.to_string(), xsd_uri("boolean")),
+        AtomValue::Int(value) => Node::typed(value.to_string(), xsd_uri("int")),
+        AtomValue::Long(value) => Node::typed(value.to_string(), xsd_uri("long")),
+        AtomValue::Float(value) => float_node(*value),
+        AtomValue::Double(value) => double_node(*value),
+        AtomValue::URID(urid) => Node::Iri(unmap_urid(unmap, *urid)?),
+        AtomValue::String(text) => Node::Literal(Literal {
+            text: text.clone(),
+            ..Default::default()
+        }),
+        AtomValue::Literal(LiteralInfo::Language(language), text) => {
+            let uri = unmap_urid(unmap, *language)?;
+            let language = uri
+                .strip_prefix(LEXVO)
+                .ok_or_else(|| Error::Invalid(format!("{} isn't a language tag", uri)))?;
+            Node::Literal(Literal {
+                text: text.clone(),
+                datatype: None,
+                language: Some(language.to_owned()),
+            })
+        }
+        AtomValue::Literal(LiteralInfo::Datatype(datatype), text) => {
+            Node::typed(text.clone(), unmap_urid(unmap, *datatype)?)
+        }
+        AtomValue::URI(uri) => Node::typed(
+            uri.to_str()
+                .map_err(|_| invalid("a URI that isn't valid UTF-8"))?
+                .to_owned(),
+            atom_uri("URI"),
+        ),
+        AtomValue::Path(path) => {
+            let path = path
+                .to_str()
+                .ok_or_else(|| invalid("a path that isn't valid UTF-8"))?;
+            if path.starts_with('/') {
+                Node::Iri(format!("file://{}", percent_encode(path)))
+            } else {
+                Node::typed(path.to_owned(), atom_uri("Path"))
+            }
+        }
+        AtomValue::Chunk(data) => Node::typed(base64_encode(data), xsd_uri("base64Binary")),
+        AtomValue::Vector(vector) => {
+            let (child_type, elements): (&str, Vec<Node>) = match vector {
+                VectorValue::Bool(values) => (
+                    "Bool",
+                    values
+                        .iter()
+                        .map(|value| Node::typed(value.to_string(), xsd_uri("boolean")))
+                        .collect(),
+                ),
+                VectorValue::Int(values) => (
+                    "Int",
+                    values
+                        .iter()
+                        .map(|value| Node::typed(value.to_string(), xsd_uri("int")))
+                        .collect(),
+                ),
+                VectorValue::Long(values) => (
+                    "Long",
+                    values
+                        .iter()
+                        .map(|value| Node::typed(value.to_string(), xsd_uri("long")))
+                        .collect(),
+                ),
+                VectorValue::Float(values) => (
+                    "Float",
+                    values.iter().map(|value| float_node(*value)).collect(),
+                ),
+                VectorValue::Double(values) => (
+                    "Double",
+                    values.iter().map(|value| double_node(*value)).collect(),
+                ),
+                VectorValue::URID(values) => (
+                    "URID",
+                    values
+                        .iter()
+                        .map(|urid| unmap_urid(unmap, *urid).map(Node::Iri))
+                        .collect::<Result<_, _>>()?,
+                ),
+            };
+            Node::resource(
+                "Vector",
+                vec![
+                    (atom_uri("childType"), Node::Iri(atom_uri(child_type))),
+                    (rdf_uri("value"), Node::List(elements)),
+                ],
+            )
+        }
+        AtomValue::Sound(samples) => Node::resource(
+            "Sound",
+            vec![(
+                rdf_uri("value"),
+                Node::List(samples.iter().map(|sample| float_node(*sample)).collect()),
+            )],
+        ),
+        AtomValue::Tuple(children) => Node::resource(
+            "Tuple",
+            vec![(
+                rdf_uri("value"),
+                Node::List(
+                    children
+                        .iter()
+                        .map(|child| to_node(child, unmap))
+                        .collect::<Result<_, _>>()?,
+                ),
+            )],
+        ),
+        AtomValue::Object(object) => {
+            let mut resource = Resource {
+                id: object.id.map(|id| unmap_urid(unmap, id)).transpose()?,
+                properties: vec![(rdf_uri("type"), Node::Iri(unmap_urid(unmap, object.otype)?))],
+            };
+            for (key, value) in object.properties.iter() {
+                resource
+                    .properties
+                    .push((unmap_urid(unmap, *key)?, to_node(value, unmap)?));
+            }
+            Node::Resource(resource)
+        }
+        AtomValue::Sequence(sequence) => {
+            let mut events = Vec::with_capacity(sequence.events.len());
+            for (time_stamp, event) in sequence.events.iter() {
+                let time = match time_stamp {
+                    TimeStamp::Frames(frames) => (
+                        atom_uri("frameTime"),
+                        Node::typed(frames.to_string(), xsd_uri("integer")),
+                    ),
+                    TimeStamp::BeatsPerMinute(beats) => (atom_uri("beatTime"), double_node(*beats)),
+                };
+                events.push(Node::Resource(Resource {
+                    id: None,
+                    properties: vec![time, (rdf_uri("value"), to_node(event, unmap)?)],
+                }));
+            }
+            Node::resource("Sequence", vec![(rdf_uri("value"), Node::List(events))])
+        }
+        AtomValue::Other { type_urid, body } => {
+            let otype = unmap_urid(unmap, *type_urid)?;
+            if otype == format!("{}MidiEvent", MIDI) {
+                Node::typed(hex_encode(body), otype)
+            } else {
+                Node::Resource(Resource {
+                    id: None,
+                    properties: vec![
+                        (rdf_uri("type"), Node::Iri(otype)),
+                        (
+                            rdf_uri("value"),
+                            Node::typed(base64_encode(body), xsd_uri("base64Binary")),
+                        ),
+                    ],
+                })
+            }
+        }
+    })
+}
+
+fn from_node<M: Map + ?Sized>(node: &Node, map: &M) -> Result<AtomValue, Error> {
+    match node {
+        Node::Iri(iri) => match iri.strip_prefix("file://") {
+            Some(path) => Ok(AtomValue::Path(PathBuf::from(percent_decode(path)?))),
+            None => Ok(AtomValue::URID(map_uri(map, iri)?)),
+        },
+        Node::Literal(literal) => literal_from_node(literal, map),
+        Node::List(_) => Err(invalid("a list outside of a container")),
+        Node::Resource(resource) => resource_from_node(resource, map),
+    }
+}
+
+fn literal_from_node<M: Map + ?Sized>(literal: &Literal, map: &M) -> Result<AtomValue, Error> {
+    let text = literal.text.as_str();
+    let malformed = || Error::Invalid(format!("malformed literal \"{}\"", text));
+
+    if let Some(language) = literal.language.as_ref() {
+        let language = map_uri(map, &format!("{}{}", LEXVO, language.to_lowercase()))?;
+        return Ok(AtomValue::Literal(
+            LiteralInfo::Language(language),
+            text.to_owned(),
+        ));
+    }
+    let datatype = match literal.datatype.as_ref() {
+        Some(datatype) => datatype.as_str(),
+        None => return Ok(AtomValue::String(text.to_owned())),
+    };
+
+    if let Some(name) = datatype.strip_prefix(XSD) {
+        match name {
+            "string" => return Ok(AtomValue::String(text.to_owned())),
+            "boolean" => {
+                return match text {
+                    "true" | "1" => Ok(AtomValue::Bool(true)),
+                    "false" | "0" => Ok(AtomValue::Bool(false)),
+                    _ => Err(malformed()),
+                }
+            }
+            "int" => return text.parse().map(AtomValue::Int).map_err(|_| malformed()),
+            "long" | "integer" => {
+                return text.parse().map(AtomValue::Long).map_err(|_| malformed())
+            }
+            "float" => {
+                return parse_float(text)
+                    .map(AtomValue::Float)
+                    .ok_or_else(malformed)
+            }
+            "double" | "decimal" => {
+                return parse_float(text)
+                    .map(AtomValue::Double)
+                    .ok_or_else(malformed)
+            }
+            "base64Binary" => {
+                return base64_decode(text)
+                    .map(AtomValue::Chunk)
+                    .ok_or_else(malformed)
+            }
+            _ => (),
+        }
+    } else if let Some(name) = datatype.strip_prefix(ATOM) {
+        match name {
+            "URI" => {
+                return CString::new(text)
+                    .map(AtomValue::URI)
+                    .map_err(|_| malformed())
+            }
+            "Path" => return Ok(AtomValue::Path(PathBuf::from(text))),
+            _ => (),
+        }
+    } else if datatype == format!("{}MidiEvent", MIDI) {
+        return Ok(AtomValue::Other {
+            type_urid: map_uri(map, datatype)?,
+            body: hex_decode(text).ok_or_else(malformed)?,
+        });
+    }
+
+    Ok(AtomValue::Literal(
+        LiteralInfo::Datatype(map_uri(map, datatype)?),
+        text.to_owned(),
+    ))
+}
+
+/// Convert the elements of a container, which all have to match `extract`.
+fn elements<M, T, F>(nodes: &[Node], map: &M, extract: F) -> Result<Vec<T>, Error>
+where
+    M: Map + ?Sized,
+    F: Fn(AtomValue) -> Option<T>,
+{
+    nodes
+        .iter()
+        .map(|node| {
+            extract(from_node(node, map)?)
+                .ok_or_else(|| invalid("an element of the wrong type in a vector"))
+        })
+        .collect()
+}
+
+fn resource_from_node<M: Map + ?Sized>(resource: &Resource, map: &M) -> Result<AtomValue, Error> {
+    let otype = resource
+        .otype()
+        .ok_or_else(|| invalid("a node without a type"))?;
+
+    match otype.strip_prefix(ATOM) {
+        Some("Tuple") => {
+            return Ok(AtomValue::Tuple(
+                resource
+                    .list()?
+                    .iter()
+                    .map(|node| from_node(node, map))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+        Some("Vector") => {
+            let nodes = resource.list()?;
+            let child_type = match resource.get(&atom_uri("childType")) {
+                Some(Node::Iri(child_type)) => child_type.strip_prefix(ATOM),
+                _ => None,
+            };
+            let vector = match child_type {
+                Some("Bool") => VectorValue::Bool(elements(nodes, map, |value| match value {
+                    AtomValue::Bool(value) => Some(value),
+                    _ => None,
+                })?),
+                Some("Int") => VectorValue::Int(elements(nodes, map, |value| match value {
+                    AtomValue::Int(value) => Some(value),
+                    _ => None,
+                })?),
+                Some("Long") => VectorValue::Long(elements(nodes, map, |value| match value {
+                    AtomValue::Long(value) => Some(value),
+                    _ => None,
+                })?),
+                Some("Float") => VectorValue::Float(elements(nodes, map, |value| match value {
+                    AtomValue::Float(value) => Some(value),
+                    _ => None,
+                })?),
+                Some("Double") => VectorValue::Double(elements(nodes, map, |value| match value {
+                    AtomValue::Double(value) => Some(value),
+                    _ => None,
+                })?),
+                Some("URID") => VectorValue::URID(elements(nodes, map, |value| match value {
+                    AtomValue::URID(value) => Some(value),
+                    _ => None,
+                })?),
+                _ => return Err(invalid("a vector with an unsupported child type")),
+            };
+            return Ok(AtomValue::Vector(vector));
+        }
+        Some("Sound") => {
+            return Ok(AtomValue::Sound(elements(
+                resource.list()?,
+                map,
+                |value| match value {
+                    AtomValue::Float(value) => Some(value),
+                    _ => None,
+                },
+            )?))
+        }
+        Some("Sequence") => return sequence_from_node(resource, map),
+        _ => (),
+    }
+
+    // Atoms of unknown types only have a type and a binary body.
+    if let (None, [_, (key, Node::Literal(literal))]) =
+        (resource.id.as_ref(), resource.properties.as_slice())
+    {
+        if *key == rdf_uri("value") && literal.datatype == Some(xsd_uri("base64Binary")) {
+            return Ok(AtomValue::Other {
+                type_urid: map_uri(map, otype)?,
+                body: base64_decode(&literal.text)
+                    .ok_or_else(|| invalid("malformed base64 literal"))?,
+            });
+        }
+    }
+
+    let mut properties = BTreeMap::new();
+    for (key, node) in resource.properties.iter() {
+        if *key != rdf_uri("type") {
+            properties.insert(map_uri(map, key)?, from_node(node, map)?);
+        }
+    }
+    Ok(AtomValue::Object(ObjectValue {
+        id: resource
+            .id
+            .as_ref()
+            .map(|id| map_uri(map, id))
+            .transpose()?,
+        otype: map_uri(map, otype)?,
+        properties,
+    }))
+}
+
+fn sequence_from_node<M: Map + ?Sized>(resource: &Resource, map: &M) -> Result<AtomValue, Error> {
+    let mut unit = None;
+    let mut events = Vec::new();
+    for node in resource.list()? {
+        let event = match node {
+            Node::Resource(event) => event,
+            _ => return Err(invalid("an event that isn't a node")),
+        };
+        let time_stamp = if let Some(Node::Literal(frames)) = event.get(&atom_uri("frameTime")) {
+            frames
+                .text
+                .parse()
+                .map(TimeStamp::Frames)
+                .map_err(|_| invalid("a malformed frame time"))?
+        } else if let Some(Node::Literal(beats)) = event.get(&atom_uri("beatTime")) {
+            parse_float(&beats.text)
+                .map(TimeStamp::BeatsPerMinute)
+                .ok_or_else(|| invalid("a malformed beat time"))?
+        } else {
+            return Err(invalid("an event without a time stamp"));
+        };
+        let event_unit = match time_stamp {
+            TimeStamp::Frames(_) => TimeStampUnit::Frames,
+            TimeStamp::BeatsPerMinute(_) => TimeStampUnit::BeatsPerMinute,
+        };
+        if *unit.get_or_insert(event_unit) != event_unit {
+            return Err(invalid("a sequence with mixed time stamp units"));
+        }
+        let value = event
+            .get(&rdf_uri("value"))
+            .ok_or_else(|| invalid("an event without a value"))?;
+        events.push((time_stamp, from_node(value, map)?));
+    }
+    Ok(AtomValue::Sequence(SequenceValue {
+        unit: unit.unwrap_or(TimeStampUnit::Frames),
+        events,
+    }))
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let group = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(text.len() / 4 * 3);
+    let mut group = 0u32;
+    let mut bits = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' {
+            break;
+        }
+        let value = BASE64.iter().position(|b| *b == c)? as u32;
+        group = (group << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((group >> bits) as u8);
+        }
+    }
+    Some(data)
+}
+
+fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn percent_encode(path: &str) -> String {
+    let mut text = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            text.push(byte as char);
+        } else {
+            text.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    text
+}
+
+fn percent_decode(text: &str) -> Result<String, Error> {
+    let malformed = || Error::Invalid(format!("malformed file URI {}", text));
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        if text.as_bytes()[i] == b'%' {
+            let hex = text.get(i + 1..i + 3).ok_or_else(malformed)?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| malformed())?);
+            i += 3;
+        } else {
+            bytes.push(text.as_bytes()[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| malformed())
+}
+
+/// Renders a node tree as a Turtle document.
+struct TurtleWriter {
+    text: String,
+    /// Descriptions of nodes with ids, which are written after the current statement.
+    pending: Vec<Resource>,
+}
+
+impl TurtleWriter {
+    fn write(node: &Node) -> String {
+        let mut writer = TurtleWriter {
+            text: String::new(),
+            pending: Vec::new(),
+        };
+        for (prefix, namespace) in PREFIXES.iter() {
+            writer
+                .text
+                .push_str(&format!("@prefix {}: <{}> .\n", prefix, namespace));
+        }
+
+        writer.text.push_str("\n[]\n\trdf:value ");
+        writer.write_node(node, 1);
+        writer.text.push_str(" .\n");
+
+        while !writer.pending.is_empty() {
+            let resource = writer.pending.remove(0);
+            writer.text.push('\n');
+            writer.write_iri(resource.id.as_ref().unwrap());
+            writer.text.push('\n');
+            writer.write_properties(&resource.properties, 0);
+            writer.text.push_str(" .\n");
+        }
+        writer.text
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.text.push('\t');
+        }
+    }
+
+    fn write_iri(&mut self, iri: &str) {
+        for (prefix, namespace) in PREFIXES.iter() {
+            if let Some(local) = iri.strip_prefix(namespace) {
+                let mut chars = local.chars();
+                let valid = chars
+                    .next()
+                    .map(|c| c.is_ascii_alphabetic() || c == '_')
+                    .unwrap_or(false)
+                    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                if valid {
+                    self.text.push_str(&format!("{}:{}", prefix, local));
+                    return;
+                }
+            }
+        }
+        self.text.push('<');
+        for c in iri.chars() {
+            if c <= ' ' || "<>\"{}|^`\\".contains(c) {
+                self.text.push_str(&format!("\\u{:04X}", c as u32));
+            } else {
+                self.text.push(c);
+            }
+        }
+        self.text.push('>');
+    }
+
+    fn write_literal(&mut self, literal: &Literal) {
+        let text = literal.text.as_str();
+        let numeric = |fraction: bool| {
+            let digits = text.strip_prefix('-').unwrap_or(text);
+            let (whole, rest) = match digits.split_once('.') {
+                Some((whole, rest)) => (whole, Some(rest)),
+                None => (digits, None),
+            };
+            !whole.is_empty()
+                && whole.bytes().all(|c| c.is_ascii_digit())
+                && match rest {
+                    Some(rest) => {
+                        fraction && !rest.is_empty() && rest.bytes().all(|c| c.is_ascii_digit())
+                    }
+                    None => !fraction,
+                }
+        };
+        let bare = match literal
+            .datatype
+            .as_ref()
+            .and_then(|dt| dt.strip_prefix(XSD))
+        {
+            Some("integer") => numeric(false),
+            Some("decimal") => numeric(true),
+            Some("boolean") => text == "true" || text == "false",
+            _ => false,
+        };
+        if bare {
+            self.text.push_str(text);
+            return;
+        }
+
+        self.text.push('"');
+        for c in text.chars() {
+            match c {
+                '"' => self.text.push_str("\\\""),
+                '\\' => self.text.push_str("\\\\"),
+                '\n' => self.text.push_str("\\n"),
+                '\r' => self.text.push_str("\\r"),
+                '\t' => self.text.push_str("\\t"),
+                c if c < ' ' => self.text.push_str(&format!("\\u{:04X}", c as u32)),
+                c => self.text.push(c),
+            }
+        }
+        self.text.push('"');
+        if let Some(language) = literal.language.as_ref() {
+            self.text.push('@');
+            self.text.push_str(language);
+        } else if let Some(datatype) = literal.datatype.as_ref() {
+            self.text.push_str("^^");
+            self.write_iri(datatype);
+        }
+    }
+
+    fn write_properties(&mut self, properties: &[(String, Node)], depth: usize) {
+        for (i, (predicate, node)) in properties.iter().enumerate() {
+            if i > 0 {
+                self.text.push_str(" ;\n");
+            }
+            self.indent(depth + 1);
+            if *predicate == rdf_uri("type") {
+                self.text.push('a');
+            } else {
+                self.write_iri(predicate);
+            }
+            self.text.push(' ');
+            self.write_node(node, depth + 1);
+        }
+    }
+
+    fn write_node(&mut self, node: &Node, depth: usize) {
+        match node {
+            Node::Iri(iri) => self.write_iri(iri),
+            Node::Literal(literal) => self.write_literal(literal),
+            Node::List(nodes) if nodes.is_empty() => self.text.push_str("()"),
+            Node::List(nodes) => {
+                self.text.push_str("(\n");
+                for node in nodes {
+                    self.indent(depth + 1);
+                    self.write_node(node, depth + 1);
+                    self.text.push('\n');
+                }
+                self.indent(depth);
+                self.text.push(')');
+            }
+            Node::Resource(resource) => match resource.id.as_ref() {
+                Some(id) => {
+                    self.write_iri(id);
+                    self.pending.push(resource.clone());
+                }
+                None if resource.properties.is_empty() => self.text.push_str("[]"),
+                None => {
+                    self.text.push_str("[\n");
+                    self.write_properties(&resource.properties, depth);
+                    self.text.push('\n');
+                    self.indent(depth);
+                    self.text.push(']');
+                }
+            },
+        }
+    }
+}
+
+/// A term of a parsed Turtle document.
+#[derive(Clone, PartialEq, Debug)]
+enum Term {
+    Iri(String),
+    Blank(usize),
+    Literal(Literal),
+    List(Vec<Term>),
+}
+
+/// Parses the subset of Turtle that is needed to describe atoms.
+///
+/// Collections are kept as terms instead of being expanded to `rdf:first` and `rdf:rest` triples.
+struct TurtleParser<'a> {
+    text: &'a str,
+    position: usize,
+    line: usize,
+    base: Option<String>,
+    prefixes: HashMap<String, String>,
+    blank_labels: HashMap<String, usize>,
+    blank_count: usize,
+    triples: Vec<(Term, String, Term)>,
+}
+
+impl<'a> TurtleParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            position: 0,
+            line: 1,
+            base: None,
+            prefixes: HashMap::new(),
+            blank_labels: HashMap::new(),
+            blank_count: 0,
+            triples: Vec::new(),
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, Error> {
+        Err(Error::Syntax {
+            line: self.line,
+            message: message.to_owned(),
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", expected))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while !matches!(self.next(), Some('\n') | None) {}
+            } else if c.is_whitespace() {
+                self.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Consume a keyword if it's the next token.
+    fn keyword(&mut self, keyword: &str, case_sensitive: bool) -> bool {
+        let rest = self.rest();
+        let matches = rest.len() >= keyword.len()
+            && rest.is_char_boundary(keyword.len())
+            && if case_sensitive {
+                rest.starts_with(keyword)
+            } else {
+                rest[..keyword.len()].eq_ignore_ascii_case(keyword)
+            };
+        if !matches {
+            return false;
+        }
+        let separated = !rest[keyword.len()..]
+            .chars()
+            .next()
+            .map(|c| is_name_char(c) || c == ':')
+            .unwrap_or(false);
+        if separated {
+            self.position += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn new_blank(&mut self) -> Term {
+        self.blank_count += 1;
+        Term::Blank(self.blank_count - 1)
+    }
+
+    fn parse(mut self) -> Result<Node, Error> {
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() {
+                break;
+            }
+            if self.keyword("@prefix", true) {
+                self.parse_prefix()?;
+                self.expect('.')?;
+            } else if self.keyword("PREFIX", false) {
+                self.parse_prefix()?;
+            } else if self.keyword("@base", true) {
+                self.parse_base()?;
+                self.expect('.')?;
+            } else if self.keyword("BASE", false) {
+                self.parse_base()?;
+            } else {
+                self.parse_statement()?;
+            }
+        }
+        Graph {
+            triples: self.triples,
+        }
+        .root()
+    }
+
+    fn parse_prefix(&mut self) -> Result<(), Error> {
+        self.skip_whitespace();
+        let mut prefix = String::new();
+        while let Some(c) = self.peek().filter(|c| *c != ':') {
+            if !is_name_char(c) {
+                return self.error("invalid prefix name");
+            }
+            prefix.push(c);
+            self.next();
+        }
+        self.expect(':')?;
+        self.skip_whitespace();
+        let namespace = self.parse_iri_ref()?;
+        self.prefixes.insert(prefix, namespace);
+        Ok(())
+    }
+
+    fn parse_base(&mut self) -> Result<(), Error> {
+        self.skip_whitespace();
+        self.base = Some(self.parse_iri_ref()?);
+        Ok(())
+    }
+
+    fn parse_statement(&mut self) -> Result<(), Error> {
+        let subject = match self.peek() {
+            Some('[') => {
+                let subject = self.parse_blank_node_properties()?;
+                self.skip_whitespace();
+                if self.peek() == Some('.') {
+                    self.next();
+                    return Ok(());
+                }
+                subject
+            }
+            Some('(') => self.parse_collection()?,
+            _ => self.parse_resource()?,
+        };
+        self.parse_predicate_objects(&subject)?;
+        self.expect('.')
+    }
+
+    fn parse_predicate_objects(&mut self, subject: &Term) -> Result<(), Error> {
+        loop {
+            self.skip_whitespace();
+            let predicate = if self.keyword("a", true) {
+                rdf_uri("type")
+            } else {
+                match self.parse_resource()? {
+                    Term::Iri(iri) => iri,
+                    _ => return self.error("a predicate has to be an IRI"),
+                }
+            };
+            loop {
+                let object = self.parse_object()?;
+                self.triples
+                    .push((subject.clone(), predicate.clone(), object));
+                self.skip_whitespace();
+                if self.peek() != Some(',') {
+                    break;
+                }
+                self.next();
+            }
+            if self.peek() != Some(';') {
+                return Ok(());
+            }
+            while self.peek() == Some(';') {
+                self.next();
+                self.skip_whitespace();
+            }
+            if matches!(self.peek(), Some('.') | Some(']')) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Term, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('[') => self.parse_blank_node_properties(),
+            Some('(') => self.parse_collection(),
+            Some('"') | Some('\'') => self.parse_literal(),
+            Some(c) if c.is_ascii_digit() || "+-.".contains(c) => self.parse_number(),
+            _ if self.keyword("true", true) => Ok(boolean_term(true)),
+            _ if self.keyword("false", true) => Ok(boolean_term(false)),
+            _ => self.parse_resource(),
+        }
+    }
+
+    fn parse_blank_node_properties(&mut self) -> Result<Term, Error> {
+        self.expect('[')?;
+        let subject = self.new_blank();
+        self.skip_whitespace();
+        if self.peek() != Some(']') {
+            self.parse_predicate_objects(&subject)?;
+        }
+        self.expect(']')?;
+        Ok(subject)
+    }
+
+    fn parse_collection(&mut self) -> Result<Term, Error> {
+        self.expect('(')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(')') {
+                self.next();
+                return Ok(Term::List(items));
+            }
+            if self.peek().is_none() {
+                return self.error("unterminated collection");
+            }
+            items.push(self.parse_object()?);
+        }
+    }
+
+    /// Parse an IRI, a prefixed name or a labeled blank node.
+    fn parse_resource(&mut self) -> Result<Term, Error> {
+        self.skip_whitespace();
+        if self.peek() == Some('<') {
+            return self.parse_iri_ref().map(Term::Iri);
+        }
+        if self.rest().starts_with("_:") {
+            self.position += 2;
+            let label = self.parse_name(false);
+            if label.is_empty() {
+                return self.error("empty blank node label");
+            }
+            let count = self.blank_count;
+            let id = *self.blank_labels.entry(label).or_insert(count);
+            if id == count {
+                self.blank_count += 1;
+            }
+            return Ok(Term::Blank(id));
+        }
+
+        let prefix = self.parse_name(false);
+        if self.next() != Some(':') {
+            return self.error("expected an IRI or a prefixed name");
+        }
+        let local = self.parse_name(true);
+        match self.prefixes.get(&prefix) {
+            Some(namespace) => Ok(Term::Iri(format!("{}{}", namespace, local))),
+            None => self.error(&format!("undefined prefix \"{}\"", prefix)),
+        }
+    }
+
+    /// Parse a part of a prefixed name or a blank node label.
+    ///
+    /// Only the local part of a prefixed name may contain colons.
+    fn parse_name(&mut self, local: bool) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            let continued = self.rest()[c.len_utf8()..]
+                .chars()
+                .next()
+                .map(is_name_char)
+                .unwrap_or(false);
+            if is_name_char(c) || (local && c == ':') || (c == '.' && continued) {
+                name.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        name
+    }
+
+    fn parse_iri_ref(&mut self) -> Result<String, Error> {
+        self.expect('<')?;
+        let mut iri = String::new();
+        loop {
+            match self.next() {
+                Some('>') => break,
+                Some('\\') => iri.push(self.parse_unicode_escape()?),
+                Some(c) if c > ' ' => iri.push(c),
+                _ => return self.error("unterminated IRI"),
+            }
+        }
+        match self.base.as_ref() {
+            Some(base) if !iri.contains(':') => Ok(format!("{}{}", base, iri)),
+            _ => Ok(iri),
+        }
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, Error> {
+        let length = match self.next() {
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return self.error("invalid escape sequence"),
+        };
+        let hex = self.rest().get(..length).unwrap_or("");
+        match u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(std::char::from_u32)
+        {
+            Some(c) if hex.len() == length => {
+                self.position += length;
+                Ok(c)
+            }
+            _ => self.error("invalid unicode escape sequence"),
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<Term, Error> {
+        let quote = self.next().unwrap();
+        let end = quote.to_string().repeat(3);
+        let long = if self.rest().starts_with(&end[1..]) {
+            self.position += 2;
+            true
+        } else {
+            false
+        };
+
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some(c) if c == quote => {
+                    if !long {
+                        break;
+                    }
+                    if self.rest().starts_with(&end[1..]) {
+                        self.position += 2;
+                        break;
+                    }
+                    text.push(c);
+                }
+                Some('\\') => match self.peek() {
+                    Some('u') | Some('U') => text.push(self.parse_unicode_escape()?),
+                    Some(c) => {
+                        self.next();
+                        text.push(match c {
+                            't' => '\t',
+                            'b' => '\u{8}',
+                            'n' => '\n',
+                            'r' => '\r',
+                            'f' => '\u{c}',
+                            '"' | '\'' | '\\' => c,
+                            _ => return self.error("invalid escape sequence"),
+                        });
+                    }
+                    None => return self.error("unterminated string"),
+                },
+                Some('\n') | Some('\r') if !long => return self.error("line break in a string"),
+                Some(c) => text.push(c),
+                None => return self.error("unterminated string"),
+            }
+        }
+
+        let mut literal = Literal {
+            text,
+            ..Default::default()
+        };
+        if self.peek() == Some('@') {
+            self.next();
+            let mut language = String::new();
+            while let Some(c) = self
+                .peek()
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            {
+                language.push(c);
+                self.next();
+            }
+            if language.is_empty() {
+                return self.error("empty language tag");
+            }
+            literal.language = Some(language);
+        } else if self.rest().starts_with("^^") {
+            self.position += 2;
+            match self.parse_resource()? {
+                Term::Iri(datatype) => literal.datatype = Some(datatype),
+                _ => return self.error("a datatype has to be an IRI"),
+            }
+        }
+        Ok(Term::Literal(literal))
+    }
+
+    fn parse_number(&mut self) -> Result<Term, Error> {
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let mut count = 0;
+            while parser.peek().filter(char::is_ascii_digit).is_some() {
+                parser.next();
+                count += 1;
+            }
+            count
+        };
+
+        if matches!(self.peek(), Some('+') | Some('-')) {
+            self.next();
+        }
+        let mut count = digits(self);
+        let mut datatype = "integer";
+        let fraction = self.rest().starts_with('.')
+            && self.rest()[1..]
+                .chars()
+                .next()
+                .filter(char::is_ascii_digit)
+                .is_some();
+        if fraction {
+            self.next();
+            count += digits(self);
+            datatype = "decimal";
+        }
+        if matches!(self.peek(), Some('e') | Some('E')) {
+            self.next();
+            if matches!(self.peek(), Some('+') | Some('-')) {
+                self.next();
+            }
+            if digits(self) == 0 {
+                return self.error("malformed exponent");
+            }
+            datatype = "double";
+        }
+        if count == 0 {
+            return self.error("malformed number");
+        }
+        Ok(Term::Literal(Literal {
+            text: self.text[start..self.position].to_owned(),
+            datatype: Some(xsd_uri(datatype)),
+            language: None,
+        }))
+    }
+}
+
+fn boolean_term(value: bool) -> Term {
+    Term::Literal(Literal {
+        text: value.to_string(),
+        datatype: Some(xsd_uri("boolean")),
+        language: None,
+    })
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '%'
+}
+
+/// The triples of a parsed Turtle document.
+struct Graph {
+    triples: Vec<(Term, String, Term)>,
+}
+
+impl Graph {
+    /// Find the value of the anonymous root node, which isn't the object of any triple.
+    fn root(&self) -> Result<Node, Error> {
+        let mut objects = Vec::new();
+        for (_, _, object) in self.triples.iter() {
+            collect_terms(object, &mut objects);
+        }
+        let mut roots = self.triples.iter().filter(|(subject, predicate, _)| {
+            matches!(subject, Term::Blank(_))
+                && *predicate == rdf_uri("value")
+                && !objects.contains(&subject)
+        });
+        match (roots.next(), roots.next()) {
+            (Some((_, _, object)), None) => self.node(object, 0),
+            _ => Err(invalid("the document doesn't describe exactly one atom")),
+        }
+    }
+
+    fn node(&self, term: &Term, depth: usize) -> Result<Node, Error> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("the document is nested too deeply"));
+        }
+        Ok(match term {
+            Term::Iri(iri) if !self.triples.iter().any(|(subject, _, _)| subject == term) => {
+                Node::Iri(iri.clone())
+            }
+            Term::Iri(_) | Term::Blank(_) => {
+                let mut resource = Resource {
+                    id: match term {
+                        Term::Iri(iri) => Some(iri.clone()),
+                        _ => None,
+                    },
+                    properties: Vec::new(),
+                };
+                for (_, predicate, object) in self
+                    .triples
+                    .iter()
+                    .filter(|(subject, _, _)| subject == term)
+                {
+                    resource
+                        .properties
+                        .push((predicate.clone(), self.node(object, depth + 1)?));
+                }
+                Node::Resource(resource)
+            }
+            Term::Literal(literal) => Node::Literal(literal.clone()),
+            Term::List(items) => Node::List(
+                items
+                    .iter()
+                    .map(|item| self.node(item, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+fn collect_terms<'a>(term: &'a Term, terms: &mut Vec<&'a Term>) {
+    terms.push(term);
+    if let Term::List(items) = term {
+        for item in items {
+            collect_terms(item, terms);
+        }
+    }
+}
+
+/// A parsed JSON value.
+///
+/// Numbers are kept as text, since their datatype depends on their representation.
+#[derive(Clone, PartialEq, Debug)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn string(text: &str) -> Self {
+        Json::String(text.to_owned())
+    }
+}
+
+fn node_to_json(node: &Node) -> Json {
+    match node {
+        Node::Iri(iri) => Json::Object(vec![("@id".to_owned(), Json::string(iri))]),
+        Node::Literal(literal) => match (&literal.datatype, &literal.language) {
+            (None, None) => Json::string(&literal.text),
+            (_, Some(language)) => Json::Object(vec![
+                ("@value".to_owned(), Json::string(&literal.text)),
+                ("@language".to_owned(), Json::string(language)),
+            ]),
+            (Some(datatype), None) => Json::Object(vec![
+                ("@value".to_owned(), Json::string(&literal.text)),
+                ("@type".to_owned(), Json::string(datatype)),
+            ]),
+        },
+        Node::List(nodes) => Json::Object(vec![(
+            "@list".to_owned(),
+            Json::Array(nodes.iter().map(node_to_json).collect()),
+        )]),
+        Node::Resource(resource) => {
+            let mut members = Vec::new();
+            if let Some(id) = resource.id.as_ref() {
+                members.push(("@id".to_owned(), Json::string(id)));
+            }
+            for (predicate, node) in resource.properties.iter() {
+                match node {
+                    Node::Iri(otype) if *predicate == rdf_uri("type") => {
+                        members.push(("@type".to_owned(), Json::string(otype)));
+                    }
+                    _ => members.push((predicate.clone(), node_to_json(node))),
+                }
+            }
+            Json::Object(members)
+        }
+    }
+}
+
+fn json_to_node(json: &Json, depth: usize) -> Result<Node, Error> {
+    if depth > MAX_DEPTH {
+        return Err(invalid("the document is nested too deeply"));
+    }
+    let string = |json: Option<&Json>| match json {
+        Some(Json::String(text)) => Ok(Some(text.clone())),
+        None => Ok(None),
+        Some(_) => Err(invalid("a JSON keyword with a value that isn't a string")),
+    };
+
+    Ok(match json {
+        Json::Null => return Err(invalid("a null value")),
+        Json::Bool(value) => Node::typed(value.to_string(), xsd_uri("boolean")),
+        Json::Number(text) => {
+            let datatype = if text.contains(|c| ".eE".contains(c)) {
+                "double"
+            } else {
+                "integer"
+            };
+            Node::typed(text.clone(), xsd_uri(datatype))
+        }
+        Json::String(text) => Node::Literal(Literal {
+            text: text.clone(),
+            ..Default::default()
+        }),
+        Json::Array(_) => return Err(invalid("an array outside of a property")),
+        Json::Object(members) => {
+            if let Some(value) = json.get("@value") {
+                let text = match value {
+                    Json::String(text) | Json::Number(text) => text.clone(),
+                    Json::Bool(value) => value.to_string(),
+                    _ => return Err(invalid("a literal with a value that isn't a string")),
+                };
+                return Ok(Node::Literal(Literal {
+                    text,
+                    datatype: string(json.get("@type"))?,
+                    language: string(json.get("@language"))?,
+                }));
+            }
+            if let Some(items) = json.get("@list") {
+                return match items {
+                    Json::Array(items) => Ok(Node::List(
+                        items
+                            .iter()
+                            .map(|item| json_to_node(item, depth + 1))
+                            .collect::<Result<_, _>>()?,
+                    )),
+                    _ => Err(invalid("a list that isn't an array")),
+                };
+            }
+            let id = string(json.get("@id"))?;
+            if let (Some(id), 1) = (id.as_ref(), members.len()) {
+                return Ok(Node::Iri(id.clone()));
+            }
+
+            let mut resource = Resource {
+                id,
+                properties: Vec::new(),
+            };
+            for (key, value) in members.iter() {
+                let values = match value {
+                    Json::Array(values) => values.as_slice(),
+                    value => std::slice::from_ref(value),
+                };
+                for value in values {
+                    match key.as_str() {
+                        "@id" => (),
+                        "@type" => match value {
+                            Json::String(otype) => resource
+                                .properties
+                                .push((rdf_uri("type"), Node::Iri(otype.clone()))),
+                            _ => return Err(invalid("a type that isn't a string")),
+                        },
+                        key if key.starts_with('@') => {
+                            return Err(Error::Invalid(format!("unsupported keyword {}", key)))
+                        }
+                        key => resource
+                            .properties
+                            .push((key.to_owned(), json_to_node(value, depth + 1)?)),
+                    }
+                }
+            }
+            Node::Resource(resource)
+        }
+    })
+}
+
+fn write_json_string(text: &str, output: &mut String) {
+    output.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c < ' ' => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn write_json(json: &Json, depth: usize, output: &mut String) {
+    let indent = |depth: usize, output: &mut String| {
+        output.push('\n');
+        for _ in 0..depth {
+            output.push_str("  ");
+        }
+    };
+    match json {
+        Json::Null => output.push_str("null"),
+        Json::Bool(value) => output.push_str(&value.to_string()),
+        Json::Number(text) => output.push_str(text),
+        Json::String(text) => write_json_string(text, output),
+        Json::Array(items) if items.is_empty() => output.push_str("[]"),
+        Json::Array(items) => {
+            output.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                indent(depth + 1, output);
+                write_json(item, depth + 1, output);
+            }
+            indent(depth, output);
+            output.push(']');
+        }
+        Json::Object(members) if members.is_empty() => output.push_str("{}"),
+        Json::Object(members) => {
+            output.push('{');
+            for (i, (key, value)) in members.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                indent(depth + 1, output);
+                write_json_string(key, output);
+                output.push_str(": ");
+                write_json(value, depth + 1, output);
+            }
+            indent(depth, output);
+            output.push('}');
+        }
+    }
+}
+
+/// A parser for JSON documents.
+struct JsonParser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, position: 0 }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, Error> {
+        Err(Error::Syntax {
+            line: self.text[..self.position].matches('\n').count() + 1,
+            message: message.to_owned(),
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().filter(|c| c.is_whitespace()).is_some() {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", expected))
+        }
+    }
+
+    fn parse(mut self) -> Result<Json, Error> {
+        let json = self.parse_value(0)?;
+        self.skip_whitespace();
+        if self.peek().is_some() {
+            return self.error("trailing characters");
+        }
+        Ok(json)
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Json, Error> {
+        if depth > MAX_DEPTH {
+            return self.error("the document is nested too deeply");
+        }
+        self.skip_whitespace();
+        for (keyword, json) in [
+            ("null", Json::Null),
+            ("true", Json::Bool(true)),
+            ("false", Json::Bool(false)),
+        ] {
+            if self.rest().starts_with(keyword) {
+                self.position += keyword.len();
+                return Ok(json);
+            }
+        }
+        match self.peek() {
+            Some('"') => self.parse_string().map(Json::String),
+            Some('[') => {
+                self.next();
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.next();
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.parse_value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => (),
+                        Some(']') => return Ok(Json::Array(items)),
+                        _ => return self.error("expected ',' or ']'"),
+                    }
+                }
+            }
+            Some('{') => {
+                self.next();
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.next();
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.parse_string()?;
+                    self.expect(':')?;
+                    members.push((key, self.parse_value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => (),
+                        Some('}') => return Ok(Json::Object(members)),
+                        _ => return self.error("expected ',' or '}'"),
+                    }
+                }
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.position;
+                while self
+                    .peek()
+                    .filter(|c| c.is_ascii_digit() || "+-.eE".contains(*c))
+                    .is_some()
+                {
+                    self.next();
+                }
+                let text = &self.text[start..self.position];
+                if text.parse::<f64>().is_err() {
+                    return self.error("malformed number");
+                }
+                Ok(Json::Number(text.to_owned()))
+            }
+            _ => self.error("expected a value"),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, Error> {
+        if self.next() != Some('"') {
+            return self.error("expected a string");
+        }
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.parse_unicode_escape()?,
+                        _ => return self.error("invalid escape sequence"),
+                    };
+                    text.push(c);
+                }
+                Some(c) if c >= ' ' => text.push(c),
+                _ => return self.error("unterminated string"),
+            }
+        }
+    }
+
+    fn parse_code_unit(&mut self) -> Option<u32> {
+        let hex = self.rest().get(..4)?;
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        self.position += 4;
+        Some(value)
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, Error> {
+        let high = match self.parse_code_unit() {
+            Some(high) => high,
+            None => return self.error("invalid unicode escape sequence"),
+        };
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.rest().starts_with("\\u") {
+                return self.error("unpaired surrogate");
+            }
+            self.position += 2;
+            let low = match self.parse_code_unit() {
+                Some(low) if (0xDC00..0xE000).contains(&low) => low,
+                _ => return self.error("unpaired surrogate"),
+            };
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        match std::char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.error("invalid unicode escape sequence"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rdf::*;
+
+    #[test]
+    fn test_turtle_shape() {
+        let map = HashURIDMapper::new();
+        let value = AtomValue::Object(ObjectValue {
+            id: None,
+            otype: map.map_str("urn:test#Type").unwrap(),
+            properties: [
+                (
+                    map.map_str("urn:test#tuple").unwrap(),
+                    AtomValue::Tuple(vec![AtomValue::Int(42), AtomValue::Double(1.0)]),
+                ),
+                (
+                    map.map_str("urn:test#name").unwrap(),
+                    AtomValue::String("a \"name\"".to_owned()),
+                ),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        });
+
+        let text = value_to_turtle(&value, &map).unwrap();
+        let expected = "@prefix atom: <http://lv2plug.in/ns/ext/atom#> .
+@prefix midi: <http://lv2plug.in/ns/ext/midi#> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+[]
+\trdf:value [
+\t\ta <urn:test#Type> ;
+\t\t<urn:test#tuple> [
+\t\t\ta atom:Tuple ;
+\t\t\trdf:value (
+\t\t\t\t\"42\"^^xsd:int
+\t\t\t\t1.0
+\t\t\t)
+\t\t] ;
+\t\t<urn:test#name> \"a \\\"name\\\"\"
+\t] .
+";
+        assert_eq!(expected, text);
+        assert_eq!(value, value_from_turtle(&text, &map).unwrap());
+    }
+
+    #[test]
+    fn test_parse_turtle() {
+        let map = HashURIDMapper::new();
+        let text = "
+            # A hand-written document.
+            PREFIX eg: <urn:test#>
+            @prefix atom: <http://lv2plug.in/ns/ext/atom#> .
+
+            _:root <http://www.w3.org/1999/02/22-rdf-syntax-ns#value> eg:object .
+
+            eg:object a eg:Type ;
+                eg:number 17, 18 ;
+                eg:flag true ;
+                eg:text '''multi
+line''' ;
+                eg:label \"Hallo\"@de ;
+                eg:empty [ a atom:Tuple ; <http://www.w3.org/1999/02/22-rdf-syntax-ns#value> () ] ;
+                .
+        ";
+        let value = match value_from_turtle(text, &map).unwrap() {
+            AtomValue::Object(object) => object,
+            _ => panic!("Not an object!"),
+        };
+        let property = |name: &str| {
+            let key = map.map_str(&format!("urn:test#{}", name)).unwrap();
+            value.properties.get(&key).cloned().unwrap()
+        };
+
+        assert_eq!(Some(map.map_str("urn:test#object").unwrap()), value.id);
+        assert_eq!(map.map_str("urn:test#Type").unwrap(), value.otype);
+        assert_eq!(5, value.properties.len());
+        assert_eq!(AtomValue::Long(18), property("number"));
+        assert_eq!(AtomValue::Bool(true), property("flag"));
+        assert_eq!(
+            AtomValue::String("multi\nline".to_owned()),
+            property("text")
+        );
+        assert_eq!(
+            AtomValue::Literal(
+                LiteralInfo::Language(map.map_str("http://lexvo.org/id/iso639-1/de").unwrap()),
+                "Hallo".to_owned()
+            ),
+            property("label")
+        );
+        assert_eq!(AtomValue::Tuple(Vec::new()), property("empty"));
+
+        match value_from_turtle("[] <urn:test#value> eg:missing .", &map) {
+            Err(Error::Syntax { line: 1, .. }) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert!(value_from_turtle("[] <urn:test#value> 1 .", &map).is_err());
+
+        // Non-ASCII text where a keyword is expected.
+        assert!(value_from_turtle("abcdeé", &map).is_err());
+        assert!(value_from_turtle(
+            "[] <http://www.w3.org/1999/02/22-rdf-syntax-ns#value> tru€ .",
+            &map
+        )
+        .is_err());
+        assert_eq!(
+            AtomValue::String("Grüße".to_owned()),
+            value_from_turtle(
+                "[] <http://www.w3.org/1999/02/22-rdf-syntax-ns#value> \"Grüße\" .",
+                &map
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let map = HashURIDMapper::new();
+        let text = r#"{
+            "@type": "http://lv2plug.in/ns/ext/atom#Tuple",
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#value": {
+                "@list": [1, 0.5, true, "\ud83c\udfb5", {"@id": "urn:test#id"}]
+            }
+        }"#;
+
+        assert_eq!(
+            AtomValue::Tuple(vec![
+                AtomValue::Long(1),
+                AtomValue::Double(0.5),
+                AtomValue::Bool(true),
+                AtomValue::String("\u{1f3b5}".to_owned()),
+                AtomValue::URID(map.map_str("urn:test#id").unwrap()),
+            ]),
+            value_from_json(text, &map).unwrap()
+        );
+        assert!(value_from_json("{\"@list\": [1, 2}", &map).is_err());
+    }
+}
@@ -67,6 +67,7 @@
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Sequence](http://lv2plug.in/ns/ext/atom/atom.html#Sequence)
 use crate::space::*;
 use crate::*;
+use std::ops::Range;
 use sys::LV2_Atom_Event__bindgen_ty_1 as RawTimeStamp;
 use units::prelude::*;
 use urid::*;
@@ -130,7 +131,7 @@ pub enum TimeStampUnit {
 }
 
 /// An event time stamp.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum TimeStamp {
     Frames(i64),
     BeatsPerMinute(f64),
@@ -169,6 +170,7 @@ impl TimeStamp {
 }
 
 /// An iterator over all events in a sequence.
+#[derive(Clone)]
 pub struct SequenceIterator<'a> {
     space: Space<'a>,
     unit: TimeStampUnit,
@@ -195,9 +197,183 @@ impl<'a> Iterator for SequenceIterator<'a> {
     }
 }
 
+/// Process a block of frames in sub-blocks, split at the time stamps of the events of a sequence.
+///
+/// Plugins that react to events with sample accuracy have to process the frames between two events, apply the next event and continue with the frames after it. This function does the bookkeeping: It calls `process` for every [`SubBlock`](struct.SubBlock.html), which covers the frames from one event time stamp to the next and contains the events that occur at its first frame. Before, the sampled ports of the port collection, like audio and CV ports, are restricted to the frames of the sub-block. The sub-blocks are consecutive and cover all `sample_count` frames:
+///
+///     use lv2_atom::prelude::*;
+///     use lv2_core::prelude::*;
+///     use lv2_units::prelude::*;
+///     use urid::*;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         control: InputPort<AtomPort>,
+///         input: InputPort<Audio>,
+///         output: OutputPort<Audio>,
+///     }
+///
+///     #[derive(URIDCollection)]
+///     struct URIDs {
+///         atom: AtomURIDCollection,
+///         units: UnitURIDCollection,
+///     }
+///
+///     /// Something like a plugin's run method.
+///     fn run(ports: &mut Ports, urids: &URIDs, gain: &mut f32) {
+///         let sequence = ports.control.read(urids.atom.sequence, urids.units.beat).unwrap();
+///         split_at_events(sequence, ports.input.len(), ports, |ports, block| {
+///             // Apply the new gain values before the frames of the block are processed.
+///             for (_, atom) in block.events() {
+///                 if let Some(new_gain) = atom.read(urids.atom.float, ()) {
+///                     *gain = new_gain;
+///                 }
+///             }
+///             for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+///                 *output = *input * *gain;
+///             }
+///         });
+///     }
+///
+/// Time stamps are clamped to the block. Events that aren't in order are applied at the start of the current sub-block, and so are all events of sequences with time stamps in beats. After the last sub-block, the ports are restored to the whole block.
+///
+/// If there is no port collection to slice, the sub-blocks can also be iterated with an [`EventSplitter`](struct.EventSplitter.html).
+///
+/// # Panics
+///
+/// This function panics if a sampled port has less than `sample_count` frames.
+#[cfg(feature = "lv2-core")]
+pub fn split_at_events<'a, P, F>(
+    sequence: SequenceIterator<'a>,
+    sample_count: usize,
+    ports: &mut P,
+    mut process: F,
+) where
+    P: lv2_core::port::SlicePorts,
+    F: FnMut(&mut P, SubBlock<'a>),
+{
+    // The copy of the ports is only used as the source of the sliced ports and never dropped.
+    let mut block = std::mem::ManuallyDrop::new(unsafe { std::ptr::read(ports) });
+    for sub_block in EventSplitter::new(sequence, sample_count) {
+        unsafe { ports.slice_from(&mut block, sub_block.range()) };
+        process(ports, sub_block);
+    }
+    unsafe { ports.slice_from(&mut block, 0..sample_count) };
+}
+
+/// An iterator over the sub-blocks between the events of a sequence.
+///
+/// This iterator splits a block of frames like [`split_at_events`](fn.split_at_events.html), but doesn't slice any ports. Instead, the buffers of sampled ports can be sliced with the sub-blocks.
+pub struct EventSplitter<'a> {
+    events: SequenceIterator<'a>,
+    sample_count: usize,
+    position: usize,
+    finished: bool,
+}
+
+impl<'a> EventSplitter<'a> {
+    /// Create a new iterator over the sub-blocks of `sample_count` frames between the events of the sequence.
+    pub fn new(sequence: SequenceIterator<'a>, sample_count: usize) -> Self {
+        Self {
+            events: sequence,
+            sample_count,
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Return the frame of a time stamp, relative to the current position.
+    fn frame(&self, stamp: TimeStamp) -> usize {
+        match stamp.as_frames() {
+            Some(frame) if frame > 0 => (frame as usize).max(self.position).min(self.sample_count),
+            _ => self.position,
+        }
+    }
+}
+
+impl<'a> Iterator for EventSplitter<'a> {
+    type Item = SubBlock<'a>;
+
+    fn next(&mut self) -> Option<SubBlock<'a>> {
+        if self.finished {
+            return None;
+        }
+
+        let events = self.events.clone();
+        let mut event_count = 0;
+        let mut end = self.sample_count;
+        let mut lookahead = self.events.clone();
+        while let Some((stamp, _)) = lookahead.next() {
+            let frame = self.frame(stamp);
+            if frame > self.position {
+                end = frame;
+                break;
+            }
+            event_count += 1;
+            self.events = lookahead.clone();
+        }
+
+        if event_count == 0 && self.position == end {
+            self.finished = true;
+            return None;
+        }
+
+        let block = SubBlock {
+            start: self.position,
+            end,
+            events,
+            event_count,
+        };
+        self.position = end;
+        Some(block)
+    }
+}
+
+/// A range of frames, together with the events that occur at its start.
+///
+/// Sub-blocks are created by [`split_at_events`](fn.split_at_events.html) and [`EventSplitter`](struct.EventSplitter.html).
+pub struct SubBlock<'a> {
+    start: usize,
+    end: usize,
+    events: SequenceIterator<'a>,
+    event_count: usize,
+}
+
+impl<'a> SubBlock<'a> {
+    /// Return the range of frames of this sub-block.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Return the number of frames of this sub-block.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Return `true` if the sub-block doesn't contain any frames, which may happen if events are at the very end of the block.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Iterate over the events that have to be applied before the frames of this sub-block are processed.
+    pub fn events(&self) -> impl Iterator<Item = (TimeStamp, UnidentifiedAtom<'a>)> {
+        self.events.clone().take(self.event_count)
+    }
+
+    /// Return the part of a sampled input, like an audio or CV input, that belongs to this sub-block.
+    pub fn input<'b>(&self, buffer: &'b [f32]) -> &'b [f32] {
+        &buffer[self.range()]
+    }
+
+    /// Return the part of a sampled output, like an audio or CV output, that belongs to this sub-block.
+    pub fn output<'b>(&self, buffer: &'b mut [f32]) -> &'b mut [f32] {
+        &mut buffer[self.range()]
+    }
+}
+
 /// The writing handle for sequences.
 pub struct SequenceWriter<'a, 'b> {
-    frame: FramedMutSpace<'a, 'b>,
+    pub(crate) frame: FramedMutSpace<'a, 'b>,
     unit: TimeStampUnit,
     last_stamp: Option<TimeStamp>,
 }
@@ -209,7 +385,7 @@ impl<'a, 'b> SequenceWriter<'a, 'b> {
     /// * The time stamp is not measured in our unit.
     /// * The last time stamp is younger than the time stamp.
     /// * Space is insufficient.
-    fn write_time_stamp(&mut self, stamp: TimeStamp) -> Option<()> {
+    pub(crate) fn write_time_stamp(&mut self, stamp: TimeStamp) -> Option<()> {
         let raw_stamp = match self.unit {
             TimeStampUnit::Frames => {
                 let frames = stamp.as_frames()?;
@@ -266,6 +442,7 @@ mod tests {
     use crate::prelude::*;
     use crate::sequence::*;
     use std::mem::size_of;
+    use std::ops::Range;
     use sys::LV2_Atom_Event__bindgen_ty_1 as RawTimeStamp;
 
     #[derive(URIDCollection)]
@@ -361,4 +538,121 @@ mod tests {
             assert!(reader.next().is_none());
         }
     }
+
+    #[test]
+    fn test_split_at_events() {
+        let map = HashURIDMapper::new();
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            for (frame, value) in [(2, 1), (2, 2), (5, 3), (12, 4)].iter() {
+                writer
+                    .init::<Int>(TimeStamp::Frames(*frame), urids.atom.int, *value)
+                    .unwrap();
+            }
+        }
+
+        let space = Space::from_slice(raw_space.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+        let sequence = Sequence::read(body, urids.units.beat).unwrap();
+
+        let blocks: Vec<(Range<usize>, Vec<i32>)> = EventSplitter::new(sequence, 8)
+            .map(|block| {
+                let values = block
+                    .events()
+                    .map(|(_, atom)| atom.read(urids.atom.int, ()).unwrap())
+                    .collect();
+                (block.range(), values)
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (0..2, vec![]),
+                (2..5, vec![1, 2]),
+                (5..8, vec![3]),
+                (8..8, vec![4]),
+            ],
+            blocks
+        );
+
+        let samples: Vec<f32> = (0..8).map(|sample| sample as f32).collect();
+        let sequence = Sequence::read(body, urids.units.beat).unwrap();
+        let block = EventSplitter::new(sequence, 8).nth(1).unwrap();
+        assert_eq!(&[2.0, 3.0, 4.0], block.input(&samples));
+    }
+
+    #[test]
+    fn test_split_ports_at_events() {
+        use lv2_core::port::*;
+
+        #[derive(PortCollection)]
+        struct SplitPorts {
+            gain: InputPort<Control>,
+            input: InputPort<Audio>,
+            output: OutputPort<Audio>,
+        }
+
+        let map = HashURIDMapper::new();
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            for (frame, value) in [(3, 2), (6, 3)].iter() {
+                writer
+                    .init::<Int>(TimeStamp::Frames(*frame), urids.atom.int, *value)
+                    .unwrap();
+            }
+        }
+        let space = Space::from_slice(raw_space.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+        let sequence = Sequence::read(body, urids.units.beat).unwrap();
+
+        let mut gain = 0.5f32;
+        let mut input: Vec<f32> = (0..8).map(|sample| sample as f32).collect();
+        let mut output = vec![0.0f32; 8];
+        let mut cache = <SplitPorts as PortCollection>::Cache::default();
+        cache.connect(0, &mut gain as *mut f32 as *mut _);
+        cache.connect(1, input.as_mut_ptr() as *mut _);
+        cache.connect(2, output.as_mut_ptr() as *mut _);
+        let mut ports = unsafe { SplitPorts::from_connections(&cache, 8) }.unwrap();
+
+        let mut factor = 1;
+        let mut blocks = Vec::new();
+        split_at_events(sequence, 8, &mut ports, |ports, block| {
+            for (_, atom) in block.events() {
+                factor = atom.read(urids.atom.int, ()).unwrap();
+            }
+            blocks.push((block.range(), ports.input.to_vec(), *ports.gain));
+            for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+                *output = *input * factor as f32;
+            }
+        });
+
+        assert_eq!(
+            vec![
+                (0..3, vec![0.0, 1.0, 2.0], 0.5),
+                (3..6, vec![3.0, 4.0, 5.0], 0.5),
+                (6..8, vec![6.0, 7.0], 0.5),
+            ],
+            blocks
+        );
+        assert_eq!(8, ports.input.len());
+        assert_eq!(8, ports.output.len());
+        assert_eq!(vec![0.0, 1.0, 2.0, 6.0, 8.0, 10.0, 18.0, 21.0], output);
+    }
 }
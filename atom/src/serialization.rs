@@ -0,0 +1,1099 @@
+//! Conversion of Rust values to and from atoms with [serde](https://serde.rs).
+//!
+//! This module is only available if the `serde` feature of this crate is enabled. It maps Rust values to [`AtomValue`s](../value/enum.AtomValue.html), which can then be written to any [`MutSpace`](../space/trait.MutSpace.html), and back:
+//!
+//! * Structs are mapped to `Object` atoms. The names of their fields are turned into URIs by appending them to a prefix, for example `urn:my-plugin#` for the field `gain`, and the URIs are mapped to the keys of the properties. If a field is renamed to a complete URI with `#[serde(rename = "...")]`, this URI is used instead. The type of the object is the name of the struct, appended to the prefix. Fields with a value of `None` are omitted.
+//! * Booleans, integers, floats, strings and byte arrays are mapped to the respective scalar, string and chunk atoms. Unsigned integers that don't fit into an `Int` are mapped to `Long`s.
+//! * Sequences of booleans, numbers or URIDs are mapped to `Vector` atoms, and all other sequences and tuples are mapped to `Tuple` atoms.
+//! * Unit variants of enums are mapped to the URIDs of their names, appended to the prefix. Other variants are mapped to objects with the variant as their type, and the contents of newtype and tuple variants are stored in their `rdf:value` property.
+//!
+//! Maps aren't supported, since atom objects can only have URIDs as keys.
+//!
+//! Since the conversion allocates memory and maps URIs, it isn't real-time safe. Values are usually converted outside of the audio threading class, for example in a worker, and then written to the fixed-size buffer of a port or to a growable space.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::serialization::*;
+//! use lv2_atom::space::*;
+//! use lv2_atom::value::ValueURIDCollection;
+//! use lv2_atom::UnidentifiedAtom;
+//! use serde::{Deserialize, Serialize};
+//! use urid::*;
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! enum Mode {
+//!     Clean,
+//!     Crunch,
+//! }
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Configuration {
+//!     gain: f32,
+//!     mode: Mode,
+//!     #[serde(rename = "http://lv2plug.in/ns/lv2core#name")]
+//!     name: String,
+//!     taps: Vec<i32>,
+//! }
+//!
+//! let map = HashURIDMapper::new();
+//! let urids: ValueURIDCollection = map.populate_collection().unwrap();
+//!
+//! let configuration = Configuration {
+//!     gain: 0.5,
+//!     mode: Mode::Crunch,
+//!     name: "Preset".to_owned(),
+//!     taps: vec![1, 2, 3],
+//! };
+//!
+//! // Writing the configuration to a fixed-size buffer.
+//! let mut buffer: Box<[u8]> = Box::new([0; 256]);
+//! let mut space = RootMutSpace::new(buffer.as_mut());
+//! to_space(&configuration, &mut space, &map, "urn:my-plugin#", &urids).unwrap();
+//!
+//! // The configuration is an object.
+//! let atom = UnidentifiedAtom::new(Space::from_slice(buffer.as_ref()));
+//! let (header, _) = atom.read(urids.atom.object, ()).unwrap();
+//! assert_eq!(map.map_str("urn:my-plugin#Configuration").unwrap(), header.otype);
+//!
+//! // Reading it again.
+//! let read_configuration: Configuration = from_atom(atom, &map, "urn:my-plugin#", &urids).unwrap();
+//! assert_eq!(configuration, read_configuration);
+//! ```
+use crate::space::*;
+use crate::value::*;
+use crate::UnidentifiedAtom;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{self, Impossible, Serialize};
+use serde::{forward_to_deserialize_any, Deserialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use urid::*;
+
+/// The URI of the property that stores the contents of newtype and tuple variants.
+const RDF_VALUE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#value";
+
+/// Errors that may occur while converting values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A URI couldn't be mapped, or a URID couldn't be unmapped.
+    Mapping(String),
+    /// The value can't be represented as an atom.
+    Unsupported(&'static str),
+    /// The atom is malformed or the space is insufficient.
+    Atom,
+    /// An error reported by the serialized or deserialized type.
+    Message(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Mapping(uri) => write!(f, "failed to map or unmap {}", uri),
+            Error::Unsupported(what) => write!(f, "{} can't be represented as atoms", what),
+            Error::Atom => write!(f, "the atom is malformed or the space is insufficient"),
+            Error::Message(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Message(message.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Message(message.to_string())
+    }
+}
+
+/// Convert a value to an atom value.
+///
+/// Names are mapped to URIs by appending them to `prefix`, unless they already are complete URIs.
+pub fn to_value<T, M>(value: &T, map: &M, prefix: &str) -> Result<AtomValue, Error>
+where
+    T: Serialize + ?Sized,
+    M: Map + ?Sized,
+{
+    value
+        .serialize(ValueSerializer {
+            names: &Names {
+                mapper: map,
+                prefix,
+            },
+        })?
+        .ok_or(Error::Unsupported("None outside of a struct"))
+}
+
+/// Convert a value and write it to a space.
+///
+/// The space may be a fixed-size buffer, like the space of a port, or a growable space.
+pub fn to_space<'a, T, M>(
+    value: &T,
+    space: &mut (dyn MutSpace<'a> + '_),
+    map: &M,
+    prefix: &str,
+    urids: &ValueURIDCollection,
+) -> Result<(), Error>
+where
+    T: Serialize + ?Sized,
+    M: Map + ?Sized,
+{
+    to_value(value, map, prefix)?
+        .write(space, urids)
+        .ok_or(Error::Atom)
+}
+
+/// Convert an atom value to a value.
+///
+/// URIDs are unmapped and the prefix is removed from their URIs to retrieve the names of fields and variants.
+pub fn from_value<'de, T, U>(value: &'de AtomValue, unmap: &U, prefix: &str) -> Result<T, Error>
+where
+    T: Deserialize<'de>,
+    U: Unmap + ?Sized,
+{
+    T::deserialize(ValueDeserializer {
+        value,
+        names: &Names {
+            mapper: unmap,
+            prefix,
+        },
+    })
+}
+
+/// Read an atom and convert it to a value.
+pub fn from_atom<T, U>(
+    atom: UnidentifiedAtom,
+    unmap: &U,
+    prefix: &str,
+    urids: &ValueURIDCollection,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+    U: Unmap + ?Sized,
+{
+    let value = AtomValue::read(atom, urids).ok_or(Error::Atom)?;
+    from_value(&value, unmap, prefix)
+}
+
+/// Conversion between names and URIDs.
+struct Names<'a, T: ?Sized> {
+    mapper: &'a T,
+    prefix: &'a str,
+}
+
+impl<'a, M: Map + ?Sized> Names<'a, M> {
+    fn map(&self, name: &str) -> Result<URID, Error> {
+        let uri = if name.contains(':') {
+            name.to_owned()
+        } else {
+            format!("{}{}", self.prefix, name)
+        };
+        self.mapper.map_str(&uri).ok_or(Error::Mapping(uri))
+    }
+}
+
+impl<'a, U: Unmap + ?Sized> Names<'a, U> {
+    fn unmap(&self, urid: URID) -> Result<String, Error> {
+        let uri = self
+            .mapper
+            .unmap(urid)
+            .and_then(|uri| uri.to_str().ok())
+            .ok_or_else(|| Error::Mapping(format!("URID {}", urid.get())))?;
+        Ok(uri.strip_prefix(self.prefix).unwrap_or(uri).to_owned())
+    }
+}
+
+/// Turn the elements of a sequence into a vector if possible, or into a tuple otherwise.
+fn sequence_value(elements: Vec<AtomValue>) -> AtomValue {
+    fn collect<T>(
+        elements: &[AtomValue],
+        element: impl Fn(&AtomValue) -> Option<T>,
+        vector: impl Fn(Vec<T>) -> VectorValue,
+    ) -> Option<VectorValue> {
+        elements
+            .iter()
+            .map(element)
+            .collect::<Option<_>>()
+            .map(vector)
+    }
+
+    let vector = match elements.first() {
+        Some(AtomValue::Bool(_)) => collect(
+            &elements,
+            |element| match element {
+                AtomValue::Bool(value) => Some(*value),
+                _ => None,
+            },
+            VectorValue::Bool,
+        ),
+        Some(AtomValue::Int(_)) => collect(
+            &elements,
+            |element| match element {
+                AtomValue::Int(value) => Some(*value),
+                _ => None,
+            },
+            VectorValue::Int,
+        ),
+        Some(AtomValue::Long(_)) => collect(
+            &elements,
+            |element| match element {
+                AtomValue::Long(value) => Some(*value),
+                _ => None,
+            },
+            VectorValue::Long,
+        ),
+        Some(AtomValue::Float(_)) => collect(
+            &elements,
+            |element| match element {
+                AtomValue::Float(value) => Some(*value),
+                _ => None,
+            },
+            VectorValue::Float,
+        ),
+        Some(AtomValue::Double(_)) => collect(
+            &elements,
+            |element| match element {
+                AtomValue::Double(value) => Some(*value),
+                _ => None,
+            },
+            VectorValue::Double,
+        ),
+        Some(AtomValue::URID(_)) => collect(
+            &elements,
+            |element| match element {
+                AtomValue::URID(value) => Some(*value),
+                _ => None,
+            },
+            VectorValue::URID,
+        ),
+        _ => None,
+    };
+    match vector {
+        Some(vector) => AtomValue::Vector(vector),
+        None => AtomValue::Tuple(elements),
+    }
+}
+
+/// Serializer that creates atom values.
+///
+/// `None` is returned for absent optional values.
+struct ValueSerializer<'a, M: ?Sized> {
+    names: &'a Names<'a, M>,
+}
+
+impl<'a, M: ?Sized> Clone for ValueSerializer<'a, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, M: ?Sized> Copy for ValueSerializer<'a, M> {}
+
+impl<'a, M: Map + ?Sized> ValueSerializer<'a, M> {
+    fn element<T: Serialize + ?Sized>(self, value: &T) -> Result<AtomValue, Error> {
+        value
+            .serialize(self)?
+            .ok_or(Error::Unsupported("None outside of a struct"))
+    }
+
+    fn object(self, name: &str) -> Result<ObjectSerializer<'a, M>, Error> {
+        Ok(ObjectSerializer {
+            serializer: self,
+            object: ObjectValue {
+                id: None,
+                otype: self.names.map(name)?,
+                properties: BTreeMap::new(),
+            },
+        })
+    }
+}
+
+impl<'a, M: Map + ?Sized> ser::Serializer for ValueSerializer<'a, M> {
+    type Ok = Option<AtomValue>;
+    type Error = Error;
+    type SerializeSeq = SequenceSerializer<'a, M>;
+    type SerializeTuple = SequenceSerializer<'a, M>;
+    type SerializeTupleStruct = SequenceSerializer<'a, M>;
+    type SerializeTupleVariant = SequenceSerializer<'a, M>;
+    type SerializeMap = Impossible<Option<AtomValue>, Error>;
+    type SerializeStruct = ObjectSerializer<'a, M>;
+    type SerializeStructVariant = ObjectSerializer<'a, M>;
+
+    fn serialize_bool(self, value: bool) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Bool(value)))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Option<AtomValue>, Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Option<AtomValue>, Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Int(value)))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Long(value)))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Option<AtomValue>, Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Option<AtomValue>, Error> {
+        self.serialize_i32(value.into())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Option<AtomValue>, Error> {
+        self.serialize_u64(value.into())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Option<AtomValue>, Error> {
+        if value <= i32::MAX as u64 {
+            self.serialize_i32(value as i32)
+        } else if value <= i64::MAX as u64 {
+            self.serialize_i64(value as i64)
+        } else {
+            Err(Error::Unsupported("integers above i64::MAX"))
+        }
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Float(value)))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Double(value)))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::String(value.to_string())))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::String(value.to_owned())))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Chunk(value.to_owned())))
+    }
+
+    fn serialize_none(self) -> Result<Option<AtomValue>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<AtomValue>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Tuple(Vec::new())))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Option<AtomValue>, Error> {
+        ser::SerializeStruct::end(self.object(name)?)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::URID(self.names.map(variant)?)))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Option<AtomValue>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Option<AtomValue>, Error> {
+        let mut object = self.object(variant)?;
+        ser::SerializeStruct::serialize_field(&mut object, RDF_VALUE, value)?;
+        ser::SerializeStruct::end(object)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SequenceSerializer<'a, M>, Error> {
+        Ok(SequenceSerializer {
+            serializer: self,
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+            kind: SequenceKind::Sequence,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SequenceSerializer<'a, M>, Error> {
+        Ok(SequenceSerializer {
+            serializer: self,
+            elements: Vec::with_capacity(len),
+            kind: SequenceKind::Tuple,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SequenceSerializer<'a, M>, Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SequenceSerializer<'a, M>, Error> {
+        Ok(SequenceSerializer {
+            serializer: self,
+            elements: Vec::with_capacity(len),
+            kind: SequenceKind::Variant(self.object(variant)?),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(Error::Unsupported("maps"))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<ObjectSerializer<'a, M>, Error> {
+        self.object(name)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<ObjectSerializer<'a, M>, Error> {
+        self.object(variant)
+    }
+}
+
+/// What a sequence of serialized elements becomes.
+enum SequenceKind<'a, M: ?Sized> {
+    /// A vector or a tuple, depending on the elements.
+    Sequence,
+    /// Always a tuple.
+    Tuple,
+    /// The value of a variant object.
+    Variant(ObjectSerializer<'a, M>),
+}
+
+/// Serializer for sequences, tuples and tuple variants.
+struct SequenceSerializer<'a, M: ?Sized> {
+    serializer: ValueSerializer<'a, M>,
+    elements: Vec<AtomValue>,
+    kind: SequenceKind<'a, M>,
+}
+
+impl<'a, M: Map + ?Sized> SequenceSerializer<'a, M> {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.elements.push(self.serializer.element(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<AtomValue>, Error> {
+        match self.kind {
+            SequenceKind::Sequence => Ok(Some(sequence_value(self.elements))),
+            SequenceKind::Tuple => Ok(Some(AtomValue::Tuple(self.elements))),
+            SequenceKind::Variant(mut object) => {
+                let key = object.serializer.names.map(RDF_VALUE)?;
+                object
+                    .object
+                    .properties
+                    .insert(key, AtomValue::Tuple(self.elements));
+                ser::SerializeStruct::end(object)
+            }
+        }
+    }
+}
+
+impl<'a, M: Map + ?Sized> ser::SerializeSeq for SequenceSerializer<'a, M> {
+    type Ok = Option<AtomValue>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<AtomValue>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a, M: Map + ?Sized> ser::SerializeTuple for SequenceSerializer<'a, M> {
+    type Ok = Option<AtomValue>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<AtomValue>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a, M: Map + ?Sized> ser::SerializeTupleStruct for SequenceSerializer<'a, M> {
+    type Ok = Option<AtomValue>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<AtomValue>, Error> {
+        self.finish()
+    }
+}
+
+impl<'a, M: Map + ?Sized> ser::SerializeTupleVariant for SequenceSerializer<'a, M> {
+    type Ok = Option<AtomValue>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<AtomValue>, Error> {
+        self.finish()
+    }
+}
+
+/// Serializer for structs and variants, which are turned into objects.
+struct ObjectSerializer<'a, M: ?Sized> {
+    serializer: ValueSerializer<'a, M>,
+    object: ObjectValue,
+}
+
+impl<'a, M: Map + ?Sized> ser::SerializeStruct for ObjectSerializer<'a, M> {
+    type Ok = Option<AtomValue>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if let Some(value) = value.serialize(self.serializer)? {
+            let key = self.serializer.names.map(key)?;
+            self.object.properties.insert(key, value);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Option<AtomValue>, Error> {
+        Ok(Some(AtomValue::Object(self.object)))
+    }
+}
+
+impl<'a, M: Map + ?Sized> ser::SerializeStructVariant for ObjectSerializer<'a, M> {
+    type Ok = Option<AtomValue>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Option<AtomValue>, Error> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+/// Deserializer that reads atom values.
+struct ValueDeserializer<'de, 'a, U: ?Sized> {
+    value: &'de AtomValue,
+    names: &'a Names<'a, U>,
+}
+
+impl<'de, 'a, U: Unmap + ?Sized> ValueDeserializer<'de, 'a, U> {
+    fn with(&self, value: &'de AtomValue) -> Self {
+        Self {
+            value,
+            names: self.names,
+        }
+    }
+
+    /// Return the object of a variant and the value of its `rdf:value` property.
+    fn variant_value(&self) -> Result<(&'de ObjectValue, Option<&'de AtomValue>), Error> {
+        match self.value {
+            AtomValue::Object(object) => {
+                let mut value = None;
+                for (key, property) in object.properties.iter() {
+                    if self.names.unmap(*key)? == RDF_VALUE {
+                        value = Some(property);
+                    }
+                }
+                Ok((object, value))
+            }
+            _ => Err(de::Error::invalid_type(self.unexpected(), &"an object")),
+        }
+    }
+
+    fn unexpected(&self) -> de::Unexpected<'static> {
+        de::Unexpected::Other(match self.value {
+            AtomValue::Bool(_) => "a bool atom",
+            AtomValue::Int(_) => "an int atom",
+            AtomValue::Long(_) => "a long atom",
+            AtomValue::Float(_) => "a float atom",
+            AtomValue::Double(_) => "a double atom",
+            AtomValue::URID(_) => "a URID atom",
+            AtomValue::String(_) => "a string atom",
+            AtomValue::Literal(..) => "a literal atom",
+            AtomValue::URI(_) => "a URI atom",
+            AtomValue::Path(_) => "a path atom",
+            AtomValue::Chunk(_) => "a chunk atom",
+            AtomValue::Vector(_) => "a vector atom",
+            AtomValue::Sound(_) => "a sound atom",
+            AtomValue::Tuple(_) => "a tuple atom",
+            AtomValue::Object(_) => "an object atom",
+            AtomValue::Sequence(_) => "a sequence atom",
+            AtomValue::Other { .. } => "an atom of unknown type",
+        })
+    }
+}
+
+impl<'de, 'a, U: Unmap + ?Sized> de::Deserializer<'de> for ValueDeserializer<'de, 'a, U> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            AtomValue::Bool(value) => visitor.visit_bool(*value),
+            AtomValue::Int(value) => visitor.visit_i32(*value),
+            AtomValue::Long(value) => visitor.visit_i64(*value),
+            AtomValue::Float(value) => visitor.visit_f32(*value),
+            AtomValue::Double(value) => visitor.visit_f64(*value),
+            AtomValue::URID(urid) => visitor.visit_string(self.names.unmap(*urid)?),
+            AtomValue::String(text) | AtomValue::Literal(_, text) => {
+                visitor.visit_borrowed_str(text)
+            }
+            AtomValue::URI(uri) => match uri.to_str() {
+                Ok(uri) => visitor.visit_borrowed_str(uri),
+                Err(_) => visitor.visit_borrowed_bytes(uri.as_bytes()),
+            },
+            AtomValue::Path(path) => match path.to_str() {
+                Some(path) => visitor.visit_borrowed_str(path),
+                None => Err(de::Error::invalid_value(self.unexpected(), &visitor)),
+            },
+            AtomValue::Chunk(data) | AtomValue::Other { body: data, .. } => {
+                visitor.visit_borrowed_bytes(data)
+            }
+            AtomValue::Vector(VectorValue::Bool(elements)) => {
+                visit_elements(elements.iter().copied(), visitor)
+            }
+            AtomValue::Vector(VectorValue::Int(elements)) => {
+                visit_elements(elements.iter().copied(), visitor)
+            }
+            AtomValue::Vector(VectorValue::Long(elements)) => {
+                visit_elements(elements.iter().copied(), visitor)
+            }
+            AtomValue::Vector(VectorValue::Float(elements)) | AtomValue::Sound(elements) => {
+                visit_elements(elements.iter().copied(), visitor)
+            }
+            AtomValue::Vector(VectorValue::Double(elements)) => {
+                visit_elements(elements.iter().copied(), visitor)
+            }
+            AtomValue::Vector(VectorValue::URID(elements)) => visit_elements(
+                elements
+                    .iter()
+                    .map(|urid| self.names.unmap(*urid))
+                    .collect::<Result<Vec<_>, _>>()?,
+                visitor,
+            ),
+            AtomValue::Tuple(elements) => visitor.visit_seq(ElementsAccess {
+                deserializer: self.with(self.value),
+                elements: elements.iter(),
+            }),
+            AtomValue::Sequence(sequence) => visitor.visit_seq(ElementsAccess {
+                deserializer: self.with(self.value),
+                elements: sequence.events.iter().map(|(_, event)| event),
+            }),
+            AtomValue::Object(object) => visitor.visit_map(PropertiesAccess {
+                deserializer: self.with(self.value),
+                properties: object.properties.iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            AtomValue::Tuple(elements) if elements.is_empty() => visitor.visit_unit(),
+            _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            AtomValue::Object(_) => visitor.visit_unit(),
+            _ => self.deserialize_unit(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// Visit the elements of a vector.
+fn visit_elements<'de, I, V>(elements: I, visitor: V) -> Result<V::Value, Error>
+where
+    I: IntoIterator,
+    I::Item: IntoDeserializer<'de, Error>,
+    V: Visitor<'de>,
+{
+    de::Deserializer::deserialize_any(
+        de::value::SeqDeserializer::new(elements.into_iter()),
+        visitor,
+    )
+}
+
+/// Access to the elements of tuples and sequences.
+struct ElementsAccess<'de, 'a, U: ?Sized, I> {
+    deserializer: ValueDeserializer<'de, 'a, U>,
+    elements: I,
+}
+
+impl<'de, 'a, U, I> SeqAccess<'de> for ElementsAccess<'de, 'a, U, I>
+where
+    U: Unmap + ?Sized,
+    I: Iterator<Item = &'de AtomValue>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.elements.next() {
+            Some(element) => seed.deserialize(self.deserializer.with(element)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Access to the properties of objects.
+struct PropertiesAccess<'de, 'a, U: ?Sized> {
+    deserializer: ValueDeserializer<'de, 'a, U>,
+    properties: std::collections::btree_map::Iter<'de, URID, AtomValue>,
+    value: Option<&'de AtomValue>,
+}
+
+impl<'de, 'a, U: Unmap + ?Sized> MapAccess<'de> for PropertiesAccess<'de, 'a, U> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.properties.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                let name = self.deserializer.names.unmap(*key)?;
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before key"))?;
+        seed.deserialize(self.deserializer.with(value))
+    }
+}
+
+impl<'de, 'a, U: Unmap + ?Sized> EnumAccess<'de> for ValueDeserializer<'de, 'a, U> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let urid = match self.value {
+            AtomValue::URID(urid) => *urid,
+            AtomValue::Object(object) => object.otype,
+            _ => {
+                return Err(de::Error::invalid_type(
+                    self.unexpected(),
+                    &"a URID or an object",
+                ))
+            }
+        };
+        let name = self.names.unmap(urid)?;
+        let variant = seed.deserialize(name.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a, U: Unmap + ?Sized> VariantAccess<'de> for ValueDeserializer<'de, 'a, U> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        match self.variant_value()? {
+            (_, Some(value)) => seed.deserialize(self.with(value)),
+            (_, None) => Err(de::Error::missing_field(RDF_VALUE)),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.variant_value()? {
+            (_, Some(value)) => de::Deserializer::deserialize_any(self.with(value), visitor),
+            (_, None) => Err(de::Error::missing_field(RDF_VALUE)),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (object, _) = self.variant_value()?;
+        visitor.visit_map(PropertiesAccess {
+            deserializer: self.with(self.value),
+            properties: object.properties.iter(),
+            value: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serialization::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Point(f64, f64);
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Empty,
+        Circle(f32),
+        Line(Point, Point),
+        Polygon { corners: Vec<Point>, closed: bool },
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Drawing {
+        title: Option<String>,
+        author: Option<String>,
+        #[serde(rename = "urn:other-namespace#layer")]
+        layer: u8,
+        size: (u32, u64),
+        shapes: Vec<Shape>,
+        #[serde(with = "bytes")]
+        thumbnail: Vec<u8>,
+    }
+
+    mod bytes {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(data)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<u8>, D::Error> {
+            <&[u8]>::deserialize(deserializer).map(|data| data.to_owned())
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let map = HashURIDMapper::new();
+        let urids: ValueURIDCollection = map.populate_collection().unwrap();
+        let prefix = "urn:drawing#";
+
+        let drawing = Drawing {
+            title: Some("Shapes".to_owned()),
+            author: None,
+            layer: 3,
+            size: (640, 480),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(2.0),
+                Shape::Line(Point(0.0, 0.0), Point(1.0, 1.0)),
+                Shape::Polygon {
+                    corners: vec![Point(0.0, 0.0), Point(0.0, 1.0), Point(1.0, 0.0)],
+                    closed: true,
+                },
+            ],
+            thumbnail: vec![1, 2, 3],
+        };
+
+        let value = to_value(&drawing, &map, prefix).unwrap();
+        let object = match &value {
+            AtomValue::Object(object) => object,
+            _ => panic!("Not an object"),
+        };
+        assert_eq!(map.map_str("urn:drawing#Drawing").unwrap(), object.otype);
+        assert_eq!(5, object.properties.len());
+        assert_eq!(
+            Some(&AtomValue::Int(3)),
+            object
+                .properties
+                .get(&map.map_str("urn:other-namespace#layer").unwrap())
+        );
+        assert_eq!(
+            Some(&AtomValue::Chunk(vec![1, 2, 3])),
+            object
+                .properties
+                .get(&map.map_str("urn:drawing#thumbnail").unwrap())
+        );
+        match object
+            .properties
+            .get(&map.map_str("urn:drawing#shapes").unwrap())
+        {
+            Some(AtomValue::Tuple(shapes)) => {
+                assert_eq!(
+                    AtomValue::URID(map.map_str("urn:drawing#Empty").unwrap()),
+                    shapes[0]
+                );
+            }
+            _ => panic!("Shapes are not a tuple"),
+        }
+
+        let read_drawing: Drawing = from_value(&value, &map, prefix).unwrap();
+        assert_eq!(drawing, read_drawing);
+
+        // Writing to and reading from a space.
+        let mut raw_space: Box<[u8]> = Box::new([0; 1024]);
+        to_space(
+            &drawing,
+            &mut RootMutSpace::new(raw_space.as_mut()),
+            &map,
+            prefix,
+            &urids,
+        )
+        .unwrap();
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        let read_drawing: Drawing = from_atom(atom, &map, prefix, &urids).unwrap();
+        assert_eq!(drawing, read_drawing);
+
+        // Sequences of numbers are vectors.
+        assert_eq!(
+            AtomValue::Vector(VectorValue::Float(vec![1.0, 2.0])),
+            to_value(&vec![1.0f32, 2.0], &map, prefix).unwrap()
+        );
+        assert_eq!(
+            vec![1.0f32, 2.0],
+            from_value::<Vec<f32>, _>(&AtomValue::Sound(vec![1.0, 2.0]), &map, prefix).unwrap()
+        );
+
+        // Errors.
+        let mut raw_space: Box<[u8]> = Box::new([0; 64]);
+        assert_eq!(
+            Err(Error::Atom),
+            to_space(
+                &drawing,
+                &mut RootMutSpace::new(raw_space.as_mut()),
+                &map,
+                prefix,
+                &urids,
+            )
+        );
+        assert_eq!(
+            Err(Error::Unsupported("maps")),
+            to_value(&std::collections::HashMap::<i32, i32>::new(), &map, prefix)
+        );
+        assert!(from_value::<Drawing, _>(&AtomValue::Int(1), &map, prefix).is_err());
+    }
+    #[test]
+    fn test_unsigned_integers() {
+        let map = HashURIDMapper::new();
+        let prefix = "urn:integers#";
+
+        // Unsigned integers are only mapped to `Long`s if they don't fit into an `Int`.
+        let int_max = i32::MAX as u32;
+        assert_eq!(
+            AtomValue::Int(i32::MAX),
+            to_value(&int_max, &map, prefix).unwrap()
+        );
+        assert_eq!(
+            AtomValue::Long(i32::MAX as i64 + 1),
+            to_value(&(int_max + 1), &map, prefix).unwrap()
+        );
+        assert_eq!(
+            AtomValue::Int(i32::MAX),
+            to_value(&(int_max as u64), &map, prefix).unwrap()
+        );
+        assert_eq!(
+            AtomValue::Long(i64::MAX),
+            to_value(&(i64::MAX as u64), &map, prefix).unwrap()
+        );
+        assert_eq!(
+            Err(Error::Unsupported("integers above i64::MAX")),
+            to_value(&u64::MAX, &map, prefix)
+        );
+
+        for value in [0, int_max, int_max + 1, u32::MAX].iter() {
+            let atom = to_value(value, &map, prefix).unwrap();
+            assert_eq!(*value, from_value::<u32, _>(&atom, &map, prefix).unwrap());
+        }
+    }
+}
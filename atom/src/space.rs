@@ -357,6 +357,170 @@ impl<'a> MutSpace<'a> for SpaceHead<'a> {
     }
 }
 
+/// A growable buffer for atoms.
+///
+/// This buffer is written with a [`VecMutSpace`](struct.VecMutSpace.html) and grows on demand, which makes it useful wherever the size of the written atoms isn't known in advance and memory may be allocated, for example in workers, when saving state or in tests.
+///
+/// Memory that has been handed out by a `MutSpace` may not move until the space isn't borrowed anymore. Therefore, the buffer allocates additional chunks of memory while it is written and only moves them into one contiguous and 64-bit-aligned slice when it is read again. The padding between atoms is preserved, so the atoms can be read like any other space:
+///
+/// ```
+/// # use lv2_atom::prelude::*;
+/// # use lv2_atom::space::*;
+/// # use urid::*;
+/// # let map = HashURIDMapper::new();
+/// let urids: AtomURIDCollection = map.populate_collection().unwrap();
+///
+/// let mut buffer = AtomBuffer::new();
+/// {
+///     let mut space = VecMutSpace::new(&mut buffer);
+///     let mut writer = (&mut space as &mut dyn MutSpace)
+///         .init(urids.vector(), urids.long)
+///         .unwrap();
+///     writer.append(&[42; 1000]).unwrap();
+/// }
+///
+/// let atom = UnidentifiedAtom::new(buffer.space());
+/// assert_eq!(&[42; 1000], atom.read(urids.vector(), urids.long).unwrap());
+/// ```
+#[derive(Default)]
+pub struct AtomBuffer {
+    /// The contiguous part of the buffer.
+    words: Vec<u64>,
+    /// The number of bytes used in the contiguous part.
+    used: usize,
+    /// Chunks that have been allocated since the buffer was made contiguous.
+    chunks: Vec<BufferChunk>,
+    /// The number of bytes used in the contiguous part and in all chunks.
+    len: usize,
+}
+
+/// A chunk of an `AtomBuffer`, together with the range of bytes used in it.
+///
+/// The used bytes start at the same offset, modulo 8, as they would have in a contiguous buffer.
+struct BufferChunk {
+    words: Vec<u64>,
+    start: usize,
+    end: usize,
+}
+
+impl AtomBuffer {
+    /// Create a new, empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new, empty buffer that can hold at least `capacity` bytes without allocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: vec![0; capacity.div_ceil(8)],
+            ..Self::default()
+        }
+    }
+
+    /// Return the number of bytes written to the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if nothing was written to the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all written data, but keep the allocated memory.
+    pub fn clear(&mut self) {
+        self.make_contiguous();
+        self.used = 0;
+        self.len = 0;
+    }
+
+    /// Return the written data.
+    ///
+    /// The returned slice is 64-bit-aligned.
+    pub fn as_bytes(&mut self) -> &[u8] {
+        self.make_contiguous();
+        let len = self.len;
+        &Self::bytes(&mut self.words)[..len]
+    }
+
+    /// Return a space containing the written data.
+    pub fn space(&mut self) -> Space<'_> {
+        Space::from_slice(self.as_bytes())
+    }
+
+    /// Move the data of all chunks into the contiguous part.
+    fn make_contiguous(&mut self) {
+        if self.chunks.is_empty() {
+            return;
+        }
+        if self.words.len() * 8 < self.len {
+            self.words.resize(self.len.div_ceil(8), 0);
+        }
+        let mut used = self.used;
+        for chunk in self.chunks.drain(..) {
+            let mut chunk_words = chunk.words;
+            let data = &Self::bytes(&mut chunk_words)[chunk.start..chunk.end];
+            Self::bytes(&mut self.words)[used..used + data.len()].copy_from_slice(data);
+            used += data.len();
+        }
+        self.used = used;
+    }
+
+    fn bytes(words: &mut Vec<u64>) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, words.len() * 8) }
+    }
+}
+
+/// A `MutSpace` that writes to a growable [`AtomBuffer`](struct.AtomBuffer.html).
+pub struct VecMutSpace<'a> {
+    buffer: &'a mut AtomBuffer,
+}
+
+impl<'a> VecMutSpace<'a> {
+    /// Create a new space that appends data to the buffer.
+    pub fn new(buffer: &'a mut AtomBuffer) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<'a> MutSpace<'a> for VecMutSpace<'a> {
+    fn allocate(&mut self, size: usize, apply_padding: bool) -> Option<(usize, &'a mut [u8])> {
+        let buffer = &mut *self.buffer;
+        let padding = if apply_padding {
+            (8 - buffer.len % 8) % 8
+        } else {
+            0
+        };
+        let required = padding + size;
+
+        // The memory of the current chunk or contiguous part is never moved while the buffer is borrowed, so only raw pointers are used to access it.
+        let fits = match buffer.chunks.last() {
+            Some(chunk) => chunk.end + required <= chunk.words.len() * 8,
+            None => buffer.used + required <= buffer.words.len() * 8,
+        };
+        if !fits {
+            let capacity = (buffer.len * 2).max(required + 8).max(64);
+            let start = buffer.len % 8;
+            buffer.chunks.push(BufferChunk {
+                words: vec![0; capacity.div_ceil(8)],
+                start,
+                end: start,
+            });
+        }
+        let (words, end) = match buffer.chunks.last_mut() {
+            Some(chunk) => (&mut chunk.words, &mut chunk.end),
+            None => (&mut buffer.words, &mut buffer.used),
+        };
+        let data = unsafe { (words.as_mut_ptr() as *mut u8).add(*end + padding) };
+        *end += required;
+        buffer.len += required;
+
+        Some((padding, unsafe {
+            std::slice::from_raw_parts_mut(data, size)
+        }))
+    }
+}
+
 /// A `MutSpace` that notes the amount of allocated space in an atom header.
 pub struct FramedMutSpace<'a, 'b> {
     atom: &'a mut sys::LV2_Atom,
@@ -565,6 +729,49 @@ mod tests {
         test_mut_space(head);
     }
 
+    #[test]
+    fn test_vec_mut_space() {
+        let mut buffer = AtomBuffer::with_capacity(16);
+        test_mut_space(VecMutSpace::new(&mut buffer));
+        assert_eq!(0, buffer.as_bytes().as_ptr() as usize % 8);
+
+        // The buffer contains the same data as a fixed-size space after growing.
+        const MEMORY_SIZE: usize = 256;
+        let mut memory: [u64; MEMORY_SIZE] = [0; MEMORY_SIZE];
+        let raw_space: &mut [u8] = unsafe {
+            std::slice::from_raw_parts_mut(
+                memory.as_mut_ptr() as *mut u8,
+                MEMORY_SIZE * size_of::<u64>(),
+            )
+        };
+        let mut root = RootMutSpace::new(raw_space);
+        let mut buffer = AtomBuffer::new();
+        let mut space = VecMutSpace::new(&mut buffer);
+        for space in [
+            &mut root as &mut dyn MutSpace,
+            &mut space as &mut dyn MutSpace,
+        ]
+        .iter_mut()
+        {
+            let mut frame = FramedMutSpace::new(*space, URID::<()>::new(1).unwrap()).unwrap();
+            let frame = &mut frame as &mut dyn MutSpace;
+            for i in 0..100u8 {
+                frame.write(&i, false).unwrap();
+                frame.write(&(i as u64), true).unwrap();
+            }
+        }
+        let len = buffer.len();
+        assert_eq!(8 + 100 * 16, len);
+        assert_eq!(
+            unsafe { std::slice::from_raw_parts(memory.as_ptr() as *const u8, len) },
+            buffer.as_bytes()
+        );
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert!(buffer.as_bytes().is_empty());
+    }
+
     #[test]
     fn test_padding_inside_frame() {
         const MEMORY_SIZE: usize = 256;
@@ -1,8 +1,8 @@
 //! String handling atoms.
 //!
-//! This module contains two different atoms: The [`String`](struct.String.html) and the [`Literal`](struct.Literal.html). The former is for simple, non-localized UTF-8 strings, like URIs or paths, and the later is either for localized text, e.g. descriptions in the user interface, or RDF literals.
+//! This module contains four different atoms: The [`String`](struct.String.html), the [`Literal`](struct.Literal.html), the [`AtomURI`](struct.AtomURI.html) and the [`Path`](struct.Path.html). The string is for simple, non-localized UTF-8 strings and the literal is either for localized text, e.g. descriptions in the user interface, or RDF literals. URIs and paths are strings too, but their types tell the receiver how to interpret them, which is for example used by hosts and plugins to exchange the paths of files to load.
 //!
-//! Reading and writing these atoms is pretty simple: They don't require a parameter and return a either a `&str` or the literal info and a `&str`. Writing is done with a writing handle which can append strings to the string/literal. When dropped, the handle will append the null character, you therefore don't have to handle it on your own.
+//! Reading and writing these atoms is pretty simple: They don't require a parameter and return a either a `&str`, the literal info and a `&str`, a `&Uri` or a `&Path`. Writing is done with a writing handle which can append strings to the atom. When dropped, the handle will append the null character, you therefore don't have to handle it on your own.
 //!
 //! # Example
 //! ```
@@ -27,6 +27,8 @@
 //!
 //! [http://lv2plug.in/ns/ext/atom/atom.html#String](http://lv2plug.in/ns/ext/atom/atom.html#String)
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Literal](http://lv2plug.in/ns/ext/atom/atom.html#Literal)
+//! [http://lv2plug.in/ns/ext/atom/atom.html#URI](http://lv2plug.in/ns/ext/atom/atom.html#URI)
+//! [http://lv2plug.in/ns/ext/atom/atom.html#Path](http://lv2plug.in/ns/ext/atom/atom.html#Path)
 use crate::prelude::*;
 use crate::space::*;
 use urid::*;
@@ -119,7 +121,66 @@ where
     }
 }
 
-/// Handle to append strings to a string or literal.
+/// An atom containing a URI.
+///
+/// The URI is stored like a [`String`](struct.String.html), but it's read as a [`Uri`](../../urid/type.Uri.html). In contrast to the [`AtomURID`](../scalar/struct.AtomURID.html), the URI doesn't have to be mapped, which is useful for URIs that are only used once.
+///
+/// [See also the module documentation.](index.html)
+pub struct AtomURI;
+
+unsafe impl UriBound for AtomURI {
+    const URI: &'static [u8] = sys::LV2_ATOM__URI;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for AtomURI
+where
+    'a: 'b,
+{
+    type ReadParameter = ();
+    type ReadHandle = &'a Uri;
+    type WriteParameter = ();
+    type WriteHandle = StringWriter<'a, 'b>;
+
+    fn read(body: Space<'a>, _: ()) -> Option<&'a Uri> {
+        body.data()
+            .and_then(|data| Uri::from_bytes_with_nul(data).ok())
+    }
+
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Option<StringWriter<'a, 'b>> {
+        Some(StringWriter { frame })
+    }
+}
+
+/// An atom containing the path of a local file.
+///
+/// The path is stored like a [`String`](struct.String.html), but it's read as a [`Path`](https://doc.rust-lang.org/std/path/struct.Path.html). Hosts and plugins use this atom to exchange files, for example samples or impulse responses the plugin should load.
+///
+/// [See also the module documentation.](index.html)
+pub struct Path;
+
+unsafe impl UriBound for Path {
+    const URI: &'static [u8] = sys::LV2_ATOM__Path;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for Path
+where
+    'a: 'b,
+{
+    type ReadParameter = ();
+    type ReadHandle = &'a std::path::Path;
+    type WriteParameter = ();
+    type WriteHandle = StringWriter<'a, 'b>;
+
+    fn read(body: Space<'a>, _: ()) -> Option<&'a std::path::Path> {
+        String::read(body, ()).map(std::path::Path::new)
+    }
+
+    fn init(frame: FramedMutSpace<'a, 'b>, _: ()) -> Option<StringWriter<'a, 'b>> {
+        Some(StringWriter { frame })
+    }
+}
+
+/// Handle to append strings to a string, literal, URI or path.
 pub struct StringWriter<'a, 'b> {
     frame: FramedMutSpace<'a, 'b>,
 }
@@ -221,6 +282,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_uri_and_path() {
+        let map = HashURIDMapper::new();
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.uri, ())
+                .unwrap();
+            writer.append("urn:lv2-atom:").unwrap();
+            writer.append("test").unwrap();
+            drop(writer);
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.path, ())
+                .unwrap();
+            writer.append("/tmp/sample.wav").unwrap();
+        }
+
+        // reading
+        {
+            let space = Space::from_slice(raw_space.as_ref());
+            let (body, space) = space.split_atom_body(urids.uri).unwrap();
+            let uri = AtomURI::read(body, ()).unwrap();
+            assert_eq!(uri.to_str().unwrap(), "urn:lv2-atom:test");
+
+            assert!(space.split_atom_body(urids.string).is_none());
+            let (body, _) = space.split_atom_body(urids.path).unwrap();
+            let path = Path::read(body, ()).unwrap();
+            assert_eq!(path, std::path::Path::new("/tmp/sample.wav"));
+        }
+    }
+
     #[test]
     fn test_string() {
         let map = HashURIDMapper::new();
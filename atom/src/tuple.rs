@@ -80,7 +80,7 @@ impl<'a> Iterator for TupleIterator<'a> {
 
 /// The writing handle to add atoms to a tuple.
 pub struct TupleWriter<'a, 'b> {
-    frame: FramedMutSpace<'a, 'b>,
+    pub(crate) frame: FramedMutSpace<'a, 'b>,
 }
 
 impl<'a, 'b> TupleWriter<'a, 'b> {
@@ -0,0 +1,440 @@
+//! Owned atom values.
+//!
+//! The reading handles of the other modules borrow the memory of the atom, which is usually the buffer of a port and therefore only valid during a single `run` call. The [`AtomValue`](enum.AtomValue.html) is an owned copy of an atom, including all of its children: It can be decoded from any [`UnidentifiedAtom`](../struct.UnidentifiedAtom.html), compared, cloned, kept for as long as needed and written back to any [`MutSpace`](../space/trait.MutSpace.html).
+//!
+//! Since decoding and encoding values allocates memory on the heap, this is not real-time safe and meant for code outside of the audio threading class, like workers, state handling and user interfaces.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::space::*;
+//! use lv2_atom::value::*;
+//! use std::collections::BTreeMap;
+//! use urid::*;
+//!
+//! let map = HashURIDMapper::new();
+//! let urids: ValueURIDCollection = map.populate_collection().unwrap();
+//!
+//! let value = AtomValue::Tuple(vec![
+//!     AtomValue::Int(42),
+//!     AtomValue::String("Hello".to_owned()),
+//!     AtomValue::Vector(VectorValue::Float(vec![0.0, 0.5, 1.0])),
+//! ]);
+//!
+//! // Writing the value.
+//! let mut element = SpaceElement::default();
+//! value.write(&mut SpaceHead::new(&mut element), &urids).unwrap();
+//!
+//! // Reading it again.
+//! let data = element.to_vec();
+//! let atom = UnidentifiedAtom::new(Space::from_slice(data.as_ref()));
+//! assert_eq!(Some(value), AtomValue::read(atom, &urids));
+//! ```
+use crate::object::{ObjectHeader, Property};
+use crate::scalar::ScalarAtom;
+use crate::sequence::{TimeStamp, TimeStampURID, TimeStampUnit};
+use crate::string::LiteralInfo;
+use crate::*;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use units::UnitURIDCollection;
+use urid::*;
+
+/// A URID cache containing everything required to read and write atom values.
+#[derive(URIDCollection)]
+pub struct ValueURIDCollection {
+    pub atom: AtomURIDCollection,
+    pub units: UnitURIDCollection,
+}
+
+/// An owned atom.
+///
+/// [See also the module documentation.](index.html)
+#[derive(Clone, PartialEq, Debug)]
+pub enum AtomValue {
+    Bool(bool),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    URID(URID),
+    String(std::string::String),
+    Literal(LiteralInfo, std::string::String),
+    URI(UriBuf),
+    Path(PathBuf),
+    Chunk(Vec<u8>),
+    Vector(VectorValue),
+    Sound(Vec<f32>),
+    Tuple(Vec<AtomValue>),
+    Object(ObjectValue),
+    Sequence(SequenceValue),
+    /// An atom of a type that isn't known to this crate, like a MIDI event.
+    ///
+    /// The body is copied as it is.
+    Other {
+        type_urid: URID,
+        body: Vec<u8>,
+    },
+}
+
+/// The owned elements of a vector atom.
+#[derive(Clone, PartialEq, Debug)]
+pub enum VectorValue {
+    Bool(Vec<bool>),
+    Int(Vec<i32>),
+    Long(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    URID(Vec<URID>),
+}
+
+/// An owned object atom.
+///
+/// `Blank` and `Resource` atoms are read as objects too, and all objects are written as `Object` atoms. The contexts of properties are ignored, and if a key appears more than once, only the last value is kept.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ObjectValue {
+    /// The id of the object.
+    pub id: Option<URID>,
+    /// The type of the object.
+    pub otype: URID,
+    /// The values of the properties, by key.
+    pub properties: BTreeMap<URID, AtomValue>,
+}
+
+/// An owned sequence atom.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SequenceValue {
+    /// The unit of the time stamps.
+    pub unit: TimeStampUnit,
+    /// The time-stamped events, in order.
+    pub events: Vec<(TimeStamp, AtomValue)>,
+}
+
+impl AtomValue {
+    /// Decode an atom and all of its children.
+    ///
+    /// Atoms of unknown types are decoded as [`Other`](#variant.Other). `None` is returned if the atom is malformed.
+    pub fn read(atom: UnidentifiedAtom, urids: &ValueURIDCollection) -> Option<Self> {
+        let type_urid = atom.type_urid()?;
+        let atom_urids = &urids.atom;
+
+        let value = if type_urid == atom_urids.bool {
+            AtomValue::Bool(atom.read(atom_urids.bool, ())? != 0)
+        } else if type_urid == atom_urids.int {
+            AtomValue::Int(atom.read(atom_urids.int, ())?)
+        } else if type_urid == atom_urids.long {
+            AtomValue::Long(atom.read(atom_urids.long, ())?)
+        } else if type_urid == atom_urids.float {
+            AtomValue::Float(atom.read(atom_urids.float, ())?)
+        } else if type_urid == atom_urids.double {
+            AtomValue::Double(atom.read(atom_urids.double, ())?)
+        } else if type_urid == atom_urids.urid {
+            AtomValue::URID(atom.read(atom_urids.urid, ())?)
+        } else if type_urid == atom_urids.string {
+            AtomValue::String(atom.read(atom_urids.string, ())?.to_owned())
+        } else if type_urid == atom_urids.literal {
+            let (info, text) = atom.read(atom_urids.literal, ())?;
+            AtomValue::Literal(info, text.to_owned())
+        } else if type_urid == atom_urids.uri {
+            AtomValue::URI(atom.read(atom_urids.uri, ())?.to_owned())
+        } else if type_urid == atom_urids.path {
+            AtomValue::Path(atom.read(atom_urids.path, ())?.to_owned())
+        } else if type_urid == atom_urids.chunk {
+            AtomValue::Chunk(atom.read(atom_urids.chunk, ())?.to_owned())
+        } else if type_urid == atom_urids.vector::<scalar::Int>() {
+            return VectorValue::read(atom, atom_urids);
+        } else if type_urid == atom_urids.sound {
+            AtomValue::Sound(atom.read(atom_urids.sound, atom_urids.float)?.to_owned())
+        } else if type_urid == atom_urids.tuple {
+            AtomValue::Tuple(
+                atom.read(atom_urids.tuple, ())?
+                    .map(|child| Self::read(child, urids))
+                    .collect::<Option<_>>()?,
+            )
+        } else if type_urid == atom_urids.object
+            || type_urid == atom_urids.blank
+            || type_urid == atom_urids.resource
+        {
+            let (header, reader) = atom
+                .read(atom_urids.object, ())
+                .or_else(|| atom.read(atom_urids.blank, ()))
+                .or_else(|| atom.read(atom_urids.resource, ()))?;
+            let mut properties = BTreeMap::new();
+            for (property_header, child) in reader {
+                properties.insert(property_header.key, Self::read(child, urids)?);
+            }
+            AtomValue::Object(ObjectValue {
+                id: header.id,
+                otype: header.otype,
+                properties,
+            })
+        } else if type_urid == atom_urids.sequence {
+            let reader = atom.read(atom_urids.sequence, urids.units.beat)?;
+            let unit = reader.unit();
+            AtomValue::Sequence(SequenceValue {
+                unit,
+                events: reader
+                    .map(|(stamp, child)| Self::read(child, urids).map(|child| (stamp, child)))
+                    .collect::<Option<_>>()?,
+            })
+        } else {
+            let (body, _) = atom.space().split_atom_body(type_urid)?;
+            AtomValue::Other {
+                type_urid,
+                body: body.data()?.to_owned(),
+            }
+        };
+        Some(value)
+    }
+
+    /// Write the atom and all of its children to a space.
+    ///
+    /// `None` is returned if the space is insufficient, if a URI or path isn't valid UTF-8 or if the events of a sequence aren't in order or don't match its unit.
+    pub fn write<'a>(
+        &self,
+        space: &mut (dyn MutSpace<'a> + '_),
+        urids: &ValueURIDCollection,
+    ) -> Option<()> {
+        let atom_urids = &urids.atom;
+        match self {
+            AtomValue::Bool(value) => {
+                space.init(atom_urids.bool, *value as i32)?;
+            }
+            AtomValue::Int(value) => {
+                space.init(atom_urids.int, *value)?;
+            }
+            AtomValue::Long(value) => {
+                space.init(atom_urids.long, *value)?;
+            }
+            AtomValue::Float(value) => {
+                space.init(atom_urids.float, *value)?;
+            }
+            AtomValue::Double(value) => {
+                space.init(atom_urids.double, *value)?;
+            }
+            AtomValue::URID(value) => {
+                space.init(atom_urids.urid, *value)?;
+            }
+            AtomValue::String(text) => {
+                space.init(atom_urids.string, ())?.append(text)?;
+            }
+            AtomValue::Literal(info, text) => {
+                space.init(atom_urids.literal, *info)?.append(text)?;
+            }
+            AtomValue::URI(uri) => {
+                space.init(atom_urids.uri, ())?.append(uri.to_str().ok()?)?;
+            }
+            AtomValue::Path(path) => {
+                space.init(atom_urids.path, ())?.append(path.to_str()?)?;
+            }
+            AtomValue::Chunk(data) => {
+                space.init(atom_urids.chunk, ())?.write_raw(data, false)?;
+            }
+            AtomValue::Vector(vector) => vector.write(space, atom_urids)?,
+            AtomValue::Sound(samples) => {
+                space
+                    .init(atom_urids.sound, atom_urids.float)?
+                    .append(samples)?;
+            }
+            AtomValue::Tuple(children) => {
+                let mut writer = space.init(atom_urids.tuple, ())?;
+                for child in children {
+                    child.write(&mut writer.frame, urids)?;
+                }
+            }
+            AtomValue::Object(object) => {
+                let mut writer = space.init(
+                    atom_urids.object,
+                    ObjectHeader {
+                        id: object.id,
+                        otype: object.otype,
+                    },
+                )?;
+                for (key, child) in object.properties.iter() {
+                    Property::write_header(&mut writer.frame, *key, None)?;
+                    child.write(&mut writer.frame, urids)?;
+                }
+            }
+            AtomValue::Sequence(sequence) => {
+                let unit = match sequence.unit {
+                    TimeStampUnit::Frames => TimeStampURID::Frames(urids.units.frame),
+                    TimeStampUnit::BeatsPerMinute => {
+                        TimeStampURID::BeatsPerMinute(urids.units.beat)
+                    }
+                };
+                let mut writer = space.init(atom_urids.sequence, unit)?;
+                for (stamp, child) in sequence.events.iter() {
+                    writer.write_time_stamp(*stamp)?;
+                    child.write(&mut writer.frame, urids)?;
+                }
+            }
+            AtomValue::Other { type_urid, body } => {
+                FramedMutSpace::new(space, *type_urid)?.write_raw(body, false)?;
+            }
+        }
+        Some(())
+    }
+}
+
+impl VectorValue {
+    /// Decode a vector atom.
+    ///
+    /// Vectors with elements of an unknown type are decoded as [`AtomValue::Other`](enum.AtomValue.html#variant.Other).
+    fn read(atom: UnidentifiedAtom, urids: &AtomURIDCollection) -> Option<AtomValue> {
+        let (body, _) = atom
+            .space()
+            .split_atom_body(urids.vector::<scalar::Int>())?;
+        let (header, _) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
+        let child_type = header.child_type;
+
+        let vector = if child_type == urids.bool {
+            VectorValue::Bool(
+                atom.read(urids.vector(), urids.bool)?
+                    .iter()
+                    .map(|value| *value != 0)
+                    .collect(),
+            )
+        } else if child_type == urids.int {
+            VectorValue::Int(atom.read(urids.vector(), urids.int)?.to_owned())
+        } else if child_type == urids.long {
+            VectorValue::Long(atom.read(urids.vector(), urids.long)?.to_owned())
+        } else if child_type == urids.float {
+            VectorValue::Float(atom.read(urids.vector(), urids.float)?.to_owned())
+        } else if child_type == urids.double {
+            VectorValue::Double(atom.read(urids.vector(), urids.double)?.to_owned())
+        } else if child_type == urids.urid {
+            VectorValue::URID(atom.read(urids.vector(), urids.urid)?.to_owned())
+        } else {
+            return Some(AtomValue::Other {
+                type_urid: urids.vector::<scalar::Int>().into_general(),
+                body: body.data()?.to_owned(),
+            });
+        };
+        Some(AtomValue::Vector(vector))
+    }
+
+    /// Write the vector atom to a space.
+    fn write<'a>(
+        &self,
+        space: &mut (dyn MutSpace<'a> + '_),
+        urids: &AtomURIDCollection,
+    ) -> Option<()> {
+        match self {
+            VectorValue::Bool(elements) => {
+                let elements: Vec<i32> = elements.iter().map(|value| *value as i32).collect();
+                write_vector(space, urids, urids.bool, &elements)
+            }
+            VectorValue::Int(elements) => write_vector(space, urids, urids.int, elements),
+            VectorValue::Long(elements) => write_vector(space, urids, urids.long, elements),
+            VectorValue::Float(elements) => write_vector(space, urids, urids.float, elements),
+            VectorValue::Double(elements) => write_vector(space, urids, urids.double, elements),
+            VectorValue::URID(elements) => write_vector(space, urids, urids.urid, elements),
+        }
+    }
+}
+
+fn write_vector<'a, C: ScalarAtom>(
+    space: &mut (dyn MutSpace<'a> + '_),
+    urids: &AtomURIDCollection,
+    child_urid: URID<C>,
+    elements: &[C::InternalType],
+) -> Option<()> {
+    space
+        .init(urids.vector(), child_urid)?
+        .append(elements)
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sequence::{TimeStamp, TimeStampUnit};
+    use crate::space::*;
+    use crate::string::LiteralInfo;
+    use crate::value::*;
+    use std::collections::BTreeMap;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_value() {
+        let map = HashURIDMapper::new();
+        let urids: ValueURIDCollection = map.populate_collection().unwrap();
+        let key_a = map.map_str("urn:key-a").unwrap();
+        let key_b = map.map_str("urn:key-b").unwrap();
+        let midi_event = map
+            .map_str("http://lv2plug.in/ns/ext/midi#MidiEvent")
+            .unwrap();
+
+        let mut properties = BTreeMap::new();
+        properties.insert(key_a, AtomValue::Double(0.25));
+        properties.insert(
+            key_b,
+            AtomValue::Vector(VectorValue::Bool(vec![true, false, true])),
+        );
+        let value = AtomValue::Tuple(vec![
+            AtomValue::Bool(true),
+            AtomValue::Long(-17),
+            AtomValue::URID(key_a),
+            AtomValue::Literal(LiteralInfo::Language(key_b), "Hallo".to_owned()),
+            AtomValue::URI(CString::new("urn:value").unwrap()),
+            AtomValue::Path("/tmp/sample.wav".into()),
+            AtomValue::Chunk(vec![1, 2, 3]),
+            AtomValue::Sound(vec![0.5; 5]),
+            AtomValue::Object(ObjectValue {
+                id: None,
+                otype: key_a,
+                properties,
+            }),
+            AtomValue::Sequence(SequenceValue {
+                unit: TimeStampUnit::Frames,
+                events: vec![
+                    (TimeStamp::Frames(0), AtomValue::Float(1.0)),
+                    (
+                        TimeStamp::Frames(3),
+                        AtomValue::Other {
+                            type_urid: midi_event,
+                            body: vec![0x90, 64, 127],
+                        },
+                    ),
+                ],
+            }),
+        ]);
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 1024]);
+        value
+            .write(&mut RootMutSpace::new(raw_space.as_mut()), &urids)
+            .unwrap();
+
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        let read_value = AtomValue::read(atom, &urids).unwrap();
+        assert_eq!(value, read_value);
+
+        // Blank objects are read as objects.
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        (&mut RootMutSpace::new(raw_space.as_mut()) as &mut dyn MutSpace)
+            .init(
+                urids.atom.blank,
+                ObjectHeader {
+                    id: None,
+                    otype: key_b,
+                },
+            )
+            .unwrap()
+            .init(key_a, None, urids.atom.int, 42)
+            .unwrap();
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        match AtomValue::read(atom, &urids).unwrap() {
+            AtomValue::Object(object) => {
+                assert_eq!(key_b, object.otype);
+                assert_eq!(Some(&AtomValue::Int(42)), object.properties.get(&key_a));
+            }
+            _ => panic!("Not an object"),
+        }
+
+        // Too small spaces are detected.
+        let mut raw_space: Box<[u8]> = Box::new([0; 64]);
+        assert!(value
+            .write(&mut RootMutSpace::new(raw_space.as_mut()), &urids)
+            .is_none());
+    }
+}
@@ -30,7 +30,7 @@
 //! # Specification
 //!
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Vector](http://lv2plug.in/ns/ext/atom/atom.html#Vector)
-use crate::scalar::ScalarAtom;
+use crate::scalar::{Float, ScalarAtom};
 use crate::space::*;
 use crate::*;
 use std::marker::PhantomData;
@@ -95,6 +95,38 @@ where
     }
 }
 
+/// An atom containing an audio waveform.
+///
+/// A sound is a vector of floats with the same format as the buffer of an audio port, and its samples are assumed to have the current sample rate of the host. Reading and writing it works just like reading and writing a `Vector<Float>`, but the atom's type tells the receiver that the samples are audio.
+///
+/// [See also the module documentation.](index.html)
+pub struct Sound;
+
+unsafe impl UriBound for Sound {
+    const URI: &'static [u8] = sys::LV2_ATOM__Sound;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for Sound
+where
+    'a: 'b,
+{
+    type ReadParameter = URID<Float>;
+    type ReadHandle = &'a [f32];
+    type WriteParameter = URID<Float>;
+    type WriteHandle = VectorWriter<'a, 'b, Float>;
+
+    fn read(body: Space<'a>, float_urid: URID<Float>) -> Option<&'a [f32]> {
+        Vector::<Float>::read(body, float_urid)
+    }
+
+    fn init(
+        frame: FramedMutSpace<'a, 'b>,
+        float_urid: URID<Float>,
+    ) -> Option<VectorWriter<'a, 'b, Float>> {
+        Vector::<Float>::init(frame, float_urid)
+    }
+}
+
 /// Handle to append elements to a vector.
 ///
 /// This works by allocating a slice of memory behind the vector and then writing your data to it.
@@ -197,4 +229,30 @@ mod tests {
             assert_eq!(children[children.len() - 1], 1);
         }
     }
+
+    #[test]
+    fn test_sound() {
+        let map = HashURIDMapper::new();
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.sound, urids.float)
+                .unwrap();
+            writer.append(&[0.0, 0.5, 1.0]).unwrap();
+        }
+
+        // reading
+        {
+            let space = Space::from_slice(raw_space.as_ref());
+            assert!(space.split_atom_body(urids.vector).is_none());
+            let (body, _) = space.split_atom_body(urids.sound).unwrap();
+            let samples: &[f32] = Sound::read(body, urids.float).unwrap();
+            assert_eq!(&[0.0, 0.5, 1.0], samples);
+        }
+    }
 }
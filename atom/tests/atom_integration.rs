@@ -180,3 +180,117 @@ fn main() {
         }
     }
 }
+
+#[test]
+fn text_round_trip() {
+    use atom::rdf::*;
+    use atom::sequence::TimeStampUnit;
+    use atom::space::*;
+    use atom::value::*;
+    use std::ffi::CString;
+    use std::path::PathBuf;
+
+    let map = HashURIDMapper::new();
+    let urids: ValueURIDCollection = map.populate_collection().unwrap();
+    let uri = |uri: &str| map.map_str(uri).unwrap();
+
+    let inner = AtomValue::Object(ObjectValue {
+        id: Some(uri("urn:test#inner")),
+        otype: uri("urn:test#Inner"),
+        properties: [(uri("urn:test#gain"), AtomValue::Float(0.25))]
+            .iter()
+            .cloned()
+            .collect(),
+    });
+    let object = AtomValue::Object(ObjectValue {
+        id: None,
+        otype: uri("urn:test#Outer"),
+        properties: [
+            (uri("urn:test#inner"), inner),
+            (uri("urn:test#mode"), AtomValue::URID(uri("urn:test#loud"))),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    });
+
+    let values = vec![
+        AtomValue::Bool(true),
+        AtomValue::Int(-42),
+        AtomValue::Long(i64::MAX),
+        AtomValue::Float(0.1),
+        AtomValue::Float(f32::INFINITY),
+        AtomValue::Double(1e-300),
+        AtomValue::Double(f64::NEG_INFINITY),
+        AtomValue::URID(uri("urn:test#urid")),
+        AtomValue::String("\"Quoted\"\n\ttext with ünïcödé".to_owned()),
+        AtomValue::Literal(
+            LiteralInfo::Language(uri("http://lexvo.org/id/iso639-1/de")),
+            "Hallo Welt".to_owned(),
+        ),
+        AtomValue::Literal(
+            LiteralInfo::Datatype(uri("urn:test#Datatype")),
+            "1 + 1".to_owned(),
+        ),
+        AtomValue::URI(CString::new("urn:test#uri").unwrap()),
+        AtomValue::Path(PathBuf::from("/usr/lib/lv2/my plugin.lv2/sample.wav")),
+        AtomValue::Path(PathBuf::from("samples/kick.wav")),
+        AtomValue::Chunk(vec![0, 1, 2, 253, 254]),
+        AtomValue::Chunk(Vec::new()),
+        AtomValue::Vector(VectorValue::Bool(vec![true, false])),
+        AtomValue::Vector(VectorValue::Int(vec![1, 2, 3])),
+        AtomValue::Vector(VectorValue::Long(vec![-1, 0])),
+        AtomValue::Vector(VectorValue::Float(vec![0.5, -0.25])),
+        AtomValue::Vector(VectorValue::Double(vec![])),
+        AtomValue::Vector(VectorValue::URID(vec![
+            uri("urn:test#a"),
+            uri("urn:test#b"),
+        ])),
+        AtomValue::Sound(vec![0.0, 0.5, -1.0]),
+        AtomValue::Tuple(vec![
+            AtomValue::Int(1),
+            AtomValue::Tuple(vec![]),
+            AtomValue::String("".to_owned()),
+        ]),
+        object.clone(),
+        AtomValue::Sequence(SequenceValue {
+            unit: TimeStampUnit::Frames,
+            events: vec![
+                (
+                    TimeStamp::Frames(0),
+                    AtomValue::Other {
+                        type_urid: uri("http://lv2plug.in/ns/ext/midi#MidiEvent"),
+                        body: vec![0x90, 0x3c, 0x40],
+                    },
+                ),
+                (TimeStamp::Frames(128), object),
+            ],
+        }),
+        AtomValue::Sequence(SequenceValue {
+            unit: TimeStampUnit::BeatsPerMinute,
+            events: vec![(TimeStamp::BeatsPerMinute(1.5), AtomValue::Long(17))],
+        }),
+        AtomValue::Other {
+            type_urid: uri("urn:test#Unknown"),
+            body: vec![1, 2, 3, 4],
+        },
+    ];
+
+    for value in values {
+        let mut buffer = AtomBuffer::new();
+        value
+            .write(&mut VecMutSpace::new(&mut buffer), &urids)
+            .unwrap();
+        let expected = buffer.as_bytes().to_vec();
+
+        let turtle = to_turtle(UnidentifiedAtom::new(buffer.space()), &map, &urids).unwrap();
+        let mut parsed = AtomBuffer::new();
+        from_turtle(&turtle, &mut VecMutSpace::new(&mut parsed), &map, &urids).unwrap();
+        assert_eq!(expected, parsed.as_bytes(), "{}", turtle);
+
+        let json = to_json(UnidentifiedAtom::new(buffer.space()), &map, &urids).unwrap();
+        let mut parsed = AtomBuffer::new();
+        from_json(&json, &mut VecMutSpace::new(&mut parsed), &map, &urids).unwrap();
+        assert_eq!(expected, parsed.as_bytes(), "{}", json);
+    }
+}
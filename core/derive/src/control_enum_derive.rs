@@ -0,0 +1,68 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, Data, DataEnum, DeriveInput, Fields, Ident, Lit, Meta, Variant};
+
+/// A variant of the enum we implement `ControlEnum` for.
+struct ControlEnumVariant<'a> {
+    identifier: &'a Ident,
+    label: String,
+}
+
+impl<'a> ControlEnumVariant<'a> {
+    /// Create a `Self` instance from a variant object.
+    fn from_variant(variant: &'a Variant) -> Self {
+        if !matches!(variant.fields, Fields::Unit) {
+            panic!("Only enums without fields can implement ControlEnum");
+        }
+        let label = variant
+            .attrs
+            .iter()
+            .find(|attribute| attribute.path.is_ident("label"))
+            .map(|attribute| match attribute.parse_meta() {
+                Ok(Meta::NameValue(pair)) => match pair.lit {
+                    Lit::Str(label) => label.value(),
+                    _ => panic!("The label has to be a string literal"),
+                },
+                _ => panic!("The label attribute has to be declared as `#[label = \"...\"]`"),
+            })
+            .unwrap_or_else(|| variant.ident.to_string());
+        ControlEnumVariant {
+            identifier: &variant.ident,
+            label,
+        }
+    }
+}
+
+/// Implement `ControlEnum` for an enum.
+#[inline]
+pub fn control_enum_derive_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    let enum_name = &input.ident;
+    let variants: Vec<ControlEnumVariant> = match &input.data {
+        Data::Enum(DataEnum { variants, .. }) => variants
+            .iter()
+            .map(ControlEnumVariant::from_variant)
+            .collect(),
+        _ => panic!("Only enums can implement ControlEnum"),
+    };
+    if variants.is_empty() {
+        panic!("A ControlEnum needs at least one variant");
+    }
+
+    let identifiers: Vec<&Ident> = variants.iter().map(|variant| variant.identifier).collect();
+    let labels = variants.iter().map(|variant| &variant.label);
+    let indices = 0..variants.len();
+
+    (quote! {
+        impl ControlEnum for #enum_name {
+            const VARIANTS: &'static [Self] = &[#(#enum_name::#identifiers),*];
+            const LABELS: &'static [&'static str] = &[#(#labels),*];
+
+            fn index(self) -> usize {
+                match self {
+                    #(#enum_name::#identifiers => #indices,)*
+                }
+            }
+        }
+    })
+    .into()
+}
@@ -15,9 +15,26 @@ impl<'a> FeatureCollectionField<'a> {
         }
     }
 
+    /// Return the identifier of the local variable that holds the retrieved feature.
+    ///
+    /// The prefix keeps fields from shadowing the variables of the generated function.
+    fn retrieved_identifier(&self) -> Ident {
+        format_ident!("__feature_{}", self.identifier)
+    }
+
     fn make_retrieval(&self) -> impl ::quote::ToTokens {
+        let retrieved = self.retrieved_identifier();
+        // Fields with a leading underscore result in a double underscore.
+        quote! {
+            #[allow(non_snake_case)]
+            let #retrieved = __missing.record(__cache.retrieve_feature(__class));
+        }
+    }
+
+    fn make_field_initialization(&self) -> impl ::quote::ToTokens {
         let identifier = self.identifier;
-        quote! {#identifier: cache.retrieve_feature(class)?,}
+        let retrieved = self.retrieved_identifier();
+        quote! {#identifier: #retrieved.unwrap(),}
     }
 }
 
@@ -47,6 +64,10 @@ impl<'a> FeatureCollectionStruct<'a> {
         let struct_name = self.struct_name;
         let generics = self.generics;
         let retrievals = self.fields.iter().map(|field| field.make_retrieval());
+        let initializations = self
+            .fields
+            .iter()
+            .map(|field| field.make_field_initialization());
         // retrieve the first lifetime of the struct, or set it to `'static` if there is none.
         let lifetime = self
             .generics
@@ -58,11 +79,17 @@ impl<'a> FeatureCollectionStruct<'a> {
         (quote! {
             impl#generics FeatureCollection<#lifetime> for #struct_name#generics {
                 fn from_cache(
-                    cache: &mut FeatureCache<#lifetime>,
-                    class: ThreadingClass,
+                    __cache: &mut FeatureCache<#lifetime>,
+                    __class: ThreadingClass,
                 ) -> Result<Self, MissingFeatureError> {
+                    // Retrieve all features before failing, in order to report every missing one.
+                    let mut __missing = MissingFeatureError::default();
+                    #(#retrievals)*
+                    if !__missing.is_empty() {
+                        return Err(__missing);
+                    }
                     Ok(Self {
-                        #(#retrievals)*
+                        #(#initializations)*
                     })
                 }
             }
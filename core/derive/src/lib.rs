@@ -6,6 +6,7 @@ extern crate syn;
 #[macro_use]
 extern crate quote;
 
+mod control_enum_derive;
 mod feature_collection_derive;
 mod lv2_descriptors;
 mod port_collection_derive;
@@ -13,17 +14,25 @@ mod port_collection_derive;
 use proc_macro::TokenStream;
 
 /// Generate external symbols for LV2 plugins.
+///
+/// The macro takes a comma-separated list of plugin types. Every type may be followed by `= "Name"`, which sets the human-readable name in the generated Turtle description, for example `lv2_descriptors!(Amp = "Simple Amplifier", Delay)`. Plugins without a name are described with the name of their type.
 #[proc_macro]
 pub fn lv2_descriptors(input: TokenStream) -> TokenStream {
     lv2_descriptors::lv2_descriptors_impl(input)
 }
 
-/// Implement the `PortCollection` trait for a port struct.
-#[proc_macro_derive(PortCollection)]
+/// Implement the `PortCollection` and `SlicePorts` traits for a port struct.
+#[proc_macro_derive(PortCollection, attributes(port, flatten))]
 pub fn port_collection_derive(input: TokenStream) -> TokenStream {
     port_collection_derive::port_collection_derive_impl(input)
 }
 
+/// Implement the `ControlEnum` trait for an enum without fields.
+#[proc_macro_derive(ControlEnum, attributes(label))]
+pub fn control_enum_derive(input: TokenStream) -> TokenStream {
+    control_enum_derive::control_enum_derive_impl(input)
+}
+
 #[proc_macro_derive(FeatureCollection)]
 pub fn feature_collection_derive(input: TokenStream) -> TokenStream {
     feature_collection_derive::feature_collection_derive_impl(input)
@@ -1,19 +1,26 @@
 use proc_macro::TokenStream;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
-use syn::{parse_macro_input, Result, Token, Type};
+use syn::{parse_macro_input, LitStr, Result, Token, Type};
 
 /// An instance descriptor that should be exported.
 ///
-/// The instance descriptor is defined by the plugin type.
+/// The instance descriptor is defined by the plugin type, optionally followed by `= "Name"` to set the human-readable name of the plugin.
 struct Lv2InstanceDescriptor {
     plugin_type: Type,
+    name: Option<LitStr>,
 }
 
 impl Parse for Lv2InstanceDescriptor {
     fn parse(input: ParseStream) -> Result<Self> {
         let plugin_type = input.parse()?;
-        Ok(Lv2InstanceDescriptor { plugin_type })
+        let name = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Lv2InstanceDescriptor { plugin_type, name })
     }
 }
 
@@ -52,6 +59,20 @@ impl Lv2InstanceDescriptor {
             #index => &<#plugin_type as PluginInstanceDescriptor>::DESCRIPTOR,
         }
     }
+
+    /// Create a matching arm for the description of the plugin.
+    ///
+    /// If no name is given, the name of the plugin type is used as the plugin's name.
+    fn make_description_match_arm(&self, index: u32) -> impl ::quote::ToTokens {
+        let plugin_type = &self.plugin_type;
+        let name = match self.name.as_ref() {
+            Some(name) => name.value(),
+            None => quote!(#plugin_type).to_string().replace(" ", ""),
+        };
+        quote! {
+            #index => Some(PluginDescription::new::<#plugin_type>(#name)),
+        }
+    }
 }
 
 /// A collection for instance descriptors.
@@ -111,6 +132,42 @@ impl Lv2InstanceDescriptorList {
             }
         }
     }
+
+    /// Create the `lv2_rs_plugin_description` function.
+    ///
+    /// This function is not part of the LV2 API. It is used by tools to generate the Turtle description of a library's plugins.
+    fn make_description_function(&self) -> impl ::quote::ToTokens {
+        let index_matchers = self
+            .descriptors
+            .iter()
+            .enumerate()
+            .map(|(i, desc)| desc.make_description_match_arm(i as u32));
+
+        quote! {
+            /// Pass the Turtle description of the plugin with the given index to the callback.
+            ///
+            /// This function is not used by hosts, but by tools that create plugin bundles. It returns `false` if there is no plugin with the given index or if the description couldn't be created.
+            ///
+            /// # Safety
+            ///
+            /// The callback is called with the passed data pointer and a pointer to a null-terminated string, which is only valid during the call.
+            #[no_mangle]
+            pub unsafe extern "C" fn lv2_rs_plugin_description(
+                index: u32,
+                callback: unsafe extern "C" fn(*mut ::std::ffi::c_void, *const ::std::os::raw::c_char),
+                data: *mut ::std::ffi::c_void,
+            ) -> bool {
+                PluginDescription::export(
+                    || match index {
+                        #(#index_matchers)*
+                        _ => None,
+                    },
+                    callback,
+                    data,
+                )
+            }
+        }
+    }
 }
 
 /// Generate external symbols for LV2 plugins.
@@ -119,10 +176,12 @@ pub fn lv2_descriptors_impl(input: TokenStream) -> TokenStream {
     let list: Lv2InstanceDescriptorList = parse_macro_input!(input);
     let descriptors = list.make_instance_descriptor_impls();
     let export_function = list.make_descriptor_function();
+    let description_function = list.make_description_function();
 
     (quote! {
         #(#descriptors)*
         #export_function
+        #description_function
     })
     .into()
 }
@@ -2,12 +2,150 @@ use proc_macro::TokenStream;
 use syn::export::Span;
 use syn::DeriveInput;
 use syn::Field;
-use syn::{parse_macro_input, Data, DataStruct, Ident, Type};
+use syn::{
+    parse_macro_input, Data, DataStruct, Expr, ExprLit, Ident, Lit, Meta, NestedMeta, Path, Type,
+};
+
+/// Metadata of a port, declared with the `port` attribute.
+#[derive(Default)]
+struct PortMetadata {
+    symbol: Option<String>,
+    name: Option<String>,
+    default: Option<f32>,
+    minimum: Option<f32>,
+    maximum: Option<f32>,
+    unit: Option<Path>,
+    properties: Vec<Path>,
+    supports: Vec<Path>,
+    sidechain_of: Option<String>,
+}
+
+impl PortMetadata {
+    /// Check whether the metadata contains anything that only applies to a single port.
+    fn describes_port(&self) -> bool {
+        self.default.is_some()
+            || self.minimum.is_some()
+            || self.maximum.is_some()
+            || self.unit.is_some()
+            || !self.properties.is_empty()
+            || !self.supports.is_empty()
+    }
+
+    /// Parse the metadata from the attributes of a field.
+    fn from_attributes(attributes: &[syn::Attribute]) -> Self {
+        let mut metadata = PortMetadata::default();
+        for attribute in attributes
+            .iter()
+            .filter(|attribute| attribute.path.is_ident("port"))
+        {
+            let arguments = match attribute.parse_meta() {
+                Ok(Meta::List(list)) => list.nested,
+                _ => panic!("The port attribute requires a list of arguments"),
+            };
+            for argument in arguments {
+                match argument {
+                    NestedMeta::Meta(Meta::NameValue(pair)) => {
+                        let key = pair.path.get_ident().map(Ident::to_string);
+                        match key.as_deref() {
+                            Some("symbol") => metadata.symbol = Some(Self::parse_string(&pair.lit)),
+                            Some("name") => metadata.name = Some(Self::parse_string(&pair.lit)),
+                            Some("sidechain_of") => {
+                                metadata.sidechain_of = Some(Self::parse_string(&pair.lit))
+                            }
+                            Some("default") => {
+                                metadata.default = Some(Self::parse_number(&pair.lit))
+                            }
+                            Some("minimum") => {
+                                metadata.minimum = Some(Self::parse_number(&pair.lit))
+                            }
+                            Some("maximum") => {
+                                metadata.maximum = Some(Self::parse_number(&pair.lit))
+                            }
+                            _ => panic!("Unknown port attribute argument"),
+                        }
+                    }
+                    NestedMeta::Meta(Meta::List(list)) => {
+                        let paths = list.nested.iter().map(|item| match item {
+                            NestedMeta::Meta(Meta::Path(path)) => path.clone(),
+                            _ => panic!("Expected a list of types"),
+                        });
+                        let key = list.path.get_ident().map(Ident::to_string);
+                        match key.as_deref() {
+                            Some("unit") => {
+                                let mut paths = paths;
+                                metadata.unit = paths.next();
+                                if metadata.unit.is_none() || paths.next().is_some() {
+                                    panic!("A port can only have one unit");
+                                }
+                            }
+                            Some("properties") => metadata.properties.extend(paths),
+                            Some("supports") => metadata.supports.extend(paths),
+                            _ => panic!("Unknown port attribute argument"),
+                        }
+                    }
+                    _ => panic!("Unknown port attribute argument"),
+                }
+            }
+        }
+        metadata
+    }
+
+    /// Parse a string literal.
+    fn parse_string(literal: &Lit) -> String {
+        match literal {
+            Lit::Str(string) => string.value(),
+            _ => panic!("Expected a string literal"),
+        }
+    }
+
+    /// Parse an integer or float literal.
+    fn parse_number(literal: &Lit) -> f32 {
+        match literal {
+            Lit::Float(float) => float.base10_parse().unwrap(),
+            Lit::Int(int) => int.base10_parse().unwrap(),
+            _ => panic!("Expected a number literal"),
+        }
+    }
+}
+
+/// The kind of a field in the struct we implement `PortCollection` for.
+enum FieldKind<'a> {
+    /// A single port handle.
+    Port,
+    /// A fixed-size array of port handles.
+    Array { element: &'a Type, length: usize },
+    /// A nested port collection or a port group, declared with the `flatten` attribute.
+    Flattened,
+}
+
+impl<'a> FieldKind<'a> {
+    /// Determine the kind of a field from its type.
+    fn from_type(port_type: &'a Type) -> Self {
+        match port_type {
+            Type::Array(array) => {
+                let length = match &array.len {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Int(length),
+                        ..
+                    }) => length.base10_parse().unwrap(),
+                    _ => panic!("The length of a port array has to be an integer literal"),
+                };
+                FieldKind::Array {
+                    element: array.elem.as_ref(),
+                    length,
+                }
+            }
+            _ => FieldKind::Port,
+        }
+    }
+}
 
 /// A field in the struct we implement `PortCollection` for.
 struct PortCollectionField<'a> {
     identifier: &'a Ident,
     port_type: &'a Type,
+    metadata: PortMetadata,
+    kind: FieldKind<'a>,
 }
 
 impl<'a> PortCollectionField<'a> {
@@ -16,6 +154,151 @@ impl<'a> PortCollectionField<'a> {
         PortCollectionField {
             identifier: input.ident.as_ref().unwrap(),
             port_type: &input.ty,
+            metadata: PortMetadata::from_attributes(&input.attrs),
+            kind: if input
+                .attrs
+                .iter()
+                .any(|attribute| attribute.path.is_ident("flatten"))
+            {
+                FieldKind::Flattened
+            } else {
+                FieldKind::from_type(&input.ty)
+            },
+        }
+    }
+
+    /// Create the expression for the number of ports of this field.
+    fn make_port_count(&self) -> impl ::quote::ToTokens {
+        let port_type = self.port_type;
+        match self.kind {
+            FieldKind::Port => quote! { 1u32 },
+            FieldKind::Flattened => {
+                quote! { <#port_type as PortCollection>::PORT_COUNT }
+            }
+            FieldKind::Array { length, .. } => {
+                let length = length as u32;
+                quote! { #length }
+            }
+        }
+    }
+
+    /// Create the builder method calls that apply the metadata of a single port.
+    fn make_port_metadata(&self) -> impl ::quote::ToTokens {
+        let metadata = &self.metadata;
+        let default = metadata
+            .default
+            .map(|default| quote! { .default_value(#default) });
+        let minimum = metadata
+            .minimum
+            .map(|minimum| quote! { .minimum(#minimum) });
+        let maximum = metadata
+            .maximum
+            .map(|maximum| quote! { .maximum(#maximum) });
+        let unit = metadata
+            .unit
+            .as_ref()
+            .map(|unit| quote! { .unit::<#unit>() });
+        let properties = metadata.properties.iter();
+        let supports = metadata.supports.iter();
+        let sidechain_of = metadata
+            .sidechain_of
+            .as_ref()
+            .map(|sidechain_of| quote! { .sidechain_of(#sidechain_of) });
+        quote! {
+            #default #minimum #maximum #unit
+            #(.property(PortProperty::#properties))*
+            #(.supports::<#supports>())*
+            #sidechain_of
+        }
+    }
+
+    /// Create the statement that adds the information about the port(s) to the `infos` vector.
+    fn make_port_info(&self) -> impl ::quote::ToTokens {
+        let port_type = self.port_type;
+        let metadata = &self.metadata;
+        let symbol = metadata
+            .symbol
+            .clone()
+            .unwrap_or_else(|| self.identifier.to_string());
+
+        match self.kind {
+            FieldKind::Port => {
+                let name = metadata.name.as_ref().map(|name| quote! { .name(#name) });
+                let port_metadata = self.make_port_metadata();
+                quote! {
+                    infos.push(
+                        <#port_type as PortHandle>::port_info(#symbol)
+                            #name #port_metadata
+                    );
+                }
+            }
+            FieldKind::Flattened if metadata.describes_port() => {
+                quote_spanned! { self.identifier.span() =>
+                    compile_error!("Flattened fields only accept the `symbol`, `name` and `sidechain_of` port arguments");
+                }
+            }
+            FieldKind::Flattened => {
+                // Symbols are only prefixed if requested, so that a nested collection can be used
+                // as a transparent building block.
+                let prefix_symbol = metadata.symbol.as_ref().map(|symbol| {
+                    quote! {
+                        info.symbol = format!("{}_{}", #symbol, info.symbol);
+                        if let Some(group) = info.group.as_mut() {
+                            group.symbol = format!("{}_{}", #symbol, group.symbol);
+                            if let Some(main_group) = group.sidechain_of.as_mut() {
+                                *main_group = format!("{}_{}", #symbol, main_group);
+                            }
+                        }
+                    }
+                });
+                let prefix_name = metadata.name.as_ref().map(|name| {
+                    quote! {
+                        info.name = format!("{} {}", #name, info.name);
+                    }
+                });
+                let prefixed_info = if prefix_symbol.is_none() && prefix_name.is_none() {
+                    quote! { info }
+                } else {
+                    quote! {
+                        let mut info = info;
+                        #prefix_symbol
+                        #prefix_name
+                        info
+                    }
+                };
+                // The ports of a port group don't have a group symbol yet, so the group is named
+                // after the field.
+                let group_name = metadata.name.clone().unwrap_or_else(|| symbol.clone());
+                let sidechain_of = metadata
+                    .sidechain_of
+                    .as_ref()
+                    .map(|sidechain_of| quote! { .sidechain_of(#sidechain_of) });
+                quote! {
+                    infos.extend(
+                        <#port_type as PortCollection>::port_infos()
+                            .into_iter()
+                            .map(|info| {
+                                if matches!(&info.group, Some(group) if group.symbol.is_empty()) {
+                                    return info.group_names(#symbol, #group_name) #sidechain_of;
+                                }
+                                #prefixed_info
+                            })
+                    );
+                }
+            }
+            FieldKind::Array { element, length } => {
+                let name = metadata.name.clone().unwrap_or_else(|| symbol.clone());
+                let port_metadata = self.make_port_metadata();
+                quote! {
+                    for number in 1..=#length {
+                        infos.push(
+                            <#element as PortHandle>::port_info(&format!("{}_{}", #symbol, number))
+                                .name(&format!("{} {}", #name, number))
+                                #port_metadata
+                        );
+                    }
+                }
+            }
         }
     }
 
@@ -23,32 +306,98 @@ impl<'a> PortCollectionField<'a> {
     fn make_connection_from_raw(&self) -> impl ::quote::ToTokens {
         let identifier = self.identifier;
         let port_type = self.port_type;
-        quote! {
-            #identifier: <#port_type as PortHandle>::from_raw(connections.#identifier, sample_count)?,
+        match self.kind {
+            FieldKind::Port => quote! {
+                #identifier: <#port_type as PortHandle>::from_raw(connections.#identifier, sample_count)?,
+            },
+            FieldKind::Flattened => quote! {
+                #identifier: <#port_type as PortCollection>::from_connections(&connections.#identifier, sample_count)?,
+            },
+            FieldKind::Array { element, length } => {
+                let elements = (0..length).map(|index| {
+                    quote! {
+                        <#element as PortHandle>::from_raw(connections.#identifier[#index], sample_count)?,
+                    }
+                });
+                quote! {
+                    #identifier: [#(#elements)*],
+                }
+            }
         }
     }
 
     /// Create the corresponding field declaration line for the raw pointer struct.
     fn make_raw_field_declaration(&self) -> impl ::quote::ToTokens {
         let identifier = self.identifier;
-        quote! {
-            pub #identifier: *mut ::std::ffi::c_void,
+        let port_type = self.port_type;
+        match self.kind {
+            FieldKind::Port => quote! {
+                pub #identifier: *mut ::std::ffi::c_void,
+            },
+            FieldKind::Flattened => quote! {
+                pub #identifier: <#port_type as PortCollection>::Cache,
+            },
+            FieldKind::Array { length, .. } => quote! {
+                pub #identifier: [*mut ::std::ffi::c_void; #length],
+            },
         }
     }
 
     /// Create the corresponding field initialization line for the raw pointer struct.
     fn make_raw_field_initialization(&self) -> impl ::quote::ToTokens {
         let identifier = self.identifier;
-        quote! {
-            #identifier: ::std::ptr::null_mut(),
+        match self.kind {
+            FieldKind::Port => quote! {
+                #identifier: ::std::ptr::null_mut(),
+            },
+            FieldKind::Flattened => quote! {
+                #identifier: Default::default(),
+            },
+            FieldKind::Array { length, .. } => quote! {
+                #identifier: [::std::ptr::null_mut(); #length],
+            },
+        }
+    }
+
+    /// Create the statement that restricts the sampled port(s) of this field to a range of frames.
+    fn make_slice(&self) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        match self.kind {
+            FieldKind::Port => quote! {
+                PortHandle::slice_from(&mut self.#identifier, &mut block.#identifier, range.clone());
+            },
+            FieldKind::Flattened => quote! {
+                SlicePorts::slice_from(&mut self.#identifier, &mut block.#identifier, range.clone());
+            },
+            FieldKind::Array { .. } => quote! {
+                for (port, block) in self.#identifier.iter_mut().zip(block.#identifier.iter_mut()) {
+                    PortHandle::slice_from(port, block, range.clone());
+                }
+            },
         }
     }
 
-    /// Create the connection matching arm for the raw pointer struct.
-    fn make_connect_matcher(&self, index: u32) -> impl ::quote::ToTokens {
+    /// Create the connection statement for the raw pointer struct.
+    ///
+    /// The statement connects the pointer if the index belongs to this field, or moves the offset to the next field.
+    fn make_connect_matcher(&self) -> impl ::quote::ToTokens {
         let identifier = self.identifier;
+        let port_count = self.make_port_count();
+        let connect = match self.kind {
+            FieldKind::Port => quote! { self.#identifier = pointer },
+            FieldKind::Flattened => quote! {
+                PortPointerCache::connect(&mut self.#identifier, index - offset, pointer)
+            },
+            FieldKind::Array { .. } => quote! {
+                self.#identifier[(index - offset) as usize] = pointer
+            },
+        };
         quote! {
-            #index => self.#identifier = pointer,
+            if index < offset + #port_count {
+                #connect;
+                return;
+            }
+            offset += #port_count;
         }
     }
 }
@@ -110,13 +459,17 @@ impl<'a> PortCollectionStruct<'a> {
         let connect_matchers = self
             .fields
             .iter()
-            .enumerate()
-            .map(|(i, f)| f.make_connect_matcher(i as u32));
+            .map(PortCollectionField::make_connect_matcher);
+        let port_counts = self.fields.iter().map(PortCollectionField::make_port_count);
+        let port_infos = self.fields.iter().map(PortCollectionField::make_port_info);
+        let slices = self.fields.iter().map(PortCollectionField::make_slice);
 
         (quote! {
             impl PortCollection for #struct_name {
                 type Cache = #internal_cache_name;
 
+                const PORT_COUNT: u32 = 0 #(+ #port_counts)*;
+
                 #[inline]
                 unsafe fn from_connections(connections: &<Self as PortCollection>::Cache, sample_count: u32) -> Option<Self> {
                     Some(
@@ -125,6 +478,20 @@ impl<'a> PortCollectionStruct<'a> {
                         }
                     )
                 }
+
+                fn port_infos() -> Vec<PortInfo> {
+                    let mut infos = Vec::new();
+                    #(#port_infos)*
+                    infos
+                }
+            }
+
+            impl SlicePorts for #struct_name {
+                #[inline]
+                #[allow(unused_variables)]
+                unsafe fn slice_from(&mut self, block: &mut Self, range: ::std::ops::Range<usize>) {
+                    #(#slices)*
+                }
             }
 
             #[doc(hidden)]
@@ -143,11 +510,10 @@ impl<'a> PortCollectionStruct<'a> {
             }
 
             impl PortPointerCache for #internal_cache_name {
+                #[allow(unused_assignments)]
                 fn connect(&mut self, index: u32, pointer: *mut ::std::ffi::c_void) {
-                    match index {
-                        #(#connect_matchers)*
-                        _ => ()
-                    }
+                    let mut offset = 0u32;
+                    #(#connect_matchers)*
                 }
             }
         }).into()
@@ -2,6 +2,7 @@ use crate::feature::*;
 use std::collections::{hash_map, HashMap};
 use std::ffi::{c_void, CStr};
 use std::iter::Map;
+use urid::Uri;
 
 /// Cache for host features, used in the feature discovery stage.
 ///
@@ -11,6 +12,16 @@ use std::iter::Map;
 #[derive(Clone)]
 pub struct FeatureCache<'a> {
     internal: HashMap<&'a CStr, *const c_void>,
+    offered: Vec<&'a CStr>,
+}
+
+/// A feature offered by the host, as listed by [`FeatureCache::offered_features`](struct.FeatureCache.html#method.offered_features).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OfferedFeature<'a> {
+    /// The URI of the feature.
+    pub uri: &'a Uri,
+    /// Whether the feature has been retrieved from the cache.
+    pub consumed: bool,
 }
 
 impl<'a> FeatureCache<'a> {
@@ -23,19 +34,23 @@ impl<'a> FeatureCache<'a> {
     /// This method is unsafe since it needs to dereference the raw feature pointers.
     pub unsafe fn from_raw(raw: *const *const ::sys::LV2_Feature) -> Self {
         let mut internal_map = HashMap::new();
+        let mut offered = Vec::new();
         let mut feature_ptr = raw;
 
         if !raw.is_null() {
             while !(*feature_ptr).is_null() {
                 let uri = CStr::from_ptr((**feature_ptr).URI);
                 let data = (**feature_ptr).data as *const c_void;
-                internal_map.insert(uri, data);
+                if internal_map.insert(uri, data).is_none() {
+                    offered.push(uri);
+                }
                 feature_ptr = feature_ptr.add(1);
             }
         }
 
         Self {
             internal: internal_map,
+            offered,
         }
     }
 
@@ -44,6 +59,53 @@ impl<'a> FeatureCache<'a> {
         self.internal.contains_key(T::uri())
     }
 
+    /// Retrieve the raw data pointer of a feature without removing it from the cache.
+    ///
+    /// The URI has to be null-terminated, just like the URIs of `UriBound`s.
+    pub(crate) fn raw_feature(&self, uri: &[u8]) -> Option<*const c_void> {
+        let uri = CStr::from_bytes_with_nul(uri).ok()?;
+        self.internal.get(uri).copied()
+    }
+
+    /// Return all features the host has offered, in the order of the host, and whether they have been retrieved.
+    ///
+    /// This is meant for diagnostics; See also [`dump`](#method.dump).
+    pub fn offered_features(&self) -> Vec<OfferedFeature<'a>> {
+        self.offered
+            .iter()
+            .map(|uri| OfferedFeature {
+                uri,
+                consumed: !self.internal.contains_key(uri),
+            })
+            .collect()
+    }
+
+    /// Describe all features the host has offered in a single line.
+    ///
+    /// The line lists the URI of every feature and whether it has been retrieved, for example `host features: urn:a (consumed), urn:b (unused)`. It can be added to error messages to help diagnose problems with hosts.
+    pub fn dump(&self) -> String {
+        let features: Vec<String> = self
+            .offered_features()
+            .iter()
+            .map(|feature| {
+                format!(
+                    "{} ({})",
+                    feature.uri.to_string_lossy(),
+                    if feature.consumed {
+                        "consumed"
+                    } else {
+                        "unused"
+                    }
+                )
+            })
+            .collect();
+        if features.is_empty() {
+            String::from("host features: none")
+        } else {
+            format!("host features: {}", features.join(", "))
+        }
+    }
+
     /// Try to retrieve a feature.
     ///
     /// If the feature is not found, this method will return `None`. Since the resulting feature object may have mutable access to the raw data, it will be removed from the cache to avoid aliasing.
@@ -82,9 +144,7 @@ impl<'a> FeatureCollection<'a> for FeatureCache<'a> {
         cache: &mut FeatureCache<'a>,
         _: ThreadingClass,
     ) -> Result<Self, MissingFeatureError> {
-        Ok(FeatureCache {
-            internal: cache.internal.clone(),
-        })
+        Ok(cache.clone())
     }
 }
 
@@ -102,7 +162,7 @@ pub trait FromResolvedFeature<F: Feature>: Sized {
 
 impl<F: Feature> FromResolvedFeature<F> for F {
     fn from_resolved_feature(feature: Option<F>) -> Result<Self, MissingFeatureError> {
-        feature.ok_or_else(|| MissingFeatureError { uri: F::uri() })
+        feature.ok_or_else(|| MissingFeatureError::new(F::uri()))
     }
 }
 
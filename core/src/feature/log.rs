@@ -0,0 +1,299 @@
+//! The log feature, which lets plugins send messages to the host.
+use crate::feature::*;
+use std::ffi::c_void;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use urid::*;
+
+/// Marker trait for the types of log entries.
+pub trait EntryType: UriBound {}
+
+/// Log entry type for errors.
+pub struct ErrorEntry;
+
+unsafe impl UriBound for ErrorEntry {
+    const URI: &'static [u8] = sys::LV2_LOG__Error;
+}
+
+impl EntryType for ErrorEntry {}
+
+/// Log entry type for warnings.
+pub struct WarningEntry;
+
+unsafe impl UriBound for WarningEntry {
+    const URI: &'static [u8] = sys::LV2_LOG__Warning;
+}
+
+impl EntryType for WarningEntry {}
+
+/// Log entry type for informative notes.
+pub struct NoteEntry;
+
+unsafe impl UriBound for NoteEntry {
+    const URI: &'static [u8] = sys::LV2_LOG__Note;
+}
+
+impl EntryType for NoteEntry {}
+
+/// Log entry type for debugging traces.
+///
+/// This is the only entry type that may be logged in the audio threading class.
+pub struct TraceEntry;
+
+unsafe impl UriBound for TraceEntry {
+    const URI: &'static [u8] = sys::LV2_LOG__Trace;
+}
+
+impl EntryType for TraceEntry {}
+
+/// Collection with the URIDs of all log entry types.
+#[derive(Clone, URIDCollection)]
+pub struct LogURIDCollection {
+    pub error: URID<ErrorEntry>,
+    pub warning: URID<WarningEntry>,
+    pub note: URID<NoteEntry>,
+    pub trace: URID<TraceEntry>,
+}
+
+/// Errors potentially generated by the printing methods of [`Log`](struct.Log.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogError {
+    /// No printing callback was provided by the host.
+    ///
+    /// This can only happen with a faulty host.
+    NoCallback,
+    /// The host failed to print the message.
+    PrintFailed,
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LogError::NoCallback => "no callback".fmt(f),
+            LogError::PrintFailed => "printing failed".fmt(f),
+        }
+    }
+}
+
+/// Host feature to send log messages to the host.
+///
+/// The feature may be used in every threading class. However, the host only guarantees that logging is realtime-safe for trace entries. Therefore, only [`TraceEntry`s](struct.TraceEntry.html) may be logged in the audio threading class.
+///
+/// Messages are formatted into a fixed buffer on the stack with the [`log`](../macro.log.html) macro, which makes logging allocation-free.
+#[repr(transparent)]
+pub struct Log<'a> {
+    internal: &'a sys::LV2_Log_Log,
+}
+
+unsafe impl<'a> UriBound for Log<'a> {
+    const URI: &'static [u8] = sys::LV2_LOG__log;
+}
+
+unsafe impl<'a> Feature for Log<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        (feature as *const sys::LV2_Log_Log)
+            .as_ref()
+            .map(|internal| Self { internal })
+    }
+}
+
+impl<'a> Log<'a> {
+    /// Create a log handle from the raw feature of the host.
+    ///
+    /// Plugins receive the log as a feature and don't need this method; It is meant for hosts that provide the feature themselves. The `handle` and the `printf` and `vprintf` callbacks of `internal` have to stay valid for as long as the log is used, which is at most the lifetime `'a`.
+    pub fn new(internal: &'a sys::LV2_Log_Log) -> Self {
+        Self { internal }
+    }
+
+    /// Send a message to the host.
+    ///
+    /// The message does not need to be null-terminated and is printed verbatim, without any interpretation of format specifiers.
+    pub fn print<T: EntryType + ?Sized>(
+        &self,
+        entry: URID<T>,
+        message: &str,
+    ) -> Result<(), LogError> {
+        let printf = self.internal.printf.ok_or(LogError::NoCallback)?;
+        let result = unsafe {
+            (printf)(
+                self.internal.handle,
+                entry.get(),
+                b"%.*s\0".as_ptr() as *const c_char,
+                message.len() as c_int,
+                message.as_ptr() as *const c_char,
+            )
+        };
+        if result < 0 {
+            Err(LogError::PrintFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Format a message into a stack buffer and send it to the host.
+    ///
+    /// Messages that are longer than [`MessageBuffer::CAPACITY`](struct.MessageBuffer.html#associatedconstant.CAPACITY) bytes are truncated. Usually, you would use the [`log`](../macro.log.html) macro instead of calling this method directly.
+    pub fn print_fmt<T: EntryType + ?Sized>(
+        &self,
+        entry: URID<T>,
+        args: fmt::Arguments,
+    ) -> Result<(), LogError> {
+        let mut buffer = MessageBuffer::new();
+        // An error only signals that the message was truncated, which is acceptable.
+        let _ = fmt::write(&mut buffer, args);
+        self.print(entry, buffer.as_str())
+    }
+}
+
+/// A fixed-size buffer for log messages.
+///
+/// The buffer implements `fmt::Write` and silently truncates messages that are too long, always at a character boundary. Since it lives on the stack, formatting a message into it does not allocate memory.
+pub struct MessageBuffer {
+    data: [u8; MessageBuffer::CAPACITY],
+    len: usize,
+}
+
+impl MessageBuffer {
+    /// The maximal number of bytes the buffer can hold.
+    pub const CAPACITY: usize = 512;
+
+    /// Create a new, empty buffer.
+    pub fn new() -> Self {
+        Self {
+            data: [0; Self::CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Return the contents of the buffer.
+    pub fn as_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(&self.data[..self.len]) }
+    }
+}
+
+impl Default for MessageBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Write for MessageBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let free = Self::CAPACITY - self.len;
+        if s.len() <= free {
+            self.data[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+            self.len += s.len();
+            Ok(())
+        } else {
+            let mut end = free;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.data[self.len..self.len + end].copy_from_slice(&s.as_bytes()[..end]);
+            self.len += end;
+            Err(fmt::Error)
+        }
+    }
+}
+
+/// Format a message and send it to the host's log.
+///
+/// This macro works like `format!`, but the first two arguments are the [`Log`](feature/struct.Log.html) feature and the URID of the entry type. The message is formatted into a fixed buffer on the stack and therefore, this macro does not allocate memory. It returns the result of [`Log::print_fmt`](feature/struct.Log.html#method.print_fmt).
+///
+/// # Example
+///
+///     use lv2_core::feature::*;
+///     use urid::*;
+///
+///     fn report(log: &Log, urids: &LogURIDCollection, sample_count: u32) {
+///         let _ = lv2_core::log!(log, urids.trace, "processing {} samples", sample_count);
+///     }
+#[macro_export]
+macro_rules! log {
+    ($log:expr, $entry:expr, $($arg:tt)*) => {
+        $log.print_fmt($entry, format_args!($($arg)*))
+    };
+}
+
+/// Retrieve the host's log, together with the URID of error entries.
+///
+/// This requires the log and the URID map features. Since the URID is mapped, this function should not be called in the audio threading class.
+pub(crate) fn error_log<'a>(features: &FeatureCache<'a>) -> Option<(Log<'a>, URID<ErrorEntry>)> {
+    let mut features = features.clone();
+    let log: Option<Log> = features
+        .retrieve_feature::<Log, Option<Log>>(ThreadingClass::Instantiation)
+        .unwrap_or(None);
+    let map = features
+        .raw_feature(sys::LV2_URID__map)
+        .and_then(|map| unsafe { (map as *const sys::LV2_URID_Map).as_ref() })?;
+    let error = map
+        .map
+        .map(|map_fn| unsafe { (map_fn)(map.handle, ErrorEntry::URI.as_ptr() as *const c_char) })
+        .and_then(URID::new)
+        .map(|urid| unsafe { URID::<ErrorEntry>::new_unchecked(urid.get()) })?;
+    log.map(|log| (log, error))
+}
+
+/// Report an error that occurred while instantiating a plugin or a plugin UI.
+///
+/// If the host provides the log and the URID map features, the error is sent to the host's log. Otherwise, it is printed to the standard error output.
+///
+/// This function is only public since it's shared with `lv2-ui` and isn't part of the stable API.
+#[doc(hidden)]
+pub fn report_instantiation_error(features: &FeatureCache, args: fmt::Arguments) {
+    if let Some((log, error)) = error_log(features) {
+        if log.print_fmt(error, args).is_ok() {
+            return;
+        }
+    }
+    eprintln!("{}", args);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::*;
+    use std::ffi::c_void;
+    use std::fmt::Write;
+    use urid::*;
+
+    #[test]
+    fn test_message_buffer() {
+        let mut buffer = MessageBuffer::new();
+        write!(buffer, "{} + {} = {}", 1, 2, 1 + 2).unwrap();
+        assert_eq!("1 + 2 = 3", buffer.as_str());
+
+        let mut buffer = MessageBuffer::new();
+        let long_message = "ä".repeat(MessageBuffer::CAPACITY);
+        assert!(write!(buffer, "x{}", long_message).is_err());
+        assert_eq!(MessageBuffer::CAPACITY - 1, buffer.as_str().len());
+        assert!(buffer.as_str().starts_with("xää"));
+    }
+
+    #[test]
+    fn test_log_without_callback() {
+        let internal = sys::LV2_Log_Log {
+            handle: std::ptr::null_mut(),
+            printf: None,
+            vprintf: None,
+        };
+        let log = unsafe {
+            Log::from_feature_ptr(
+                &internal as *const sys::LV2_Log_Log as *const c_void,
+                ThreadingClass::Audio,
+            )
+        }
+        .unwrap();
+
+        let map = HashURIDMapper::new();
+        let urids: LogURIDCollection = map.populate_collection().unwrap();
+
+        assert_eq!(
+            Err(LogError::NoCallback),
+            log.print(urids.error, "Hello World!")
+        );
+        assert_eq!(
+            Err(LogError::NoCallback),
+            crate::log!(log, urids.trace, "{} samples", 512)
+        );
+    }
+}
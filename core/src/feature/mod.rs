@@ -4,10 +4,12 @@ use urid::{Uri, UriBound};
 mod cache;
 mod core_features;
 mod descriptor;
+mod log;
 
-pub use cache::FeatureCache;
+pub use cache::{FeatureCache, OfferedFeature};
 pub use core_features::*;
 pub use descriptor::FeatureDescriptor;
+pub use log::*;
 
 use std::ffi::c_void;
 
@@ -50,23 +52,69 @@ pub unsafe trait Feature: UriBound + Sized {
     unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self>;
 }
 
-/// An error created during feature resolution when a required feature is missing.
-#[derive(Copy, Clone, Debug)]
+/// An error created during feature resolution when required features are missing.
+///
+/// Feature collections try to retrieve all of their features before they fail, and the error lists every required feature that is missing, so that the plugin's user learns about all of them at once.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct MissingFeatureError {
-    pub(crate) uri: &'static Uri,
+    uris: Vec<&'static Uri>,
+}
+
+impl MissingFeatureError {
+    /// Create an error for a single missing feature.
+    pub fn new(uri: &'static Uri) -> Self {
+        Self { uris: vec![uri] }
+    }
+
+    /// Return the URIs of all missing features.
+    pub fn uris(&self) -> &[&'static Uri] {
+        self.uris.as_slice()
+    }
+
+    /// Return `true` if no feature is missing.
+    ///
+    /// This is the case for an error created with `default`, which is used to collect the missing features of multiple retrievals with [`record`](#method.record).
+    pub fn is_empty(&self) -> bool {
+        self.uris.is_empty()
+    }
+
+    /// Record the missing features of a retrieval result and return the retrieved value, if any.
+    ///
+    /// This is used by `FeatureCollection` implementations to continue the retrieval after a feature is missing.
+    pub fn record<T>(&mut self, result: Result<T, MissingFeatureError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                for uri in error.uris {
+                    if !self.uris.contains(&uri) {
+                        self.uris.push(uri);
+                    }
+                }
+                None
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for MissingFeatureError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        let uri = self.uri.to_str().unwrap_or("[error while reading URI]");
         write!(
             f,
-            "Unable to instantiate plugin: missing required feature: {}",
-            uri
-        )
+            "Unable to instantiate plugin: missing required feature{}: ",
+            if self.uris.len() == 1 { "" } else { "s" }
+        )?;
+        for (index, uri) in self.uris.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", uri.to_str().unwrap_or("[error while reading URI]"))?;
+        }
+        Ok(())
     }
 }
 
+impl std::error::Error for MissingFeatureError {}
+
 /// Convenience trait for feature collections.
 ///
 /// The feature cache is only for temporary use; Once a feature is retrieved, it is removed from the cache. Therefore you need a way to properly store features.
@@ -277,4 +325,63 @@ mod tests {
         assert_eq!(*cache.a.number, *setting.data_a);
         assert_eq!(*cache.b.number, *setting.data_b);
     }
+
+    #[derive(FeatureCollection)]
+    struct MissingCollection<'a> {
+        _a: FeatureA<'a>,
+        _hard_rt: HardRTCapable,
+        _live: Option<IsLive>,
+        _in_place: InPlaceBroken,
+    }
+
+    #[test]
+    fn test_missing_features() {
+        let setting = FeatureTestSetting::new();
+        let mut features_cache = setting.features_cache;
+
+        let error = match MissingCollection::from_cache(&mut features_cache, ThreadingClass::Other)
+        {
+            Err(error) => error,
+            Ok(_) => panic!("Missing features not detected"),
+        };
+        assert_eq!(&[HardRTCapable::uri(), InPlaceBroken::uri()], error.uris());
+        assert_eq!(
+            "Unable to instantiate plugin: missing required features: http://lv2plug.in/ns/lv2core#hardRTCapable, http://lv2plug.in/ns/lv2core#inPlaceBroken",
+            error.to_string()
+        );
+
+        let offered = features_cache.offered_features();
+        assert_eq!(3, offered.len());
+        assert_eq!(
+            OfferedFeature {
+                uri: FeatureB::uri(),
+                consumed: false
+            },
+            offered[1]
+        );
+        assert_eq!(
+            "host features: urn:lv2Feature:A (consumed), urn:lv2Feature:B (unused), http://lv2plug.in/ns/lv2core#isLive (consumed)",
+            features_cache.dump()
+        );
+    }
+
+    /// A collection whose field names are used by the generated code too.
+    #[derive(FeatureCollection)]
+    struct ShadowingCollection<'a> {
+        missing: FeatureA<'a>,
+        cache: FeatureB<'a>,
+        class: IsLive,
+    }
+
+    #[test]
+    fn test_shadowing_field_names() {
+        let setting = FeatureTestSetting::new();
+        let mut features_cache = setting.features_cache;
+
+        let collection =
+            ShadowingCollection::from_cache(&mut features_cache, ThreadingClass::Other).unwrap();
+        assert_eq!(*collection.missing.number, *setting.data_a);
+        assert_eq!(*collection.cache.number, *setting.data_b);
+        let _: IsLive = collection.class;
+    }
 }
@@ -0,0 +1,707 @@
+use crate::plugin::{catch_panic, Plugin};
+use crate::port::*;
+use std::borrow::Cow;
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
+use urid::{Uri, UriBound};
+
+/// Generator for the Turtle description and the manifest of a plugin.
+///
+/// Hosts discover plugins by reading RDF files in the Turtle syntax: Every bundle contains a `manifest.ttl` file that lists the plugins of the bundle and refers to a description of each plugin, which contains the plugin's name, ports, required features and so on. Since most of this information is already present in the plugin's Rust code, this generator creates these files from the plugin type, which makes the Rust code the single source of truth. The ports are described using [`PortCollection::port_infos`](../port/trait.PortCollection.html#method.port_infos).
+///
+///     use lv2_core::prelude::*;
+///     use urid::*;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         #[port(name = "Gain", default = 0.0, minimum = -90.0, maximum = 24.0)]
+///         gain: InputPort<Control>,
+///         #[port(symbol = "in", name = "Input")]
+///         input: InputPort<Audio>,
+///         #[port(symbol = "out", name = "Output")]
+///         output: OutputPort<Audio>,
+///     }
+///
+///     #[uri("urn:rust-lv2-book:eg-amp-rs")]
+///     struct Amp;
+///
+///     impl Plugin for Amp {
+///         type Ports = Ports;
+///         type InitFeatures = ();
+///         type AudioFeatures = ();
+///
+///         fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+///             Some(Self)
+///         }
+///
+///         fn run(&mut self, _: &mut Ports, _: &mut ()) {}
+///     }
+///
+///     let description = PluginDescription::new::<Amp>("Simple Amplifier");
+///     let turtle = description.to_turtle();
+///     assert!(turtle.contains("<http://lv2plug.in/ns/lv2core#symbol> \"gain\""));
+///
+///     let manifest = description.manifest("libamp.so", "amp.ttl");
+///     assert!(manifest.contains("<http://lv2plug.in/ns/lv2core#binary> <libamp.so>"));
+#[derive(Clone, Debug)]
+pub struct PluginDescription {
+    uri: &'static Uri,
+    name: String,
+    classes: Vec<&'static Uri>,
+    required_features: Vec<&'static Uri>,
+    optional_features: Vec<&'static Uri>,
+    extension_data: Vec<&'static Uri>,
+    ports: Vec<PortInfo>,
+}
+
+impl PluginDescription {
+    /// Create the description of a plugin type with the given human-readable name.
+    pub fn new<P: Plugin>(name: &str) -> Self {
+        Self {
+            uri: P::uri(),
+            name: name.to_owned(),
+            classes: vec![Uri::from_bytes_with_nul(sys::LV2_CORE__Plugin).unwrap()],
+            required_features: Vec::new(),
+            optional_features: Vec::new(),
+            extension_data: Vec::new(),
+            ports: <P::Ports as PortCollection>::port_infos(),
+        }
+    }
+
+    /// Add a class to the plugin, like `lv2:AmplifierPlugin`.
+    pub fn class(mut self, class: &'static Uri) -> Self {
+        self.classes.push(class);
+        self
+    }
+
+    /// Declare that the plugin requires the given host feature.
+    pub fn required_feature<F: UriBound>(mut self) -> Self {
+        self.required_features.push(F::uri());
+        self
+    }
+
+    /// Declare that the plugin can use the given host feature, but doesn't require it.
+    pub fn optional_feature<F: UriBound>(mut self) -> Self {
+        self.optional_features.push(F::uri());
+        self
+    }
+
+    /// Declare that the plugin provides the given extension data.
+    pub fn extension_data<D: UriBound>(mut self) -> Self {
+        self.extension_data.push(D::uri());
+        self
+    }
+
+    /// Return the URI of the plugin.
+    pub fn uri(&self) -> &'static Uri {
+        self.uri
+    }
+
+    /// Return the information about the plugin's ports.
+    pub fn ports(&self) -> &[PortInfo] {
+        self.ports.as_slice()
+    }
+
+    /// Create the Turtle description of the plugin.
+    pub fn to_turtle(&self) -> String {
+        let mut turtle = format!("<{}>\n", uri_str(self.uri));
+        write_objects(&mut turtle, 1, "a", &self.classes);
+        write_statement(
+            &mut turtle,
+            1,
+            "<http://usefulinc.com/ns/doap#name>",
+            &string_literal(&self.name),
+        );
+        write_objects(
+            &mut turtle,
+            1,
+            &iri(sys::LV2_CORE__requiredFeature),
+            &self.required_features,
+        );
+        write_objects(
+            &mut turtle,
+            1,
+            &iri(sys::LV2_CORE__optionalFeature),
+            &self.optional_features,
+        );
+        write_objects(
+            &mut turtle,
+            1,
+            &iri(sys::LV2_CORE__extensionData),
+            &self.extension_data,
+        );
+        for (index, port) in self.ports.iter().enumerate() {
+            let mut description = String::from("[\n");
+            write_port(&mut description, self.uri, index, port);
+            description.push_str("    ]");
+            write_statement(&mut turtle, 1, &iri(sys::LV2_CORE__port), &description);
+        }
+        let mut turtle = finish(turtle);
+
+        let mut groups: Vec<&GroupInfo> = Vec::new();
+        for group in self.ports.iter().filter_map(|port| port.group.as_ref()) {
+            if groups.iter().all(|known| known.symbol != group.symbol) {
+                groups.push(group);
+            }
+        }
+        for group in groups {
+            turtle.push('\n');
+            turtle.push_str(&describe_group(self.uri, group));
+        }
+        turtle
+    }
+
+    /// Create the manifest that refers to the plugin's binary and description.
+    ///
+    /// `binary` is the file name of the shared library and `description` is the file name of the Turtle description, both relative to the bundle.
+    pub fn manifest(&self, binary: &str, description: &str) -> String {
+        let mut turtle = format!("<{}>\n", uri_str(self.uri));
+        write_statement(
+            &mut turtle,
+            1,
+            "a",
+            &format!("<{}>", uri_str(self.classes[0])),
+        );
+        write_statement(
+            &mut turtle,
+            1,
+            &iri(sys::LV2_CORE__binary),
+            &format!("<{}>", binary),
+        );
+        write_statement(
+            &mut turtle,
+            1,
+            "<http://www.w3.org/2000/01/rdf-schema#seeAlso>",
+            &format!("<{}>", description),
+        );
+        finish(turtle)
+    }
+
+    /// Create the statement that declares the plugin's URI and class.
+    ///
+    /// This is what a [dynamic manifest](trait.DynManifest.html) tells the host about the plugin before it asks for the full description.
+    pub fn subject(&self) -> String {
+        let mut turtle = format!("<{}>\n", uri_str(self.uri));
+        write_statement(
+            &mut turtle,
+            1,
+            "a",
+            &format!("<{}>", uri_str(self.classes[0])),
+        );
+        finish(turtle)
+    }
+
+    /// Create a description and pass its Turtle description to a C callback.
+    ///
+    /// This is used by the `lv2_rs_plugin_description` function that is exported by [`lv2_descriptors`](macro.lv2_descriptors.html), which lets tools generate the description of a plugin library without knowing the plugin types. Since the function is part of the C interface, the description is created with panics caught. Returns `false` if `describe` returns `None` or panics, or if the description contains a null byte, in which case the callback isn't called.
+    ///
+    /// # Safety
+    ///
+    /// The callback is called with `data` and a pointer to a null-terminated string, which is only valid during the call.
+    pub unsafe fn export<F: FnOnce() -> Option<Self>>(
+        describe: F,
+        callback: unsafe extern "C" fn(*mut c_void, *const c_char),
+        data: *mut c_void,
+    ) -> bool {
+        let turtle = catch_panic("lv2_rs_plugin_description", || {
+            describe().map(|description| description.to_turtle())
+        });
+        match turtle.flatten().map(CString::new) {
+            Some(Ok(turtle)) => {
+                callback(data, turtle.as_ptr());
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Write the statements about a port.
+fn write_port(turtle: &mut String, plugin: &Uri, index: usize, port: &PortInfo) {
+    write_objects(turtle, 2, "a", &port.classes);
+    write_statement(turtle, 2, &iri(sys::LV2_CORE__index), &index.to_string());
+    write_statement(
+        turtle,
+        2,
+        &iri(sys::LV2_CORE__symbol),
+        &string_literal(&port.symbol),
+    );
+    write_statement(
+        turtle,
+        2,
+        &iri(sys::LV2_CORE__name),
+        &string_literal(&port.name),
+    );
+    let values = [
+        (sys::LV2_CORE__default as &[u8], port.default),
+        (sys::LV2_CORE__minimum, port.minimum),
+        (sys::LV2_CORE__maximum, port.maximum),
+    ];
+    for (predicate, value) in values.iter() {
+        if let Some(value) = value {
+            write_statement(turtle, 2, &iri(predicate), &float_literal(*value));
+        }
+    }
+    if let Some(unit) = port.unit {
+        write_statement(
+            turtle,
+            2,
+            &iri(sys::LV2_UNITS__unit),
+            &format!("<{}>", uri_str(unit)),
+        );
+    }
+    if let Some(designation) = port.designation {
+        write_statement(
+            turtle,
+            2,
+            &iri(sys::LV2_CORE__designation),
+            &format!("<{}>", uri_str(designation)),
+        );
+    }
+    if let Some(group) = port.group.as_ref() {
+        write_statement(
+            turtle,
+            2,
+            &iri(sys::LV2_PORT_GROUPS__group),
+            &group_iri(plugin, &group.symbol),
+        );
+    }
+    let properties: Vec<&'static Uri> = port.properties.iter().map(|p| p.uri()).collect();
+    write_objects(turtle, 2, &iri(sys::LV2_CORE__portProperty), &properties);
+    for (label, value) in port.scale_points.iter() {
+        write_statement(
+            turtle,
+            2,
+            &iri(sys::LV2_CORE__scalePoint),
+            &format!(
+                "[ <http://www.w3.org/2000/01/rdf-schema#label> {} ; <http://www.w3.org/1999/02/22-rdf-syntax-ns#value> {} ]",
+                string_literal(label),
+                float_literal(*value)
+            ),
+        );
+    }
+    for (predicate, object) in port.statements.iter() {
+        write_statement(
+            turtle,
+            2,
+            &format!("<{}>", uri_str(predicate)),
+            &format!("<{}>", uri_str(object)),
+        );
+    }
+}
+
+/// Create the description of a port group.
+///
+/// Groups are resources of their own, which are identified by the plugin URI and the group's symbol.
+fn describe_group(plugin: &Uri, group: &GroupInfo) -> String {
+    let mut turtle = format!("{}\n", group_iri(plugin, &group.symbol));
+    write_objects(&mut turtle, 1, "a", &group.classes);
+    write_statement(
+        &mut turtle,
+        1,
+        &iri(sys::LV2_CORE__symbol),
+        &string_literal(&group.symbol),
+    );
+    write_statement(
+        &mut turtle,
+        1,
+        &iri(sys::LV2_CORE__name),
+        &string_literal(&group.name),
+    );
+    if let Some(main_group) = group.sidechain_of.as_ref() {
+        write_statement(
+            &mut turtle,
+            1,
+            &iri(sys::LV2_PORT_GROUPS__sideChainOf),
+            &group_iri(plugin, main_group),
+        );
+    }
+    finish(turtle)
+}
+
+/// Format the IRI reference of a port group.
+///
+/// The group is a fragment of the plugin URI. If the plugin URI already has a fragment, the symbol is appended to it instead.
+fn group_iri(plugin: &Uri, symbol: &str) -> String {
+    let plugin = uri_str(plugin);
+    if plugin.contains('#') {
+        format!("<{}-{}>", plugin, symbol)
+    } else {
+        format!("<{}#{}>", plugin, symbol)
+    }
+}
+
+/// Write a single statement, terminated by a semicolon.
+fn write_statement(turtle: &mut String, depth: usize, predicate: &str, object: &str) {
+    turtle.push_str(&format!(
+        "{}{} {} ;\n",
+        "    ".repeat(depth),
+        predicate,
+        object
+    ));
+}
+
+/// Write a statement with a list of URIs as objects, if the list isn't empty.
+fn write_objects(turtle: &mut String, depth: usize, predicate: &str, objects: &[&'static Uri]) {
+    if objects.is_empty() {
+        return;
+    }
+    let objects: Vec<String> = objects
+        .iter()
+        .map(|object| format!("<{}>", uri_str(object)))
+        .collect();
+    write_statement(turtle, depth, predicate, &objects.join(" , "));
+}
+
+/// Replace the semicolon of the last statement with a period.
+fn finish(mut turtle: String) -> String {
+    turtle.truncate(turtle.len() - " ;\n".len());
+    turtle.push_str(" .\n");
+    turtle
+}
+
+/// Format a URI constant of the `sys` crate as an IRI reference.
+fn iri(uri: &[u8]) -> String {
+    format!("<{}>", uri_str(Uri::from_bytes_with_nul(uri).unwrap()))
+}
+
+/// Return the string of a URI.
+fn uri_str(uri: &Uri) -> Cow<'_, str> {
+    uri.to_string_lossy()
+}
+
+/// Format a string as a Turtle string literal.
+fn string_literal(string: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Create a Turtle literal for a port value.
+///
+/// Turtle has no syntax for values that aren't finite, which are therefore written as typed `xsd:float` literals.
+fn float_literal(value: f32) -> String {
+    let special = if value.is_nan() {
+        "NaN"
+    } else if value == f32::INFINITY {
+        "INF"
+    } else if value == f32::NEG_INFINITY {
+        "-INF"
+    } else {
+        return format!("{:?}", value);
+    };
+    format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#float>", special)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use lv2_units::units::Decibel;
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::c_char;
+    use urid::*;
+
+    #[derive(PortCollection)]
+    #[allow(dead_code)]
+    struct Ports {
+        #[port(name = "Gain", default = 0, minimum = -90.0, maximum = 24.0, unit(Decibel))]
+        gain: InputPort<Control>,
+        #[port(symbol = "in", name = "\"Input\"")]
+        input: InputPort<Audio>,
+        #[port(properties(Toggled, NotAutomatic))]
+        bypass: Option<InputPort<Control>>,
+        output: OutputPort<CV>,
+    }
+
+    #[uri("urn:lv2-core:test-description")]
+    struct TestPlugin;
+
+    impl Plugin for TestPlugin {
+        type Ports = Ports;
+        type InitFeatures = ();
+        type AudioFeatures = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _: &mut Ports, _: &mut ()) {}
+    }
+
+    #[test]
+    fn test_port_infos() {
+        let infos = Ports::port_infos();
+        assert_eq!(4, infos.len());
+
+        assert_eq!("gain", infos[0].symbol);
+        assert_eq!("Gain", infos[0].name);
+        assert_eq!(Some(PortDirection::Input), infos[0].direction);
+        assert_eq!(Some(0.0), infos[0].default);
+        assert_eq!(Some(-90.0), infos[0].minimum);
+        assert_eq!(Some(24.0), infos[0].maximum);
+        assert_eq!(Some(Decibel::uri()), infos[0].unit);
+
+        assert_eq!("in", infos[1].symbol);
+        assert_eq!(Audio::uri(), infos[1].classes[1]);
+
+        assert_eq!("bypass", infos[2].name);
+        assert_eq!(
+            vec![
+                PortProperty::ConnectionOptional,
+                PortProperty::Toggled,
+                PortProperty::NotAutomatic
+            ],
+            infos[2].properties
+        );
+
+        assert_eq!(Some(PortDirection::Output), infos[3].direction);
+        assert_eq!(CV::uri(), infos[3].classes[1]);
+    }
+
+    #[test]
+    fn test_turtle() {
+        let description = PluginDescription::new::<TestPlugin>("Test").class(
+            Uri::from_bytes_with_nul(b"http://lv2plug.in/ns/lv2core#AmplifierPlugin\0").unwrap(),
+        );
+        let expected = r#"<urn:lv2-core:test-description>
+    a <http://lv2plug.in/ns/lv2core#Plugin> , <http://lv2plug.in/ns/lv2core#AmplifierPlugin> ;
+    <http://usefulinc.com/ns/doap#name> "Test" ;
+    <http://lv2plug.in/ns/lv2core#port> [
+        a <http://lv2plug.in/ns/lv2core#InputPort> , <http://lv2plug.in/ns/lv2core#ControlPort> ;
+        <http://lv2plug.in/ns/lv2core#index> 0 ;
+        <http://lv2plug.in/ns/lv2core#symbol> "gain" ;
+        <http://lv2plug.in/ns/lv2core#name> "Gain" ;
+        <http://lv2plug.in/ns/lv2core#default> 0.0 ;
+        <http://lv2plug.in/ns/lv2core#minimum> -90.0 ;
+        <http://lv2plug.in/ns/lv2core#maximum> 24.0 ;
+        <http://lv2plug.in/ns/extensions/units#unit> <http://lv2plug.in/ns/extensions/units#db> ;
+    ] ;
+    <http://lv2plug.in/ns/lv2core#port> [
+        a <http://lv2plug.in/ns/lv2core#InputPort> , <http://lv2plug.in/ns/lv2core#AudioPort> ;
+        <http://lv2plug.in/ns/lv2core#index> 1 ;
+        <http://lv2plug.in/ns/lv2core#symbol> "in" ;
+        <http://lv2plug.in/ns/lv2core#name> "\"Input\"" ;
+    ] ;
+    <http://lv2plug.in/ns/lv2core#port> [
+        a <http://lv2plug.in/ns/lv2core#InputPort> , <http://lv2plug.in/ns/lv2core#ControlPort> ;
+        <http://lv2plug.in/ns/lv2core#index> 2 ;
+        <http://lv2plug.in/ns/lv2core#symbol> "bypass" ;
+        <http://lv2plug.in/ns/lv2core#name> "bypass" ;
+        <http://lv2plug.in/ns/lv2core#portProperty> <http://lv2plug.in/ns/lv2core#connectionOptional> , <http://lv2plug.in/ns/lv2core#toggled> , <http://lv2plug.in/ns/ext/port-props#notAutomatic> ;
+    ] ;
+    <http://lv2plug.in/ns/lv2core#port> [
+        a <http://lv2plug.in/ns/lv2core#OutputPort> , <http://lv2plug.in/ns/lv2core#CVPort> ;
+        <http://lv2plug.in/ns/lv2core#index> 3 ;
+        <http://lv2plug.in/ns/lv2core#symbol> "output" ;
+        <http://lv2plug.in/ns/lv2core#name> "output" ;
+    ] .
+"#;
+        assert_eq!(expected, description.to_turtle());
+    }
+
+    #[test]
+    fn test_manifest() {
+        let manifest =
+            PluginDescription::new::<TestPlugin>("Test").manifest("libtest.so", "test.ttl");
+        let expected = r#"<urn:lv2-core:test-description>
+    a <http://lv2plug.in/ns/lv2core#Plugin> ;
+    <http://lv2plug.in/ns/lv2core#binary> <libtest.so> ;
+    <http://www.w3.org/2000/01/rdf-schema#seeAlso> <test.ttl> .
+"#;
+        assert_eq!(expected, manifest);
+    }
+
+    #[test]
+    fn test_export() {
+        unsafe extern "C" fn collect(data: *mut c_void, turtle: *const c_char) {
+            let turtles = &mut *(data as *mut Vec<String>);
+            turtles.push(CStr::from_ptr(turtle).to_str().unwrap().to_owned());
+        }
+
+        let mut turtles: Vec<String> = Vec::new();
+        let data = &mut turtles as *mut Vec<String> as *mut c_void;
+        unsafe {
+            assert!(PluginDescription::export(
+                || Some(PluginDescription::new::<TestPlugin>("Test")),
+                collect,
+                data
+            ));
+            assert!(!PluginDescription::export(|| None, collect, data));
+            assert!(!PluginDescription::export(
+                || Some(PluginDescription::new::<TestPlugin>("Null\0Name")),
+                collect,
+                data
+            ));
+            assert!(!PluginDescription::export(
+                || panic!("Failed to describe the plugin"),
+                collect,
+                data
+            ));
+        }
+        assert_eq!(
+            vec![PluginDescription::new::<TestPlugin>("Test").to_turtle()],
+            turtles
+        );
+    }
+
+    #[allow(dead_code)]
+    #[derive(PortCollection)]
+    struct GroupPorts {
+        #[flatten]
+        #[port(name = "Input")]
+        input: InputGroup<Audio, Stereo>,
+        #[flatten]
+        #[port(symbol = "sc", name = "Side-Chain", sidechain_of = "input")]
+        sidechain: InputGroup<Audio, Mono>,
+        #[flatten]
+        output: OutputGroup<Audio, Stereo>,
+    }
+
+    #[uri("urn:lv2-core:test-groups")]
+    struct GroupPlugin;
+
+    impl Plugin for GroupPlugin {
+        type Ports = GroupPorts;
+        type InitFeatures = ();
+        type AudioFeatures = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _: &mut GroupPorts, _: &mut ()) {}
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(5, GroupPorts::PORT_COUNT);
+
+        let infos = GroupPorts::port_infos();
+        let symbols: Vec<&str> = infos.iter().map(|info| info.symbol.as_str()).collect();
+        assert_eq!(
+            vec![
+                "input_left",
+                "input_right",
+                "sc_center",
+                "output_left",
+                "output_right"
+            ],
+            symbols
+        );
+        assert_eq!("Side-Chain Center", infos[2].name);
+        assert_eq!(Some(Channel::Right.uri()), infos[1].designation);
+        assert_eq!(vec![PortProperty::IsSideChain], infos[2].properties);
+        assert_eq!(
+            Some(&GroupInfo {
+                symbol: "sc".to_owned(),
+                name: "Side-Chain".to_owned(),
+                classes: GroupInfo::new::<Mono>(PortDirection::Input).classes,
+                sidechain_of: Some("input".to_owned()),
+            }),
+            infos[2].group.as_ref()
+        );
+
+        let turtle = PluginDescription::new::<GroupPlugin>("Groups").to_turtle();
+        assert!(turtle.contains(
+            "        <http://lv2plug.in/ns/lv2core#designation> <http://lv2plug.in/ns/ext/port-groups#left> ;\n        <http://lv2plug.in/ns/ext/port-groups#group> <urn:lv2-core:test-groups#input> ;\n"
+        ));
+        let expected_group = r#"
+<urn:lv2-core:test-groups#sc>
+    a <http://lv2plug.in/ns/ext/port-groups#MonoGroup> , <http://lv2plug.in/ns/ext/port-groups#InputGroup> ;
+    <http://lv2plug.in/ns/lv2core#symbol> "sc" ;
+    <http://lv2plug.in/ns/lv2core#name> "Side-Chain" ;
+    <http://lv2plug.in/ns/ext/port-groups#sideChainOf> <urn:lv2-core:test-groups#input> .
+"#;
+        assert!(turtle.contains(expected_group));
+        assert_eq!(3, turtle.matches("\n<urn:lv2-core:test-groups#").count());
+    }
+
+    #[uri("urn:lv2-core:test#groups")]
+    struct FragmentPlugin;
+
+    impl Plugin for FragmentPlugin {
+        type Ports = GroupPorts;
+        type InitFeatures = ();
+        type AudioFeatures = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _: &mut GroupPorts, _: &mut ()) {}
+    }
+
+    #[test]
+    fn test_groups_of_fragment_uri() {
+        let turtle = PluginDescription::new::<FragmentPlugin>("Groups").to_turtle();
+        assert!(turtle.contains(
+            "<http://lv2plug.in/ns/ext/port-groups#group> <urn:lv2-core:test#groups-input> ;\n"
+        ));
+        assert!(turtle.contains(
+            "<http://lv2plug.in/ns/ext/port-groups#sideChainOf> <urn:lv2-core:test#groups-input> .\n"
+        ));
+        assert_eq!(3, turtle.matches("\n<urn:lv2-core:test#groups-").count());
+        assert!(!turtle.contains("#groups#"));
+    }
+
+    #[derive(ControlEnum, Clone, Copy)]
+    enum Shape {
+        Sine,
+        #[label = "Square wave"]
+        Square,
+    }
+
+    #[allow(dead_code)]
+    #[derive(PortCollection)]
+    struct ShapePorts {
+        shape: InputPort<EnumControl<Shape>>,
+    }
+
+    #[uri("urn:lv2-core:test-shape")]
+    struct ShapePlugin;
+
+    impl Plugin for ShapePlugin {
+        type Ports = ShapePorts;
+        type InitFeatures = ();
+        type AudioFeatures = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _: &mut ShapePorts, _: &mut ()) {}
+    }
+
+    #[test]
+    fn test_scale_points() {
+        let turtle = PluginDescription::new::<ShapePlugin>("Shape").to_turtle();
+        assert!(turtle.contains("        <http://lv2plug.in/ns/lv2core#scalePoint> [ <http://www.w3.org/2000/01/rdf-schema#label> \"Square wave\" ; <http://www.w3.org/1999/02/22-rdf-syntax-ns#value> 1.0 ] ;\n"));
+        assert!(turtle.contains("<http://lv2plug.in/ns/lv2core#portProperty> <http://lv2plug.in/ns/lv2core#integer> , <http://lv2plug.in/ns/lv2core#enumeration> ;"));
+    }
+
+    #[test]
+    fn test_non_finite_values() {
+        let info = PortInfo::new("level")
+            .default_value(f32::NEG_INFINITY)
+            .minimum(1e-7)
+            .maximum(f32::INFINITY)
+            .scale_point("Undefined", f32::NAN);
+        let mut turtle = String::new();
+        super::write_port(&mut turtle, TestPlugin::uri(), 0, &info);
+
+        assert!(turtle.contains("<http://lv2plug.in/ns/lv2core#default> \"-INF\"^^<http://www.w3.org/2001/XMLSchema#float> ;"));
+        assert!(turtle.contains("<http://lv2plug.in/ns/lv2core#minimum> 1e-7 ;"));
+        assert!(turtle.contains("<http://lv2plug.in/ns/lv2core#maximum> \"INF\"^^<http://www.w3.org/2001/XMLSchema#float> ;"));
+        assert!(turtle.contains("<http://www.w3.org/1999/02/22-rdf-syntax-ns#value> \"NaN\"^^<http://www.w3.org/2001/XMLSchema#float> ]"));
+    }
+}
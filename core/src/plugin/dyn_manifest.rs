@@ -0,0 +1,422 @@
+use crate::feature::*;
+use crate::plugin::{catch_panic, PluginDescription};
+use std::ffi::c_void;
+use std::io::{self, Write};
+use std::os::raw::{c_char, c_int};
+use urid::Uri;
+
+/// Generator of a dynamic manifest.
+///
+/// Usually, hosts discover the plugins of a library by reading the static `manifest.ttl` file of the bundle. With the [dynamic manifest extension](http://lv2plug.in/ns/ext/dynmanifest), a library can instead describe its plugins when it's loaded, which is useful if a library contains families of generated plugins, like variants of a plugin for different channel configurations, and only some of them should be exposed, depending on the host's features or the environment.
+///
+/// The generator is created by the host, then asked for a list of plugins, and dropped afterwards. All plugins also have to be exported with [`lv2_descriptors`](macro.lv2_descriptors.html), since the host still instantiates them with the descriptors. The generator is exported with the [`lv2_dyn_manifest`](../macro.lv2_dyn_manifest.html) macro:
+///
+///     use lv2_core::prelude::*;
+///     use urid::*;
+///
+///     #[derive(PortCollection)]
+///     struct MonoPorts {
+///         input: InputPort<Audio>,
+///         output: OutputPort<Audio>,
+///     }
+///
+///     #[derive(PortCollection)]
+///     struct StereoPorts {
+///         #[flatten]
+///         input: InputGroup<Audio, Stereo>,
+///         #[flatten]
+///         output: OutputGroup<Audio, Stereo>,
+///     }
+///
+///     #[uri("urn:rust-lv2-book:mono-delay")]
+///     struct MonoDelay;
+///     # impl Plugin for MonoDelay {
+///     #     type Ports = MonoPorts;
+///     #     type InitFeatures = ();
+///     #     type AudioFeatures = ();
+///     #     fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> { Some(Self) }
+///     #     fn run(&mut self, _: &mut MonoPorts, _: &mut ()) {}
+///     # }
+///
+///     #[uri("urn:rust-lv2-book:stereo-delay")]
+///     struct StereoDelay;
+///     # impl Plugin for StereoDelay {
+///     #     type Ports = StereoPorts;
+///     #     type InitFeatures = ();
+///     #     type AudioFeatures = ();
+///     #     fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> { Some(Self) }
+///     #     fn run(&mut self, _: &mut StereoPorts, _: &mut ()) {}
+///     # }
+///
+///     struct Delays {
+///         stereo: bool,
+///     }
+///
+///     impl DynManifest for Delays {
+///         type Features = ();
+///
+///         fn new(_: &mut ()) -> Option<Self> {
+///             Some(Self {
+///                 stereo: std::env::var_os("DELAYS_MONO_ONLY").is_none(),
+///             })
+///         }
+///
+///         fn plugins(&self) -> Vec<PluginDescription> {
+///             let mut plugins = vec![PluginDescription::new::<MonoDelay>("Mono Delay")];
+///             if self.stereo {
+///                 plugins.push(PluginDescription::new::<StereoDelay>("Stereo Delay"));
+///             }
+///             plugins
+///         }
+///     }
+///
+///     lv2_descriptors!(MonoDelay, StereoDelay);
+///     lv2_dyn_manifest!(Delays);
+///
+/// The static manifest of the bundle then only has to declare the library as a dynamic manifest generator:
+///
+/// ```text
+/// @prefix dman: <http://lv2plug.in/ns/ext/dynmanifest#> .
+/// @prefix lv2:  <http://lv2plug.in/ns/lv2core#> .
+///
+/// <urn:rust-lv2-book:delays>
+///     a dman:DynManifest ;
+///     lv2:binary <libdelays.so> .
+/// ```
+pub trait DynManifest: Sized + 'static {
+    /// The host features used to generate the manifest.
+    ///
+    /// The features are retrieved in the "Discovery" threading class.
+    type Features: FeatureCollection<'static>;
+
+    /// Create a new generator.
+    ///
+    /// If `None` is returned, the host doesn't load the dynamic manifest.
+    fn new(features: &mut Self::Features) -> Option<Self>;
+
+    /// Return the descriptions of all plugins of the dynamic manifest.
+    fn plugins(&self) -> Vec<PluginDescription>;
+}
+
+/// Raw wrapper of a [`DynManifest`](trait.DynManifest.html) generator.
+///
+/// This struct is the handle that is passed to the host and provides the functions of the C interface, which are exported by the [`lv2_dyn_manifest`](../macro.lv2_dyn_manifest.html) macro.
+pub struct DynManifestInstance<D: DynManifest> {
+    /// The generator, which has to be kept alive as long as the host uses the handle.
+    #[allow(dead_code)]
+    generator: D,
+    /// The plugins of the manifest, which are only generated once.
+    plugins: Vec<PluginDescription>,
+}
+
+extern "C" {
+    fn fwrite(ptr: *const c_void, size: usize, count: usize, stream: *mut c_void) -> usize;
+}
+
+/// A C `FILE` stream, opened by the host.
+struct CFile(*mut c_void);
+
+impl Write for CFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = unsafe { fwrite(buf.as_ptr() as *const c_void, 1, buf.len(), self.0) };
+        if written == 0 && !buf.is_empty() {
+            Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "Failed to write to the stream",
+            ))
+        } else {
+            Ok(written)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<D: DynManifest> DynManifestInstance<D> {
+    /// Create a new instance of the generator and generate the plugin descriptions.
+    pub fn new(features: &mut D::Features) -> Option<Self> {
+        let generator = D::new(features)?;
+        let plugins = generator.plugins();
+        Some(Self { generator, plugins })
+    }
+
+    /// Return the descriptions of the plugins of the manifest.
+    pub fn plugins(&self) -> &[PluginDescription] {
+        self.plugins.as_slice()
+    }
+
+    /// Write the subjects of the manifest, which declares the URI and class of every plugin.
+    pub fn write_subjects<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for plugin in self.plugins.iter() {
+            writer.write_all(plugin.subject().as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write the full description of the plugin with the given URI.
+    ///
+    /// Returns `Ok(false)` if there is no plugin with this URI in the manifest.
+    pub fn write_data<W: Write>(&self, writer: &mut W, uri: &Uri) -> io::Result<bool> {
+        match self.plugins.iter().find(|plugin| plugin.uri() == uri) {
+            Some(plugin) => {
+                writer.write_all(plugin.to_turtle().as_bytes())?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Create the generator.
+    ///
+    /// This method provides the `lv2_dyn_manifest_open` function of the C interface and is used by the `lv2_dyn_manifest` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it dereferences raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn open(
+        handle: *mut *mut c_void,
+        features: *const *const sys::LV2_Feature,
+    ) -> c_int {
+        if handle.is_null() {
+            return 1;
+        }
+        let instance = catch_panic("lv2_dyn_manifest_open", || {
+            let mut cache = FeatureCache::from_raw(features);
+            let mut features =
+                D::Features::from_cache(&mut cache, ThreadingClass::Discovery).ok()?;
+            Self::new(&mut features)
+        });
+        match instance {
+            Some(Some(instance)) => {
+                *handle = Box::leak(Box::new(instance)) as *mut Self as *mut c_void;
+                0
+            }
+            _ => 1,
+        }
+    }
+
+    /// Write the subjects of the manifest to a stream.
+    ///
+    /// This method provides the `lv2_dyn_manifest_get_subjects` function of the C interface and is used by the `lv2_dyn_manifest` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it dereferences raw pointers and is part of the C interface. `stream` has to be a writable C `FILE` stream.
+    pub unsafe extern "C" fn get_subjects(handle: *mut c_void, stream: *mut c_void) -> c_int {
+        let instance = match (handle as *const Self).as_ref() {
+            Some(instance) if !stream.is_null() => instance,
+            _ => return 1,
+        };
+        match catch_panic("lv2_dyn_manifest_get_subjects", || {
+            instance.write_subjects(&mut CFile(stream))
+        }) {
+            Some(Ok(())) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Write the description of a plugin to a stream.
+    ///
+    /// This method provides the `lv2_dyn_manifest_get_data` function of the C interface and is used by the `lv2_dyn_manifest` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it dereferences raw pointers and is part of the C interface. `stream` has to be a writable C `FILE` stream.
+    pub unsafe extern "C" fn get_data(
+        handle: *mut c_void,
+        stream: *mut c_void,
+        uri: *const c_char,
+    ) -> c_int {
+        let instance = match (handle as *const Self).as_ref() {
+            Some(instance) if !stream.is_null() && !uri.is_null() => instance,
+            _ => return 1,
+        };
+        let uri = Uri::from_ptr(uri);
+        match catch_panic("lv2_dyn_manifest_get_data", || {
+            instance.write_data(&mut CFile(stream), uri)
+        }) {
+            Some(Ok(true)) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Drop the generator.
+    ///
+    /// This method provides the `lv2_dyn_manifest_close` function of the C interface and is used by the `lv2_dyn_manifest` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it dereferences raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn close(handle: *mut c_void) {
+        if !handle.is_null() {
+            catch_panic("lv2_dyn_manifest_close", || {
+                drop(Box::from_raw(handle as *mut Self))
+            });
+        }
+    }
+}
+
+/// Export a dynamic manifest generator.
+///
+/// This macro takes a type implementing [`DynManifest`](plugin/trait.DynManifest.html) and exports the functions `lv2_dyn_manifest_open`, `lv2_dyn_manifest_get_subjects`, `lv2_dyn_manifest_get_data` and `lv2_dyn_manifest_close`, which are used by the host to generate the manifest. It may only be used once per library.
+#[macro_export]
+macro_rules! lv2_dyn_manifest {
+    ($generator:ty) => {
+        /// Create the dynamic manifest generator.
+        ///
+        /// # Safety
+        ///
+        /// This function is called by the host and dereferences raw pointers.
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_open(
+            handle: *mut *mut ::std::ffi::c_void,
+            features: *const *const ::std::ffi::c_void,
+        ) -> ::std::os::raw::c_int {
+            $crate::plugin::DynManifestInstance::<$generator>::open(handle, features as *const _)
+        }
+
+        /// Write the subjects of the dynamic manifest to a stream.
+        ///
+        /// # Safety
+        ///
+        /// This function is called by the host and dereferences raw pointers.
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_get_subjects(
+            handle: *mut ::std::ffi::c_void,
+            stream: *mut ::std::ffi::c_void,
+        ) -> ::std::os::raw::c_int {
+            $crate::plugin::DynManifestInstance::<$generator>::get_subjects(handle, stream)
+        }
+
+        /// Write the description of a plugin of the dynamic manifest to a stream.
+        ///
+        /// # Safety
+        ///
+        /// This function is called by the host and dereferences raw pointers.
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_get_data(
+            handle: *mut ::std::ffi::c_void,
+            stream: *mut ::std::ffi::c_void,
+            uri: *const ::std::os::raw::c_char,
+        ) -> ::std::os::raw::c_int {
+            $crate::plugin::DynManifestInstance::<$generator>::get_data(handle, stream, uri)
+        }
+
+        /// Drop the dynamic manifest generator.
+        ///
+        /// # Safety
+        ///
+        /// This function is called by the host and dereferences raw pointers.
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_close(handle: *mut ::std::ffi::c_void) {
+            $crate::plugin::DynManifestInstance::<$generator>::close(handle)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use std::ffi::c_void;
+    use std::io::Read;
+    use urid::*;
+
+    #[derive(PortCollection)]
+    struct Ports {
+        #[flatten]
+        input: InputGroup<Audio, Stereo>,
+    }
+
+    #[uri("urn:lv2-core:test-dyn-manifest")]
+    struct Meter;
+
+    impl Plugin for Meter {
+        type Ports = Ports;
+        type InitFeatures = ();
+        type AudioFeatures = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _: &mut Ports, _: &mut ()) {}
+    }
+
+    struct Generator;
+
+    impl DynManifest for Generator {
+        type Features = ();
+
+        fn new(_: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn plugins(&self) -> Vec<PluginDescription> {
+            vec![PluginDescription::new::<Meter>("Meter")]
+        }
+    }
+
+    lv2_dyn_manifest!(Generator);
+
+    extern "C" {
+        fn tmpfile() -> *mut c_void;
+        fn fclose(stream: *mut c_void) -> i32;
+        fn rewind(stream: *mut c_void);
+        fn fread(ptr: *mut c_void, size: usize, count: usize, stream: *mut c_void) -> usize;
+    }
+
+    /// Call a function with a temporary C stream and return what has been written to it.
+    unsafe fn with_stream<F: FnOnce(*mut c_void) -> i32>(f: F) -> (i32, String) {
+        let stream = tmpfile();
+        assert!(!stream.is_null());
+        let result = f(stream);
+        rewind(stream);
+        let mut content = Vec::new();
+        let mut buffer = [0u8; 256];
+        loop {
+            let read = fread(buffer.as_mut_ptr() as *mut c_void, 1, buffer.len(), stream);
+            if read == 0 {
+                break;
+            }
+            content.extend_from_slice(&buffer[..read]);
+        }
+        fclose(stream);
+        let mut text = String::new();
+        content.as_slice().read_to_string(&mut text).unwrap();
+        (result, text)
+    }
+
+    #[test]
+    fn test_dyn_manifest() {
+        let features: [*const c_void; 1] = [std::ptr::null()];
+        let mut handle: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            assert_eq!(0, lv2_dyn_manifest_open(&mut handle, features.as_ptr()));
+            assert!(!handle.is_null());
+
+            let (result, subjects) =
+                with_stream(|stream| lv2_dyn_manifest_get_subjects(handle, stream));
+            assert_eq!(0, result);
+            assert_eq!(
+                "<urn:lv2-core:test-dyn-manifest>\n    a <http://lv2plug.in/ns/lv2core#Plugin> .\n",
+                subjects
+            );
+
+            let uri = b"urn:lv2-core:test-dyn-manifest\0".as_ptr() as *const _;
+            let (result, data) =
+                with_stream(|stream| lv2_dyn_manifest_get_data(handle, stream, uri));
+            assert_eq!(0, result);
+            assert_eq!(PluginDescription::new::<Meter>("Meter").to_turtle(), data);
+
+            let uri = b"urn:lv2-core:unknown\0".as_ptr() as *const _;
+            let (result, data) =
+                with_stream(|stream| lv2_dyn_manifest_get_data(handle, stream, uri));
+            assert_eq!(1, result);
+            assert!(data.is_empty());
+
+            lv2_dyn_manifest_close(handle);
+        }
+    }
+}
@@ -1,16 +1,22 @@
 //! Types to create plugins.
+mod description;
+mod dyn_manifest;
 pub(crate) mod info;
 
+pub use description::PluginDescription;
+pub use dyn_manifest::{DynManifest, DynManifestInstance};
 pub use info::PluginInfo;
 pub use lv2_core_derive::*;
 
 use crate::feature::*;
 use crate::port::*;
+use crate::rt_check;
 use std::any::Any;
 use std::ffi::c_void;
 use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
 use sys::LV2_Handle;
-use urid::{Uri, UriBound};
+use urid::{Uri, UriBound, URID};
 
 /// The central trait to describe LV2 plugins.
 ///
@@ -74,6 +80,10 @@ pub trait Plugin: UriBound + Sized + Send + Sync + 'static {
 /// The host interacts with the plugin via a C API, but the plugin is implemented with ideomatic, safe Rust. To bridge this gap, this wrapper is used to translate and abstract the communcation between the host and the plugin.
 ///
 /// This struct is `repr(C)` and has the plugin as it's first field. Therefore, a valid `*mut PluginInstance<T>` is also a valid `*mut T`.
+///
+/// # Panics
+///
+/// Unwinding across the C interface is undefined behaviour. Therefore, every call into the plugin is guarded with [`call_guarded`](#method.call_guarded): If the plugin panics, the panic is caught, the message is sent to the host's log (or printed to the standard error output if the host doesn't provide a log) and the instance is marked as poisoned. A poisoned instance isn't called anymore; Instead, its outputs are silenced in every `run` call.
 #[repr(C)]
 pub struct PluginInstance<T: Plugin> {
     /// The plugin instance.
@@ -84,6 +94,86 @@ pub struct PluginInstance<T: Plugin> {
     init_features: T::InitFeatures,
     /// All features that may be used in the audio threading class.
     audio_features: T::AudioFeatures,
+    /// The connection pointers of all ports, used to silence the outputs of a poisoned instance.
+    port_pointers: Vec<*mut c_void>,
+    /// The indices and kinds of all output ports.
+    outputs: Vec<(u32, OutputKind)>,
+    /// The URID of `atom:Sequence`, used to clear atom outputs.
+    sequence_urid: Option<u32>,
+    /// The host's log and the URID of error entries, used to report panics.
+    error_log: Option<(Log<'static>, URID<ErrorEntry>)>,
+    /// Whether the plugin has panicked.
+    poisoned: bool,
+    /// The last latency reported by the plugin.
+    latency: f32,
+}
+
+/// The kind of an output port, which determines how it is silenced.
+#[derive(Clone, Copy)]
+enum OutputKind {
+    /// Audio and CV ports.
+    Sampled,
+    Control,
+    Atom,
+    /// Control ports that report the latency of the plugin.
+    Latency,
+}
+
+impl OutputKind {
+    /// Determine the kind of an output port from its information, if it is an output.
+    fn from_port_info(info: &PortInfo) -> Option<Self> {
+        if info.direction != Some(PortDirection::Output) {
+            return None;
+        }
+        let has_class = |class: &[u8]| {
+            info.classes
+                .iter()
+                .any(|uri| uri.to_bytes_with_nul() == class)
+        };
+        let is_latency = match info.designation {
+            Some(designation) => designation.to_bytes_with_nul() == sys::LV2_CORE__latency,
+            None => false,
+        };
+        if is_latency {
+            Some(OutputKind::Latency)
+        } else if has_class(sys::LV2_CORE__AudioPort) || has_class(sys::LV2_CORE__CVPort) {
+            Some(OutputKind::Sampled)
+        } else if has_class(sys::LV2_CORE__ControlPort) {
+            Some(OutputKind::Control)
+        } else if has_class(sys::LV2_ATOM__AtomPort) {
+            Some(OutputKind::Atom)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extract the message of a panic.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Call a function and catch a panic.
+///
+/// This is used by functions of the C interface that don't have access to a [`PluginInstance`](struct.PluginInstance.html), for example `extension_data`. If the function panics, the panic message is printed to the standard error output, together with the name of the function, and `None` is returned.
+pub fn catch_panic<R, F: FnOnce() -> R>(function: &str, f: F) -> Option<R> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => Some(result),
+        Err(payload) => {
+            eprintln!(
+                "`{}` panicked: {}",
+                function,
+                panic_message(payload.as_ref())
+            );
+            None
+        }
+    }
 }
 
 impl<T: Plugin> PluginInstance<T> {
@@ -109,11 +199,44 @@ impl<T: Plugin> PluginInstance<T> {
         bundle_path: *const c_char,
         features: *const *const sys::LV2_Feature,
     ) -> LV2_Handle {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            Self::try_instantiate(descriptor, sample_rate, bundle_path, features)
+        }));
+        match result {
+            Ok(handle) => handle,
+            Err(payload) => {
+                report_instantiation_error(
+                    &FeatureCache::from_raw(features),
+                    format_args!(
+                        "Failed to initialize plugin: Plugin panicked: {}",
+                        panic_message(payload.as_ref())
+                    ),
+                );
+                std::ptr::null_mut()
+            }
+        }
+    }
+
+    /// Instantiate the plugin without catching panics.
+    unsafe fn try_instantiate(
+        descriptor: *const sys::LV2_Descriptor,
+        sample_rate: f64,
+        bundle_path: *const c_char,
+        features: *const *const sys::LV2_Feature,
+    ) -> LV2_Handle {
+        // Collect the supported features.
+        let host_features = FeatureCache::from_raw(features);
+        let mut init_features_cache = host_features.clone();
+        let mut audio_features_cache = host_features.clone();
+
         // Dereference the descriptor.
         let descriptor = match descriptor.as_ref() {
             Some(descriptor) => descriptor,
             None => {
-                eprintln!("Failed to initialize plugin: Descriptor points to null");
+                report_instantiation_error(
+                    &host_features,
+                    format_args!("Failed to initialize plugin: Descriptor points to null"),
+                );
                 return std::ptr::null_mut();
             }
         };
@@ -122,25 +245,27 @@ impl<T: Plugin> PluginInstance<T> {
         let plugin_info = match PluginInfo::from_raw(descriptor, bundle_path, sample_rate) {
             Ok(info) => info,
             Err(e) => {
-                eprintln!(
-                    "Failed to initialize plugin: Illegal info from host: {:?}",
-                    e
+                report_instantiation_error(
+                    &host_features,
+                    format_args!(
+                        "Failed to initialize plugin: Illegal info from host: {:?}",
+                        e
+                    ),
                 );
                 return std::ptr::null_mut();
             }
         };
 
-        // Collect the supported features.
-        let mut init_features_cache = FeatureCache::from_raw(features);
-        let mut audio_features_cache = init_features_cache.clone();
-
         let mut init_features = match T::InitFeatures::from_cache(
             &mut init_features_cache,
             ThreadingClass::Instantiation,
         ) {
             Ok(f) => f,
             Err(e) => {
-                eprintln!("{}", e);
+                report_instantiation_error(
+                    &host_features,
+                    format_args!("{}; {}", e, init_features_cache.dump()),
+                );
                 return std::ptr::null_mut();
             }
         };
@@ -148,11 +273,36 @@ impl<T: Plugin> PluginInstance<T> {
             match T::AudioFeatures::from_cache(&mut audio_features_cache, ThreadingClass::Audio) {
                 Ok(f) => f,
                 Err(e) => {
-                    eprintln!("{}", e);
+                    report_instantiation_error(
+                        &host_features,
+                        format_args!("{}; {}", e, audio_features_cache.dump()),
+                    );
                     return std::ptr::null_mut();
                 }
             };
 
+        // Collect everything that is needed to handle panics.
+        let outputs = <T::Ports as PortCollection>::port_infos()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, info)| {
+                OutputKind::from_port_info(info).map(|kind| (index as u32, kind))
+            })
+            .collect();
+        let sequence_urid = host_features
+            .raw_feature(sys::LV2_URID__map)
+            .and_then(|map| (map as *const sys::LV2_URID_Map).as_ref())
+            .and_then(|map| {
+                map.map.map(|map_fn| {
+                    (map_fn)(
+                        map.handle,
+                        sys::LV2_ATOM__Sequence.as_ptr() as *const c_char,
+                    )
+                })
+            })
+            .filter(|urid| *urid != 0);
+        let error_log = error_log(&host_features);
+
         // Instantiate the plugin.
         match T::new(&plugin_info, &mut init_features) {
             Some(instance) => {
@@ -161,6 +311,15 @@ impl<T: Plugin> PluginInstance<T> {
                     connections: <<T::Ports as PortCollection>::Cache as Default>::default(),
                     init_features,
                     audio_features,
+                    port_pointers: vec![
+                        std::ptr::null_mut();
+                        <T::Ports as PortCollection>::PORT_COUNT as usize
+                    ],
+                    outputs,
+                    sequence_urid,
+                    error_log,
+                    poisoned: false,
+                    latency: 0.0,
                 });
                 Box::leak(instance) as *mut Self as LV2_Handle
             }
@@ -177,7 +336,7 @@ impl<T: Plugin> PluginInstance<T> {
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn cleanup(instance: *mut c_void) {
         let instance = instance as *mut Self;
-        Box::from_raw(instance);
+        catch_panic("cleanup", || drop(Box::from_raw(instance)));
     }
 
     /// Call `activate`.
@@ -189,7 +348,9 @@ impl<T: Plugin> PluginInstance<T> {
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn activate(instance: *mut c_void) {
         let instance = &mut *(instance as *mut Self);
-        instance.instance.activate(&mut instance.init_features)
+        instance.call_guarded("activate", |instance| {
+            instance.instance.activate(&mut instance.init_features)
+        });
     }
 
     /// Call `deactivate`.
@@ -201,7 +362,9 @@ impl<T: Plugin> PluginInstance<T> {
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn deactivate(instance: *mut c_void) {
         let instance = &mut *(instance as *mut Self);
-        instance.instance.deactivate(&mut instance.init_features)
+        instance.call_guarded("deactivate", |instance| {
+            instance.instance.deactivate(&mut instance.init_features)
+        });
     }
 
     /// Update a port pointer.
@@ -212,8 +375,11 @@ impl<T: Plugin> PluginInstance<T> {
     ///
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn connect_port(instance: *mut c_void, port: u32, data: *mut c_void) {
-        let instance = instance as *mut Self;
-        (*instance).connections.connect(port, data)
+        let instance = &mut *(instance as *mut Self);
+        if let Some(pointer) = instance.port_pointers.get_mut(port as usize) {
+            *pointer = data;
+        }
+        instance.connections.connect(port, data)
     }
 
     /// Construct a port collection and call the `run` method.
@@ -225,11 +391,18 @@ impl<T: Plugin> PluginInstance<T> {
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn run(instance: *mut c_void, sample_count: u32) {
         let instance = &mut *(instance as *mut Self);
-        if let Some(mut ports) = instance.ports(sample_count) {
-            instance
-                .instance
-                .run(&mut ports, &mut instance.audio_features);
+        instance.write_latency();
+        let result = instance.call_guarded("run", |instance| {
+            if let Some(mut ports) = instance.ports(sample_count) {
+                let plugin = &mut instance.instance;
+                let features = &mut instance.audio_features;
+                rt_check::audio_class(|| plugin.run(&mut ports, features));
+            }
+        });
+        if result.is_none() {
+            instance.silence_outputs(sample_count);
         }
+        instance.read_latency();
     }
 
     /// Dereference the URI, call the `extension_data` function and return the pointer.
@@ -241,10 +414,114 @@ impl<T: Plugin> PluginInstance<T> {
     /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
     pub unsafe extern "C" fn extension_data(uri: *const c_char) -> *const c_void {
         let uri = Uri::from_ptr(uri);
-        if let Some(data) = T::extension_data(uri) {
-            data as *const _ as *const c_void
-        } else {
-            std::ptr::null()
+        match catch_panic("extension_data", || T::extension_data(uri)) {
+            Some(Some(data)) => data as *const _ as *const c_void,
+            _ => std::ptr::null(),
+        }
+    }
+
+    /// Call a function with the instance and catch a panic.
+    ///
+    /// If the function panics, the instance is poisoned and the panic message is reported to the host, together with the name of the function. This method is used by all functions of the C interface that call the plugin and should also be used by extensions.
+    ///
+    /// Returns `None` if the function panicked or if the instance is already poisoned, in which case the function isn't called at all.
+    pub fn call_guarded<R, F: FnOnce(&mut Self) -> R>(
+        &mut self,
+        function: &str,
+        f: F,
+    ) -> Option<R> {
+        if self.poisoned {
+            return None;
+        }
+        match panic::catch_unwind(AssertUnwindSafe(|| f(&mut *self))) {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                self.poisoned = true;
+                let message = panic_message(payload.as_ref());
+                let uri = T::uri().to_string_lossy();
+                let logged = match self.error_log.as_ref() {
+                    Some((log, error)) => log
+                        .print_fmt(
+                            *error,
+                            format_args!("<{}> panicked in `{}`: {}\n", uri, function, message),
+                        )
+                        .is_ok(),
+                    None => false,
+                };
+                if !logged {
+                    eprintln!("<{}> panicked in `{}`: {}", uri, function, message);
+                }
+                None
+            }
+        }
+    }
+
+    /// Return `true` if the plugin has panicked.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Write silence to all connected output ports.
+    ///
+    /// Audio, CV and control outputs are set to zero and atom outputs are set to an empty sequence.
+    unsafe fn silence_outputs(&mut self, sample_count: u32) {
+        for (index, kind) in self.outputs.iter() {
+            let pointer = match self.port_pointers.get(*index as usize) {
+                Some(pointer) if !pointer.is_null() => *pointer,
+                _ => continue,
+            };
+            match kind {
+                OutputKind::Sampled => {
+                    std::ptr::write_bytes(pointer as *mut f32, 0, sample_count as usize)
+                }
+                OutputKind::Control => *(pointer as *mut f32) = 0.0,
+                // The latency doesn't change because of the panic.
+                OutputKind::Latency => (),
+                OutputKind::Atom => {
+                    let sequence = &mut *(pointer as *mut sys::LV2_Atom_Sequence);
+                    let body_size = std::mem::size_of::<sys::LV2_Atom_Sequence_Body>() as u32;
+                    match self.sequence_urid {
+                        Some(urid) if sequence.atom.size >= body_size => {
+                            sequence.atom.type_ = urid;
+                            sequence.atom.size = body_size;
+                            sequence.body.unit = 0;
+                            sequence.body.pad = 0;
+                        }
+                        _ => {
+                            sequence.atom.type_ = 0;
+                            sequence.atom.size = 0;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the pointers of all connected latency ports.
+    fn latency_ports<'a>(&'a self) -> impl Iterator<Item = *mut f32> + 'a {
+        self.outputs
+            .iter()
+            .filter(|(_, kind)| matches!(kind, OutputKind::Latency))
+            .filter_map(move |(index, _)| self.port_pointers.get(*index as usize))
+            .filter(|pointer| !pointer.is_null())
+            .map(|pointer| *pointer as *mut f32)
+    }
+
+    /// Write the last reported latency to the latency ports.
+    ///
+    /// This is done before every `run` call, so that the ports are always up to date, even if the plugin doesn't set the latency.
+    unsafe fn write_latency(&mut self) {
+        let latency = self.latency;
+        for pointer in self.latency_ports() {
+            *pointer = latency;
+        }
+    }
+
+    /// Store the latency the plugin has written to the latency ports.
+    unsafe fn read_latency(&mut self) {
+        let pointer = self.latency_ports().next();
+        if let Some(pointer) = pointer {
+            self.latency = *pointer;
         }
     }
 
@@ -2,12 +2,18 @@
 //!
 //! Every plugin has a type of [`PortCollection`](trait.PortCollection.html) which is used to handle input/output ports. In order to make the creation of these port collection types easier, `PortCollection` can simply be derived. However, the macro that implements `PortCollection` requires the fields of the struct to have specific types. These types are provided in this module.
 use std::ffi::c_void;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 use std::ptr::NonNull;
-use urid::UriBound;
+use urid::{Uri, UriBound};
 
 pub use lv2_core_derive::*;
 
+mod control;
+mod group;
+
+pub use control::*;
+pub use group::*;
+
 /// Generalization of port types.
 ///
 /// A port can read input or create a pointer to the output, but the exact type of input/output (pointer) depends on the type of port. This trait generalizes these types and behaviour.
@@ -34,6 +40,41 @@ pub trait PortType {
     ///
     /// This method is unsafe because one needs to de-reference a raw pointer to implement this method.
     unsafe fn output_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::OutputPortType;
+
+    /// Restrict the input to a range of frames of the input of the whole block.
+    ///
+    /// This is used to process a block in sub-blocks. Sampled port types, like audio and CV, set `input` to the frames of `block` in `range`. The default implementation does nothing, which is right for port types that aren't sampled.
+    ///
+    /// # Safety
+    ///
+    /// `input` has to be a copy of `block`, sliced or not, and `range` has to be within the frames of `block`.
+    unsafe fn slice_input(
+        _input: &mut Self::InputPortType,
+        _block: &mut Self::InputPortType,
+        _range: Range<usize>,
+    ) {
+    }
+
+    /// Restrict the output to a range of frames of the output of the whole block.
+    ///
+    /// This is the output counterpart of [`slice_input`](#method.slice_input).
+    ///
+    /// # Safety
+    ///
+    /// `output` has to be a copy of `block`, sliced or not, and `range` has to be within the frames of `block`. `block` must not be used while `output` is in use.
+    unsafe fn slice_output(
+        _output: &mut Self::OutputPortType,
+        _block: &mut Self::OutputPortType,
+        _range: Range<usize>,
+    ) {
+    }
+
+    /// Add the RDF description of the port type to the information about a port.
+    ///
+    /// This is used to generate the Turtle description of a plugin. Implementations should at least add the class of the port type. The default implementation doesn't add anything.
+    fn describe(info: PortInfo) -> PortInfo {
+        info
+    }
 }
 
 /// Audio port type.
@@ -58,6 +99,29 @@ impl PortType for Audio {
     unsafe fn output_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::OutputPortType {
         std::slice::from_raw_parts_mut(pointer.as_ptr() as *mut f32, sample_count as usize)
     }
+
+    #[inline]
+    unsafe fn slice_input(
+        input: &mut &'static [f32],
+        block: &mut &'static [f32],
+        range: Range<usize>,
+    ) {
+        *input = &block[range];
+    }
+
+    #[inline]
+    unsafe fn slice_output(
+        output: &mut &'static mut [f32],
+        block: &mut &'static mut [f32],
+        range: Range<usize>,
+    ) {
+        assert!(range.start <= range.end && range.end <= block.len());
+        *output = std::slice::from_raw_parts_mut(block.as_mut_ptr().add(range.start), range.len());
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        info.class(<Self as UriBound>::uri())
+    }
 }
 
 /// Control value port type.
@@ -83,6 +147,10 @@ impl PortType for Control {
     unsafe fn output_from_raw(pointer: NonNull<c_void>, _sample_count: u32) -> &'static mut f32 {
         (pointer.as_ptr() as *mut f32).as_mut().unwrap()
     }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        info.class(<Self as UriBound>::uri())
+    }
 }
 
 /// CV port type.
@@ -107,6 +175,279 @@ impl PortType for CV {
     unsafe fn output_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::OutputPortType {
         std::slice::from_raw_parts_mut(pointer.as_ptr() as *mut f32, sample_count as usize)
     }
+
+    #[inline]
+    unsafe fn slice_input(
+        input: &mut &'static [f32],
+        block: &mut &'static [f32],
+        range: Range<usize>,
+    ) {
+        *input = &block[range];
+    }
+
+    #[inline]
+    unsafe fn slice_output(
+        output: &mut &'static mut [f32],
+        block: &mut &'static mut [f32],
+        range: Range<usize>,
+    ) {
+        assert!(range.start <= range.end && range.end <= block.len());
+        *output = std::slice::from_raw_parts_mut(block.as_mut_ptr().add(range.start), range.len());
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        info.class(<Self as UriBound>::uri())
+    }
+}
+
+/// The direction of a port.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortDirection {
+    /// The port is read by the plugin.
+    Input,
+    /// The port is written by the plugin.
+    Output,
+}
+
+/// A property of a port.
+///
+/// Port properties are hints for the host on how to treat a port. They are added to a port with the `properties` argument of the `port` attribute, for example `#[port(properties(Integer, Enumeration))]`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortProperty {
+    /// The port may be left unconnected by the host.
+    ConnectionOptional,
+    /// The port only takes the values of its scale points.
+    Enumeration,
+    /// The port only takes integer values.
+    Integer,
+    /// The port reports the latency of the plugin.
+    ReportsLatency,
+    /// The bounds and the default value of the port are multiplied by the sample rate.
+    SampleRate,
+    /// The port is a toggle: Values greater than zero are "on", all other values are "off".
+    Toggled,
+    /// The port is a side-chain input, which the host doesn't need to connect to a signal.
+    IsSideChain,
+    /// Changing the value of the port may cause audible artifacts.
+    CausesArtifacts,
+    /// Changing the value of the port is expensive.
+    Expensive,
+    /// The plugin can not handle values outside of the port's bounds.
+    HasStrictBounds,
+    /// The port is best displayed and changed on a logarithmic scale.
+    Logarithmic,
+    /// The port should not be automated by the host.
+    NotAutomatic,
+    /// The port should not be displayed in a generic UI.
+    NotOnGUI,
+    /// The port is a trigger which is reset to its default value after it has been set.
+    Trigger,
+}
+
+impl PortProperty {
+    /// Return the URI of the property.
+    pub fn uri(self) -> &'static Uri {
+        let uri: &'static [u8] = match self {
+            PortProperty::ConnectionOptional => sys::LV2_CORE__connectionOptional,
+            PortProperty::Enumeration => sys::LV2_CORE__enumeration,
+            PortProperty::Integer => sys::LV2_CORE__integer,
+            PortProperty::ReportsLatency => sys::LV2_CORE__reportsLatency,
+            PortProperty::SampleRate => sys::LV2_CORE__sampleRate,
+            PortProperty::Toggled => sys::LV2_CORE__toggled,
+            PortProperty::IsSideChain => b"http://lv2plug.in/ns/lv2core#isSideChain\0",
+            PortProperty::CausesArtifacts => sys::LV2_PORT_PROPS__causesArtifacts,
+            PortProperty::Expensive => sys::LV2_PORT_PROPS__expensive,
+            PortProperty::HasStrictBounds => sys::LV2_PORT_PROPS__hasStrictBounds,
+            PortProperty::Logarithmic => sys::LV2_PORT_PROPS__logarithmic,
+            PortProperty::NotAutomatic => sys::LV2_PORT_PROPS__notAutomatic,
+            PortProperty::NotOnGUI => sys::LV2_PORT_PROPS__notOnGUI,
+            PortProperty::Trigger => sys::LV2_PORT_PROPS__trigger,
+        };
+        Uri::from_bytes_with_nul(uri).unwrap()
+    }
+}
+
+/// Information about a port, used to generate the Turtle description of a plugin.
+///
+/// Instances are created by [`PortCollection::port_infos`](trait.PortCollection.html#method.port_infos), which is implemented by the `PortCollection` derive macro. The metadata of a port is declared with the `port` attribute on the field:
+///
+///     use lv2_core::port::*;
+///     use lv2_units::units::Decibel;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         #[port(name = "Gain", default = 0.0, minimum = -90.0, maximum = 24.0, unit(Decibel))]
+///         gain: InputPort<Control>,
+///         #[port(symbol = "in", name = "Input")]
+///         input: InputPort<Audio>,
+///         #[port(symbol = "out", name = "Output")]
+///         output: OutputPort<Audio>,
+///     }
+///
+///     let infos = Ports::port_infos();
+///     assert_eq!("gain", infos[0].symbol);
+///     assert_eq!(Some(24.0), infos[0].maximum);
+///     assert_eq!("Output", infos[2].name);
+///
+/// The symbol and the name default to the name of the field. The index of a port is its position in the list of port infos.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PortInfo {
+    /// The symbol of the port, a valid C identifier which is unique within the plugin.
+    pub symbol: String,
+    /// The human-readable name of the port.
+    pub name: String,
+    /// The direction of the port, if known.
+    pub direction: Option<PortDirection>,
+    /// The classes of the port, like `lv2:AudioPort`.
+    pub classes: Vec<&'static Uri>,
+    /// The default value of the port.
+    pub default: Option<f32>,
+    /// The minimal value of the port.
+    pub minimum: Option<f32>,
+    /// The maximal value of the port.
+    pub maximum: Option<f32>,
+    /// The unit of the port's values.
+    pub unit: Option<&'static Uri>,
+    /// The properties of the port.
+    pub properties: Vec<PortProperty>,
+    /// The scale points of the port, as pairs of label and value.
+    pub scale_points: Vec<(String, f32)>,
+    /// Additional statements about the port, as pairs of predicate and object.
+    pub statements: Vec<(&'static Uri, &'static Uri)>,
+    /// The designation of the port, like the channel of a port group.
+    pub designation: Option<&'static Uri>,
+    /// The group the port belongs to.
+    pub group: Option<GroupInfo>,
+}
+
+impl PortInfo {
+    /// Create a new port info without any further information.
+    ///
+    /// The symbol is also used as the name of the port.
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_owned(),
+            name: symbol.to_owned(),
+            direction: None,
+            classes: Vec::new(),
+            default: None,
+            minimum: None,
+            maximum: None,
+            unit: None,
+            properties: Vec::new(),
+            scale_points: Vec::new(),
+            statements: Vec::new(),
+            designation: None,
+            group: None,
+        }
+    }
+
+    /// Set the name of the port.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    /// Set the direction of the port.
+    pub fn direction(mut self, direction: PortDirection) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Add a class to the port.
+    pub fn class(mut self, class: &'static Uri) -> Self {
+        self.classes.push(class);
+        self
+    }
+
+    /// Set the default value of the port.
+    pub fn default_value(mut self, default: f32) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Set the minimal value of the port.
+    pub fn minimum(mut self, minimum: f32) -> Self {
+        self.minimum = Some(minimum);
+        self
+    }
+
+    /// Set the maximal value of the port.
+    pub fn maximum(mut self, maximum: f32) -> Self {
+        self.maximum = Some(maximum);
+        self
+    }
+
+    /// Set the unit of the port's values.
+    ///
+    /// The unit types are provided by the `lv2-units` crate.
+    pub fn unit<U: UriBound>(mut self) -> Self {
+        self.unit = Some(U::uri());
+        self
+    }
+
+    /// Add a property to the port.
+    pub fn property(mut self, property: PortProperty) -> Self {
+        if !self.properties.contains(&property) {
+            self.properties.push(property);
+        }
+        self
+    }
+
+    /// Add a scale point, a labeled value, to the port.
+    pub fn scale_point(mut self, label: &str, value: f32) -> Self {
+        self.scale_points.push((label.to_owned(), value));
+        self
+    }
+
+    /// Add a statement to the description of the port.
+    pub fn statement(mut self, predicate: &'static Uri, object: &'static Uri) -> Self {
+        self.statements.push((predicate, object));
+        self
+    }
+
+    /// Set the designation of the port.
+    pub fn designation(mut self, designation: &'static Uri) -> Self {
+        self.designation = Some(designation);
+        self
+    }
+
+    /// Add the port to a group.
+    pub fn group(mut self, group: GroupInfo) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Set the symbol and the name of the port's group.
+    ///
+    /// The symbol of the group is prepended to the symbol of the port and the name of the group is prepended to the name of the port. This is used by the `PortCollection` derive macro for port group fields. If the port doesn't belong to a group, nothing happens.
+    pub fn group_names(mut self, symbol: &str, name: &str) -> Self {
+        if let Some(group) = self.group.as_mut() {
+            group.symbol = symbol.to_owned();
+            group.name = name.to_owned();
+            self.symbol = format!("{}_{}", symbol, self.symbol);
+            self.name = format!("{} {}", name, self.name);
+        }
+        self
+    }
+
+    /// Declare the port's group as the side-chain of the group with the given symbol.
+    ///
+    /// This also adds the [`IsSideChain`](enum.PortProperty.html#variant.IsSideChain) property. If the port doesn't belong to a group, only the property is added.
+    pub fn sidechain_of(mut self, symbol: &str) -> Self {
+        if let Some(group) = self.group.as_mut() {
+            group.sidechain_of = Some(symbol.to_owned());
+        }
+        self.property(PortProperty::IsSideChain)
+    }
+
+    /// Declare that the port supports the given type of data, for example a type of atoms or events.
+    pub fn supports<T: UriBound>(self) -> Self {
+        self.statement(
+            Uri::from_bytes_with_nul(sys::LV2_ATOM__supports).unwrap(),
+            T::uri(),
+        )
+    }
 }
 
 /// Abstraction of safe port handles.
@@ -119,6 +460,22 @@ pub trait PortHandle: Sized {
     ///
     /// Implementing this method requires a de-referentation of a raw pointer and therefore, it is unsafe.
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self>;
+
+    /// Create the information about a port with this handle.
+    ///
+    /// The default implementation only contains the symbol.
+    fn port_info(symbol: &str) -> PortInfo {
+        PortInfo::new(symbol)
+    }
+
+    /// Restrict the port to a range of frames of the port of the whole block.
+    ///
+    /// This is used by [`SlicePorts`](trait.SlicePorts.html). The default implementation does nothing, which is right for handles of ports that aren't sampled.
+    ///
+    /// # Safety
+    ///
+    /// `self` has to be a copy of `block`, sliced or not, and `range` has to be within the frames of `block`. `block` must not be used while `self` is in use.
+    unsafe fn slice_from(&mut self, _block: &mut Self, _range: Range<usize>) {}
 }
 
 /// Handle for input ports.
@@ -148,6 +505,19 @@ impl<T: PortType> PortHandle for InputPort<T> {
             None
         }
     }
+
+    fn port_info(symbol: &str) -> PortInfo {
+        T::describe(
+            PortInfo::new(symbol)
+                .class(Uri::from_bytes_with_nul(sys::LV2_CORE__InputPort).unwrap())
+                .direction(PortDirection::Input),
+        )
+    }
+
+    #[inline]
+    unsafe fn slice_from(&mut self, block: &mut Self, range: Range<usize>) {
+        T::slice_input(&mut self.port, &mut block.port, range)
+    }
 }
 
 /// Handle for output ports.
@@ -184,12 +554,67 @@ impl<T: PortType> PortHandle for OutputPort<T> {
             None
         }
     }
+
+    fn port_info(symbol: &str) -> PortInfo {
+        T::describe(
+            PortInfo::new(symbol)
+                .class(Uri::from_bytes_with_nul(sys::LV2_CORE__OutputPort).unwrap())
+                .direction(PortDirection::Output),
+        )
+    }
+
+    #[inline]
+    unsafe fn slice_from(&mut self, block: &mut Self, range: Range<usize>) {
+        T::slice_output(&mut self.port, &mut block.port, range)
+    }
 }
 
 impl<T: PortHandle> PortHandle for Option<T> {
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32) -> Option<Self> {
         Some(T::from_raw(pointer, sample_count))
     }
+
+    fn port_info(symbol: &str) -> PortInfo {
+        T::port_info(symbol).property(PortProperty::ConnectionOptional)
+    }
+
+    unsafe fn slice_from(&mut self, block: &mut Self, range: Range<usize>) {
+        if let (Some(port), Some(block)) = (self.as_mut(), block.as_mut()) {
+            port.slice_from(block, range);
+        }
+    }
+}
+
+/// Iterate over the buffers of corresponding input and output ports.
+///
+/// This pairs every input port with the output port of the same position, which is handy for arrays of channels that are processed independently:
+///
+///     use lv2_core::port::*;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         gain: InputPort<Control>,
+///         inputs: [InputPort<Audio>; 2],
+///         outputs: [OutputPort<Audio>; 2],
+///     }
+///
+///     fn run(ports: &mut Ports) {
+///         for (input, output) in zip_ports(&ports.inputs, &mut ports.outputs) {
+///             for (input_sample, output_sample) in input.iter().zip(output.iter_mut()) {
+///                 *output_sample = *input_sample * *ports.gain;
+///             }
+///         }
+///     }
+///
+/// The iterator stops at the end of the shorter slice.
+pub fn zip_ports<'a, T: PortType>(
+    inputs: &'a [InputPort<T>],
+    outputs: &'a mut [OutputPort<T>],
+) -> impl Iterator<Item = (&'a T::InputPortType, &'a mut T::OutputPortType)> {
+    inputs
+        .iter()
+        .map(|input| &**input)
+        .zip(outputs.iter_mut().map(|output| &mut **output))
 }
 
 /// Collection of IO ports.
@@ -212,12 +637,84 @@ impl<T: PortHandle> PortHandle for Option<T> {
 ///     }
 ///
 /// Please note that port indices are mapped in the order of occurence; In our example, the implementation will treat `audio_input` as port `0`, `audio_output` as port `1` and so on. Therefore, your plugin definition and your port collection have to match. Otherwise, undefined behaviour will occur.
+///
+/// Fields may also be fixed-size arrays of port handles, which occupy one index per element. The symbols of their ports are the symbol of the field, followed by the number of the element, starting with one:
+///
+///     use lv2_core::port::*;
+///
+///     #[derive(PortCollection)]
+///     struct Mixer {
+///         #[port(name = "Level", default = 1.0)]
+///         levels: [Option<InputPort<Control>>; 4],
+///         inputs: [InputPort<Audio>; 4],
+///         output: OutputPort<Audio>,
+///     }
+///
+///     let infos = Mixer::port_infos();
+///     assert_eq!(9, Mixer::PORT_COUNT);
+///     assert_eq!("levels_2", infos[1].symbol);
+///     assert_eq!("Level 2", infos[1].name);
+///     assert_eq!("inputs_1", infos[4].symbol);
+///
+/// The length of an array has to be an integer literal.
+///
+/// Port collections can be composed: A field with the `flatten` attribute contains another port collection, whose ports are placed at the field's position. The symbols of the nested ports are kept, unless the field declares a `symbol` or a `name`, which is then used as a prefix:
+///
+///     use lv2_core::port::*;
+///
+///     #[derive(PortCollection)]
+///     struct Envelope {
+///         attack: InputPort<Control>,
+///         release: InputPort<Control>,
+///     }
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         input: InputPort<Audio>,
+///         #[flatten]
+///         envelope: Envelope,
+///         #[flatten]
+///         #[port(symbol = "filter", name = "Filter")]
+///         filter_envelope: Envelope,
+///     }
+///
+///     let infos = Ports::port_infos();
+///     assert_eq!(5, Ports::PORT_COUNT);
+///     assert_eq!("release", infos[2].symbol);
+///     assert_eq!("filter_attack", infos[3].symbol);
+///     assert_eq!("Filter attack", infos[3].name);
+///
+/// [Port groups](struct.InputGroup.html) are flattened the same way, but their ports are named after the field instead.
+///
+/// The other arguments of the `port` attribute describe a single port and are rejected on flattened fields:
+///
+/// ```compile_fail
+/// use lv2_core::port::*;
+///
+/// #[derive(PortCollection)]
+/// struct Envelope {
+///     attack: InputPort<Control>,
+///     release: InputPort<Control>,
+/// }
+///
+/// #[derive(PortCollection)]
+/// struct Ports {
+///     #[flatten]
+///     #[port(default = 0.5)]
+///     envelope: Envelope,
+/// }
+/// ```
 pub trait PortCollection: Sized {
     /// The type of the port pointer cache.
     ///
     /// The host passes port pointers to the plugin one by one and in an undefined order. Therefore, the plugin instance can not collect these pointers in the port collection directly. Instead, the pointers are stored in a cache which is then used to create the proper port collection.
     type Cache: PortPointerCache;
 
+    /// The number of ports in the collection.
+    ///
+    /// The ports of a collection have consecutive indices, starting with zero. When a collection is contained in another collection, its indices are offset by the number of ports before it.
+    const PORT_COUNT: u32;
+
     /// Try to construct a port collection instance from a port pointer cache.
     ///
     /// If one of the port connection pointers is null, this method will return `None`, because a `PortCollection` can not be constructed.
@@ -226,16 +723,43 @@ pub trait PortCollection: Sized {
     ///
     /// Since the pointer cache is only storing the pointers, implementing this method requires the de-referencation of raw pointers and therefore, this method is unsafe.
     unsafe fn from_connections(cache: &Self::Cache, sample_count: u32) -> Option<Self>;
+
+    /// Return the information about all ports of the collection, in the order of their indices.
+    ///
+    /// This is used to generate the Turtle description of the plugin. The plugin instance also uses it to find the output ports that are silenced after the plugin panicked, as well as the latency port. Manual implementations should therefore return the information about all of their ports: The default implementation returns an empty list, which leaves the stale content of the outputs in place if the plugin panics.
+    fn port_infos() -> Vec<PortInfo> {
+        Vec::new()
+    }
 }
 
 impl PortCollection for () {
     type Cache = ();
 
+    const PORT_COUNT: u32 = 0;
+
     unsafe fn from_connections(_cache: &(), _sample_count: u32) -> Option<Self> {
         Some(())
     }
 }
 
+/// Port collections whose sampled ports can be restricted to a range of frames.
+///
+/// This is used to process a block in sub-blocks, for example between the events of a sequence with `split_at_events` of `lv2-atom`. Deriving `PortCollection` also implements this trait.
+pub trait SlicePorts: PortCollection {
+    /// Restrict the sampled ports, like audio and CV ports, to a range of frames of the ports of the whole block.
+    ///
+    /// Ports that aren't sampled are left untouched.
+    ///
+    /// # Safety
+    ///
+    /// `self` has to be a bitwise copy of `block`, sliced or not, and `range` has to be within the frames of `block`. `block` must not be used while `self` is in use.
+    unsafe fn slice_from(&mut self, block: &mut Self, range: Range<usize>);
+}
+
+impl SlicePorts for () {
+    unsafe fn slice_from(&mut self, _block: &mut (), _range: Range<usize>) {}
+}
+
 /// Cache for port connection pointers.
 ///
 /// The host will pass the port connection pointers one by one and in an undefined order. Therefore, the `PortCollection` struct can not be created instantly. Instead, the pointers will be stored in a cache, which is then used to create a proper port collection for the plugin.
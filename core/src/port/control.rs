@@ -0,0 +1,370 @@
+use super::*;
+use std::marker::PhantomData;
+
+/// Generalization of typed control port types.
+///
+/// Control ports transport a single `f32` per `run` call, but many controls have a more specific meaning, like a switch or a selection from a list. Typed control ports convert the raw value into a Rust value, clamp it into the valid range and describe the port with the appropriate port properties.
+///
+/// Every type implementing this trait is also a [`PortType`](trait.PortType.html), which reads [values](#associatedtype.Value) from input ports and writes them to output ports via a [`ControlOutput`](struct.ControlOutput.html):
+///
+///     use lv2_core::port::*;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         bypass: InputPort<Toggle>,
+///         steps: InputPort<IntControl>,
+///         mix: InputPort<RangedControl<0, 100>>,
+///         active: OutputPort<Toggle>,
+///     }
+///
+///     fn run(ports: &mut Ports) {
+///         let active: bool = !*ports.bypass && *ports.steps > 0;
+///         ports.active.set(active);
+///     }
+///
+///     assert_eq!(Some(100.0), Ports::port_infos()[2].maximum);
+pub trait TypedControl {
+    /// The Rust type of the control's values.
+    type Value: Copy;
+
+    /// Convert a raw control value, clamping it into the valid range.
+    fn from_control(value: f32) -> Self::Value;
+
+    /// Convert a value to a raw control value.
+    fn to_control(value: Self::Value) -> f32;
+
+    /// Add the properties and bounds of the control to the information about a port.
+    ///
+    /// The class of control ports is already added. The default implementation doesn't add anything.
+    fn describe(info: PortInfo) -> PortInfo {
+        info
+    }
+}
+
+impl<T: TypedControl> PortType for T {
+    type InputPortType = T::Value;
+    type OutputPortType = ControlOutput<T>;
+
+    #[inline]
+    unsafe fn input_from_raw(pointer: NonNull<c_void>, _sample_count: u32) -> T::Value {
+        T::from_control(*(pointer.cast().as_ref()))
+    }
+
+    unsafe fn output_from_raw(pointer: NonNull<c_void>, _sample_count: u32) -> ControlOutput<T> {
+        ControlOutput {
+            value: (pointer.as_ptr() as *mut f32).as_mut().unwrap(),
+            control: PhantomData,
+        }
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        <T as TypedControl>::describe(info.class(Control::uri()))
+    }
+}
+
+/// Output of a typed control port.
+pub struct ControlOutput<T: TypedControl> {
+    value: &'static mut f32,
+    control: PhantomData<T>,
+}
+
+impl<T: TypedControl> ControlOutput<T> {
+    /// Write a value to the port.
+    pub fn set(&mut self, value: T::Value) {
+        *self.value = T::to_control(value);
+    }
+
+    /// Return the last value written to the port.
+    pub fn get(&self) -> T::Value {
+        T::from_control(*self.value)
+    }
+}
+
+/// Handle for the output port that reports the latency of the plugin.
+///
+/// Plugins that delay their signal, like lookahead limiters or linear-phase filters, have to report this delay to the host, which then compensates it. The port is described with the `lv2:latency` designation and the `lv2:reportsLatency` property. The latency is set in frames:
+///
+///     use lv2_core::port::*;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         input: InputPort<Audio>,
+///         output: OutputPort<Audio>,
+///         latency: LatencyOutput,
+///     }
+///
+///     fn run(ports: &mut Ports) {
+///         ports.latency.set(64.0);
+///     }
+///
+/// The plugin instance remembers the last latency that has been set and writes it to the port on every call to `run`, even if the host only queries the latency by running the plugin with zero frames, the plugin doesn't set the latency in this call, or the plugin has panicked.
+pub struct LatencyOutput {
+    value: &'static mut f32,
+}
+
+impl LatencyOutput {
+    /// Set the latency of the plugin, in frames.
+    pub fn set(&mut self, latency: f32) {
+        *self.value = latency.max(0.0);
+    }
+
+    /// Return the current latency of the plugin, in frames.
+    pub fn get(&self) -> f32 {
+        *self.value
+    }
+}
+
+impl PortHandle for LatencyOutput {
+    unsafe fn from_raw(pointer: *mut c_void, _sample_count: u32) -> Option<Self> {
+        (pointer as *mut f32).as_mut().map(|value| Self { value })
+    }
+
+    fn port_info(symbol: &str) -> PortInfo {
+        PortInfo::new(symbol)
+            .class(Uri::from_bytes_with_nul(sys::LV2_CORE__OutputPort).unwrap())
+            .class(Control::uri())
+            .direction(PortDirection::Output)
+            .designation(Uri::from_bytes_with_nul(sys::LV2_CORE__latency).unwrap())
+            .property(PortProperty::ReportsLatency)
+            .minimum(0.0)
+    }
+}
+
+/// A switch that is either on or off.
+///
+/// Every positive value is interpreted as `true`.
+pub struct Toggle;
+
+impl TypedControl for Toggle {
+    type Value = bool;
+
+    fn from_control(value: f32) -> bool {
+        value > 0.0
+    }
+
+    fn to_control(value: bool) -> f32 {
+        if value {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        info.property(PortProperty::Toggled)
+            .minimum(0.0)
+            .maximum(1.0)
+    }
+}
+
+/// A toggle that is only on for a single `run` call, like a button.
+///
+/// The host resets the port after it has been processed.
+pub struct Trigger;
+
+impl TypedControl for Trigger {
+    type Value = bool;
+
+    fn from_control(value: f32) -> bool {
+        Toggle::from_control(value)
+    }
+
+    fn to_control(value: bool) -> f32 {
+        Toggle::to_control(value)
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        <Toggle as TypedControl>::describe(info).property(PortProperty::Trigger)
+    }
+}
+
+/// A control with integer values.
+///
+/// Raw values are rounded to the nearest integer.
+pub struct IntControl;
+
+impl TypedControl for IntControl {
+    type Value = i32;
+
+    fn from_control(value: f32) -> i32 {
+        value.round() as i32
+    }
+
+    fn to_control(value: i32) -> f32 {
+        value as f32
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        info.property(PortProperty::Integer)
+    }
+}
+
+/// A control with values between `MIN` and `MAX`.
+///
+/// Raw values are clamped into the range, and so are values written to outputs.
+pub struct RangedControl<const MIN: i32, const MAX: i32>;
+
+impl<const MIN: i32, const MAX: i32> TypedControl for RangedControl<MIN, MAX> {
+    type Value = f32;
+
+    fn from_control(value: f32) -> f32 {
+        value.max(MIN as f32).min(MAX as f32)
+    }
+
+    fn to_control(value: f32) -> f32 {
+        Self::from_control(value)
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        info.minimum(MIN as f32).maximum(MAX as f32)
+    }
+}
+
+/// A control with values between `MIN` and `MAX` that is perceived logarithmically, like a frequency.
+///
+/// This behaves like a [`RangedControl`](struct.RangedControl.html), but hosts should display it on a logarithmic scale. Therefore, `MIN` has to be positive.
+pub struct LogControl<const MIN: i32, const MAX: i32>;
+
+impl<const MIN: i32, const MAX: i32> TypedControl for LogControl<MIN, MAX> {
+    type Value = f32;
+
+    fn from_control(value: f32) -> f32 {
+        RangedControl::<MIN, MAX>::from_control(value)
+    }
+
+    fn to_control(value: f32) -> f32 {
+        RangedControl::<MIN, MAX>::to_control(value)
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        <RangedControl<MIN, MAX> as TypedControl>::describe(info)
+            .property(PortProperty::Logarithmic)
+    }
+}
+
+/// An enumeration whose variants can be selected with a control port.
+///
+/// This trait should be derived for enums without fields. The variants are numbered in the order of their declaration, starting with zero, and their labels are their names, unless they are declared with the `label` attribute:
+///
+///     use lv2_core::port::*;
+///
+///     #[derive(ControlEnum, Clone, Copy, PartialEq, Debug)]
+///     enum Waveform {
+///         Sine,
+///         #[label = "Sawtooth wave"]
+///         Saw,
+///     }
+///
+///     assert_eq!(Waveform::Saw, EnumControl::<Waveform>::from_control(1.0));
+///     assert_eq!(Waveform::Saw, EnumControl::<Waveform>::from_control(5.0));
+///     assert_eq!(&["Sine", "Sawtooth wave"], Waveform::LABELS);
+pub trait ControlEnum: Copy + 'static {
+    /// All variants, in the order of their values.
+    const VARIANTS: &'static [Self];
+    /// The labels of the variants, in the same order.
+    const LABELS: &'static [&'static str];
+
+    /// Return the position of the variant.
+    fn index(self) -> usize;
+}
+
+/// A control that selects a variant of an enumeration.
+///
+/// Raw values are rounded and clamped to the indices of the variants, and the variants are described as scale points of the port.
+pub struct EnumControl<E: ControlEnum> {
+    enumeration: PhantomData<E>,
+}
+
+impl<E: ControlEnum> TypedControl for EnumControl<E> {
+    type Value = E;
+
+    fn from_control(value: f32) -> E {
+        let last = E::VARIANTS.len() - 1;
+        let index = value.round().max(0.0) as usize;
+        E::VARIANTS[index.min(last)]
+    }
+
+    fn to_control(value: E) -> f32 {
+        value.index() as f32
+    }
+
+    fn describe(info: PortInfo) -> PortInfo {
+        E::LABELS.iter().enumerate().fold(
+            info.property(PortProperty::Integer)
+                .property(PortProperty::Enumeration)
+                .minimum(0.0)
+                .maximum((E::VARIANTS.len() - 1) as f32),
+            |info, (index, label)| info.scale_point(label, index as f32),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::port::*;
+
+    #[derive(ControlEnum, Clone, Copy, PartialEq, Debug)]
+    enum Mode {
+        Off,
+        #[label = "Half speed"]
+        Half,
+        Full,
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert!(Toggle::from_control(0.5));
+        assert!(!Toggle::from_control(0.0));
+        assert_eq!(3, IntControl::from_control(2.6));
+        assert_eq!(-10.0, RangedControl::<-10, 10>::from_control(-20.0));
+        assert_eq!(10.0, RangedControl::<-10, 10>::to_control(11.0));
+        assert_eq!(Mode::Off, EnumControl::<Mode>::from_control(-1.0));
+        assert_eq!(Mode::Full, EnumControl::<Mode>::from_control(7.0));
+        assert_eq!(1.0, EnumControl::<Mode>::to_control(Mode::Half));
+    }
+
+    #[test]
+    fn test_port_infos() {
+        let info = InputPort::<EnumControl<Mode>>::port_info("mode");
+        assert_eq!(Control::uri(), info.classes[1]);
+        assert_eq!(Some(2.0), info.maximum);
+        assert_eq!(
+            vec![PortProperty::Integer, PortProperty::Enumeration],
+            info.properties
+        );
+        assert_eq!(("Half speed".to_owned(), 1.0), info.scale_points[1]);
+
+        let info = OutputPort::<LogControl<20, 20000>>::port_info("frequency");
+        assert_eq!(Some(20.0), info.minimum);
+        assert_eq!(vec![PortProperty::Logarithmic], info.properties);
+    }
+
+    #[test]
+    fn test_latency_output() {
+        let info = LatencyOutput::port_info("latency");
+        assert_eq!(Some(PortDirection::Output), info.direction);
+        assert_eq!(
+            Some(sys::LV2_CORE__latency as &[u8]),
+            info.designation.map(|uri| uri.to_bytes_with_nul())
+        );
+        assert_eq!(vec![PortProperty::ReportsLatency], info.properties);
+
+        let mut value: f32 = 0.0;
+        let mut output =
+            unsafe { LatencyOutput::from_raw(&mut value as *mut f32 as _, 0) }.unwrap();
+        output.set(-3.0);
+        assert_eq!(0.0, output.get());
+        output.set(128.0);
+        assert_eq!(128.0, value);
+    }
+
+    #[test]
+    fn test_output() {
+        let mut value: f32 = 0.0;
+        let pointer = std::ptr::NonNull::from(&mut value).cast();
+        let mut output = unsafe { Trigger::output_from_raw(pointer, 1) };
+        output.set(true);
+        assert!(output.get());
+        assert_eq!(1.0, value);
+    }
+}
@@ -0,0 +1,510 @@
+use super::*;
+use std::marker::PhantomData;
+
+/// The maximal number of channels of a port group.
+const MAX_CHANNELS: usize = 8;
+
+/// A channel of a port group.
+///
+/// Every port of a group is designated to one of these channels, which tells the host the role of the port.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Channel {
+    Left,
+    Right,
+    Center,
+    Side,
+    CenterLeft,
+    CenterRight,
+    SideLeft,
+    SideRight,
+    RearLeft,
+    RearRight,
+    RearCenter,
+    LowFrequencyEffects,
+}
+
+impl Channel {
+    /// Return the URI of the channel's designation.
+    pub fn uri(self) -> &'static Uri {
+        let uri: &'static [u8] = match self {
+            Channel::Left => sys::LV2_PORT_GROUPS__left,
+            Channel::Right => sys::LV2_PORT_GROUPS__right,
+            Channel::Center => sys::LV2_PORT_GROUPS__center,
+            Channel::Side => sys::LV2_PORT_GROUPS__side,
+            Channel::CenterLeft => sys::LV2_PORT_GROUPS__centerLeft,
+            Channel::CenterRight => sys::LV2_PORT_GROUPS__centerRight,
+            Channel::SideLeft => sys::LV2_PORT_GROUPS__sideLeft,
+            Channel::SideRight => sys::LV2_PORT_GROUPS__sideRight,
+            Channel::RearLeft => sys::LV2_PORT_GROUPS__rearLeft,
+            Channel::RearRight => sys::LV2_PORT_GROUPS__rearRight,
+            Channel::RearCenter => sys::LV2_PORT_GROUPS__rearCenter,
+            Channel::LowFrequencyEffects => sys::LV2_PORT_GROUPS__lowFrequencyEffects,
+        };
+        Uri::from_bytes_with_nul(uri).unwrap()
+    }
+
+    /// Return the symbol of the channel, which is used as the suffix of the port symbol.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Channel::Left => "left",
+            Channel::Right => "right",
+            Channel::Center => "center",
+            Channel::Side => "side",
+            Channel::CenterLeft => "center_left",
+            Channel::CenterRight => "center_right",
+            Channel::SideLeft => "side_left",
+            Channel::SideRight => "side_right",
+            Channel::RearLeft => "rear_left",
+            Channel::RearRight => "rear_right",
+            Channel::RearCenter => "rear_center",
+            Channel::LowFrequencyEffects => "lfe",
+        }
+    }
+
+    /// Return the human-readable name of the channel.
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Left => "Left",
+            Channel::Right => "Right",
+            Channel::Center => "Center",
+            Channel::Side => "Side",
+            Channel::CenterLeft => "Center Left",
+            Channel::CenterRight => "Center Right",
+            Channel::SideLeft => "Side Left",
+            Channel::SideRight => "Side Right",
+            Channel::RearLeft => "Rear Left",
+            Channel::RearRight => "Rear Right",
+            Channel::RearCenter => "Rear Center",
+            Channel::LowFrequencyEffects => "LFE",
+        }
+    }
+}
+
+/// The type of a port group, which defines its channels.
+///
+/// The channels are in the order of their port indices. A group can have at most eight channels; Using a group type with more channels in a port collection fails to compile:
+///
+/// ```compile_fail
+/// use lv2_core::prelude::*;
+/// use urid::*;
+///
+/// #[uri("urn:lv2-core:nine-channels")]
+/// struct NineChannels;
+///
+/// impl GroupType for NineChannels {
+///     const CHANNELS: &'static [Channel] = &[Channel::Center; 9];
+/// }
+///
+/// #[derive(PortCollection)]
+/// struct Ports {
+///     #[flatten]
+///     input: InputGroup<Audio, NineChannels>,
+/// }
+///
+/// let _ = Ports::PORT_COUNT;
+/// ```
+pub trait GroupType: UriBound {
+    /// The channels of the group.
+    const CHANNELS: &'static [Channel];
+}
+
+/// Return the number of channels of a group type, which is also the number of ports of the group.
+///
+/// This fails at compile time if the group has more channels than a group can store.
+const fn channel_count<G: GroupType>() -> u32 {
+    assert!(
+        G::CHANNELS.len() <= MAX_CHANNELS,
+        "A port group can't have more than eight channels"
+    );
+    G::CHANNELS.len() as u32
+}
+
+macro_rules! make_group_type {
+    ($name:ident, $uri:expr, [$($channel:ident),*], $doc:literal) => {
+        #[doc = $doc]
+        pub struct $name;
+
+        unsafe impl UriBound for $name {
+            const URI: &'static [u8] = $uri;
+        }
+
+        impl GroupType for $name {
+            const CHANNELS: &'static [Channel] = &[$(Channel::$channel),*];
+        }
+    };
+}
+
+make_group_type!(
+    Mono,
+    sys::LV2_PORT_GROUPS__MonoGroup,
+    [Center],
+    "A group with a single channel."
+);
+make_group_type!(
+    Stereo,
+    sys::LV2_PORT_GROUPS__StereoGroup,
+    [Left, Right],
+    "A group with a left and a right channel."
+);
+make_group_type!(
+    MidSide,
+    sys::LV2_PORT_GROUPS__MidSideGroup,
+    [Center, Side],
+    "A group with a mid and a side channel."
+);
+make_group_type!(
+    ThreePointZero,
+    sys::LV2_PORT_GROUPS__ThreePointZeroGroup,
+    [Left, Right, RearCenter],
+    "A 3.0 surround group."
+);
+make_group_type!(
+    FourPointZero,
+    sys::LV2_PORT_GROUPS__FourPointZeroGroup,
+    [Left, Center, Right, RearCenter],
+    "A 4.0 surround group."
+);
+make_group_type!(
+    FivePointZero,
+    sys::LV2_PORT_GROUPS__FivePointZeroGroup,
+    [Left, Center, Right, RearLeft, RearRight],
+    "A 5.0 surround group."
+);
+make_group_type!(
+    FivePointOne,
+    sys::LV2_PORT_GROUPS__FivePointOneGroup,
+    [
+        Left,
+        Center,
+        Right,
+        RearLeft,
+        RearRight,
+        LowFrequencyEffects
+    ],
+    "A 5.1 surround group."
+);
+make_group_type!(
+    SixPointOne,
+    sys::LV2_PORT_GROUPS__SixPointOneGroup,
+    [
+        Left,
+        Center,
+        Right,
+        SideLeft,
+        SideRight,
+        RearCenter,
+        LowFrequencyEffects
+    ],
+    "A 6.1 surround group."
+);
+make_group_type!(
+    SevenPointOne,
+    sys::LV2_PORT_GROUPS__SevenPointOneGroup,
+    [
+        Left,
+        Center,
+        Right,
+        SideLeft,
+        SideRight,
+        RearLeft,
+        RearRight,
+        LowFrequencyEffects
+    ],
+    "A 7.1 surround group."
+);
+make_group_type!(
+    SevenPointOneWide,
+    sys::LV2_PORT_GROUPS__SevenPointOneWideGroup,
+    [
+        Left,
+        CenterLeft,
+        Center,
+        CenterRight,
+        Right,
+        RearLeft,
+        RearRight,
+        LowFrequencyEffects
+    ],
+    "A 7.1 surround group with wide front channels."
+);
+
+/// Information about a port group, used to generate the Turtle description of a plugin.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GroupInfo {
+    /// The symbol of the group, which is unique within the plugin.
+    pub symbol: String,
+    /// The human-readable name of the group.
+    pub name: String,
+    /// The classes of the group, like `pg:StereoGroup`.
+    pub classes: Vec<&'static Uri>,
+    /// The symbol of the group this group is a side-chain of.
+    pub sidechain_of: Option<String>,
+}
+
+impl GroupInfo {
+    /// Create the information about a group of the given type.
+    ///
+    /// The symbol and the name are set by [`PortInfo::group_names`](struct.PortInfo.html#method.group_names).
+    pub fn new<G: GroupType>(direction: PortDirection) -> Self {
+        let direction_class: &'static [u8] = match direction {
+            PortDirection::Input => sys::LV2_PORT_GROUPS__InputGroup,
+            PortDirection::Output => sys::LV2_PORT_GROUPS__OutputGroup,
+        };
+        Self {
+            symbol: String::new(),
+            name: String::new(),
+            classes: vec![G::uri(), Uri::from_bytes_with_nul(direction_class).unwrap()],
+            sidechain_of: None,
+        }
+    }
+}
+
+/// Cache for the port connection pointers of a group.
+pub struct GroupPointerCache {
+    pointers: [*mut c_void; MAX_CHANNELS],
+}
+
+impl Default for GroupPointerCache {
+    fn default() -> Self {
+        Self {
+            pointers: [std::ptr::null_mut(); MAX_CHANNELS],
+        }
+    }
+}
+
+impl PortPointerCache for GroupPointerCache {
+    fn connect(&mut self, index: u32, pointer: *mut c_void) {
+        if let Some(stored) = self.pointers.get_mut(index as usize) {
+            *stored = pointer;
+        }
+    }
+}
+
+/// Create the port information of all channels of a group.
+fn group_port_infos<G: GroupType, P: PortHandle>(direction: PortDirection) -> Vec<PortInfo> {
+    G::CHANNELS
+        .iter()
+        .map(|channel| {
+            P::port_info(channel.symbol())
+                .name(channel.name())
+                .designation(channel.uri())
+                .group(GroupInfo::new::<G>(direction))
+        })
+        .collect()
+}
+
+macro_rules! make_channel_accessors {
+    ($type:ident, $($method:ident => $channel:ident),*) => {
+        $(
+            #[doc = concat!("Return the `", stringify!($channel), "` channel, if the group has one.")]
+            pub fn $method(&self) -> Option<&T::$type> {
+                self.get(Channel::$channel)
+            }
+        )*
+    };
+}
+
+/// A group of input ports, like the left and right channels of a stereo input.
+///
+/// A group consumes one port index per channel of its [group type](trait.GroupType.html). Like other nested port collections, it is added to a struct that derives `PortCollection` with the `flatten` attribute:
+///
+///     use lv2_core::prelude::*;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         #[flatten]
+///         #[port(name = "Input")]
+///         input: InputGroup<Audio, Stereo>,
+///         #[flatten]
+///         #[port(name = "Side-Chain", sidechain_of = "input")]
+///         sidechain: InputGroup<Audio, Mono>,
+///         #[flatten]
+///         output: OutputGroup<Audio, Stereo>,
+///     }
+///
+///     assert_eq!(5, Ports::PORT_COUNT);
+///     assert_eq!("input_right", Ports::port_infos()[1].symbol);
+///     assert_eq!("Side-Chain Center", Ports::port_infos()[2].name);
+///
+/// The symbol and the name of the field are the symbol and the name of the group. The symbols of the ports are the symbol of the group, followed by the symbol of the channel.
+pub struct InputGroup<T: PortType, G: GroupType> {
+    ports: [Option<T::InputPortType>; MAX_CHANNELS],
+    group: PhantomData<G>,
+}
+
+impl<T: PortType, G: GroupType> InputGroup<T, G> {
+    /// Return the number of channels.
+    pub fn len(&self) -> usize {
+        G::CHANNELS.len()
+    }
+
+    /// Return `true` if the group has no channels.
+    pub fn is_empty(&self) -> bool {
+        G::CHANNELS.is_empty()
+    }
+
+    /// Return the port of the given channel, if the group has it.
+    pub fn get(&self, channel: Channel) -> Option<&T::InputPortType> {
+        let index = G::CHANNELS.iter().position(|c| *c == channel)?;
+        self.ports[index].as_ref()
+    }
+
+    /// Iterate over the ports of all channels, in the order of their indices.
+    pub fn iter(&self) -> impl Iterator<Item = &T::InputPortType> {
+        self.ports.iter().flatten()
+    }
+
+    make_channel_accessors!(
+        InputPortType,
+        left => Left,
+        right => Right,
+        center => Center,
+        side => Side,
+        lfe => LowFrequencyEffects
+    );
+}
+
+impl<T: PortType, G: GroupType> PortCollection for InputGroup<T, G> {
+    type Cache = GroupPointerCache;
+
+    const PORT_COUNT: u32 = channel_count::<G>();
+
+    unsafe fn from_connections(cache: &GroupPointerCache, sample_count: u32) -> Option<Self> {
+        let mut ports: [Option<T::InputPortType>; MAX_CHANNELS] =
+            [None, None, None, None, None, None, None, None];
+        let pointers = &cache.pointers[..Self::PORT_COUNT as usize];
+        for (port, pointer) in ports.iter_mut().zip(pointers) {
+            *port = Some(T::input_from_raw(NonNull::new(*pointer)?, sample_count));
+        }
+        Some(Self {
+            ports,
+            group: PhantomData,
+        })
+    }
+
+    fn port_infos() -> Vec<PortInfo> {
+        group_port_infos::<G, InputPort<T>>(PortDirection::Input)
+    }
+}
+
+impl<T: PortType, G: GroupType> SlicePorts for InputGroup<T, G> {
+    unsafe fn slice_from(&mut self, block: &mut Self, range: Range<usize>) {
+        for (port, block) in self.ports.iter_mut().zip(block.ports.iter_mut()) {
+            if let (Some(port), Some(block)) = (port, block) {
+                T::slice_input(port, block, range.clone());
+            }
+        }
+    }
+}
+
+/// A group of output ports, like the left and right channels of a stereo output.
+///
+/// This is the output counterpart of [`InputGroup`](struct.InputGroup.html).
+pub struct OutputGroup<T: PortType, G: GroupType> {
+    ports: [Option<T::OutputPortType>; MAX_CHANNELS],
+    group: PhantomData<G>,
+}
+
+impl<T: PortType, G: GroupType> OutputGroup<T, G> {
+    /// Return the number of channels.
+    pub fn len(&self) -> usize {
+        G::CHANNELS.len()
+    }
+
+    /// Return `true` if the group has no channels.
+    pub fn is_empty(&self) -> bool {
+        G::CHANNELS.is_empty()
+    }
+
+    /// Return the port of the given channel, if the group has it.
+    pub fn get(&self, channel: Channel) -> Option<&T::OutputPortType> {
+        let index = G::CHANNELS.iter().position(|c| *c == channel)?;
+        self.ports[index].as_ref()
+    }
+
+    /// Return the mutable port of the given channel, if the group has it.
+    pub fn get_mut(&mut self, channel: Channel) -> Option<&mut T::OutputPortType> {
+        let index = G::CHANNELS.iter().position(|c| *c == channel)?;
+        self.ports[index].as_mut()
+    }
+
+    /// Iterate over the ports of all channels, in the order of their indices.
+    pub fn iter(&self) -> impl Iterator<Item = &T::OutputPortType> {
+        self.ports.iter().flatten()
+    }
+
+    /// Iterate mutably over the ports of all channels, in the order of their indices.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T::OutputPortType> {
+        self.ports.iter_mut().flatten()
+    }
+
+    make_channel_accessors!(
+        OutputPortType,
+        left => Left,
+        right => Right,
+        center => Center,
+        side => Side,
+        lfe => LowFrequencyEffects
+    );
+}
+
+impl<T: PortType, G: GroupType> PortCollection for OutputGroup<T, G> {
+    type Cache = GroupPointerCache;
+
+    const PORT_COUNT: u32 = channel_count::<G>();
+
+    unsafe fn from_connections(cache: &GroupPointerCache, sample_count: u32) -> Option<Self> {
+        let mut ports: [Option<T::OutputPortType>; MAX_CHANNELS] =
+            [None, None, None, None, None, None, None, None];
+        let pointers = &cache.pointers[..Self::PORT_COUNT as usize];
+        for (port, pointer) in ports.iter_mut().zip(pointers) {
+            *port = Some(T::output_from_raw(NonNull::new(*pointer)?, sample_count));
+        }
+        Some(Self {
+            ports,
+            group: PhantomData,
+        })
+    }
+
+    fn port_infos() -> Vec<PortInfo> {
+        group_port_infos::<G, OutputPort<T>>(PortDirection::Output)
+    }
+}
+
+impl<T: PortType, G: GroupType> SlicePorts for OutputGroup<T, G> {
+    unsafe fn slice_from(&mut self, block: &mut Self, range: Range<usize>) {
+        for (port, block) in self.ports.iter_mut().zip(block.ports.iter_mut()) {
+            if let (Some(port), Some(block)) = (port, block) {
+                T::slice_output(port, block, range.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::port::*;
+
+    #[test]
+    fn test_output_group() {
+        let mut left = [0.0f32; 4];
+        let mut right = [0.0f32; 4];
+        let mut cache = GroupPointerCache::default();
+        cache.connect(0, left.as_mut_ptr() as *mut _);
+        cache.connect(1, right.as_mut_ptr() as *mut _);
+
+        let mut group =
+            unsafe { OutputGroup::<Audio, Stereo>::from_connections(&cache, 4) }.unwrap();
+        assert_eq!(2, group.len());
+        assert!(group.get(Channel::Center).is_none());
+        group.get_mut(Channel::Right).unwrap()[1] = 1.0;
+        for channel in group.iter_mut() {
+            channel[0] = 2.0;
+        }
+        assert_eq!([2.0, 0.0, 0.0, 0.0], left);
+        assert_eq!([2.0, 1.0, 0.0, 0.0], right);
+
+        let cache = GroupPointerCache::default();
+        assert!(unsafe { OutputGroup::<Audio, Stereo>::from_connections(&cache, 4) }.is_none());
+    }
+}
@@ -1,9 +1,11 @@
 //! Prelude for wildcard use, containing many important types.
 pub use crate::extension::ExtensionDescriptor;
 pub use crate::feature::{FeatureCache, FeatureCollection, MissingFeatureError, ThreadingClass};
+pub use crate::lv2_dyn_manifest;
 pub use crate::match_extensions;
 pub use crate::plugin::{
-    lv2_descriptors, Plugin, PluginInfo, PluginInstance, PluginInstanceDescriptor, PortCollection,
+    lv2_descriptors, DynManifest, Plugin, PluginDescription, PluginInfo, PluginInstance,
+    PluginInstanceDescriptor, PortCollection,
 };
 pub use crate::port::*;
 pub use crate::sys::LV2_Descriptor;
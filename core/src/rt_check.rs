@@ -0,0 +1,221 @@
+//! Detection of real-time safety violations.
+//!
+//! Everything a plugin does in the audio threading class, most notably in [`run`](../plugin/trait.Plugin.html#tymethod.run), has to be real-time safe: It must not allocate or free memory on the heap, lock mutexes or do anything else that may block for an unbounded amount of time. Violations usually don't break anything while testing, but they lead to dropouts under load.
+//!
+//! If the `rt-check` feature of this crate is enabled, this module installs a global allocator that detects heap allocations and deallocations in the audio threading class. Plugins can also use the [`Mutex`](struct.Mutex.html) of this module instead of the one of the standard library to detect locks. Violations are reported to the standard error output, and optionally, the plugin panics after the function that committed them, which poisons the plugin instance. Since the checks slow down every allocation of the process, the feature is only meant for debug builds and tests:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! lv2-core = { version = "3.0.0", features = ["rt-check"] }
+//! ```
+//!
+//! The checker doesn't work if the plugin also installs its own global allocator, since there can only be one.
+//!
+//! Without the feature, only [`audio_class`](fn.audio_class.html) is available and it simply calls the given function.
+
+/// Call a function in the audio threading class.
+///
+/// The plugin instance calls `run` with this function, and so do extensions for methods of the audio threading class, like the `work_response` method of the worker extension. If the `rt-check` feature is enabled, every violation of real-time safety committed by the function is reported, and if [`set_panic_on_violation`](fn.set_panic_on_violation.html) was called with `true`, this function panics after `f` has returned. Otherwise, this function only calls `f`.
+#[cfg(not(feature = "rt-check"))]
+#[inline]
+pub fn audio_class<R, F: FnOnce() -> R>(f: F) -> R {
+    f()
+}
+
+#[cfg(feature = "rt-check")]
+pub use checked::*;
+
+#[cfg(feature = "rt-check")]
+mod checked {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::io::Write;
+    use std::ops::{Deref, DerefMut};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{LockResult, PoisonError, TryLockResult};
+
+    thread_local! {
+        /// Whether the current thread is in the audio threading class.
+        static IN_AUDIO_CLASS: Cell<bool> = const { Cell::new(false) };
+        /// The number of violations since the current thread entered the audio threading class.
+        static VIOLATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    static PANIC_ON_VIOLATION: AtomicBool = AtomicBool::new(false);
+
+    #[global_allocator]
+    static ALLOCATOR: CheckedAllocator<System> = CheckedAllocator(System);
+
+    /// A global allocator that reports allocations and deallocations in the audio threading class.
+    ///
+    /// The allocation itself is done by the wrapped allocator. An instance wrapping the system allocator is installed as the global allocator of the process.
+    pub struct CheckedAllocator<A: GlobalAlloc>(pub A);
+
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for CheckedAllocator<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            report_violation("heap allocation");
+            self.0.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            report_violation("heap allocation");
+            self.0.alloc_zeroed(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            report_violation("heap deallocation");
+            self.0.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            report_violation("heap reallocation");
+            self.0.realloc(ptr, layout, new_size)
+        }
+    }
+
+    /// Set whether violations let the plugin panic.
+    ///
+    /// By default, violations are only reported. If this is set to `true`, [`audio_class`](fn.audio_class.html) panics after the function has returned if it committed any violations. This setting applies to all threads.
+    pub fn set_panic_on_violation(panic: bool) {
+        PANIC_ON_VIOLATION.store(panic, Ordering::Relaxed);
+    }
+
+    /// Return `true` if the current thread is in the audio threading class.
+    pub fn in_audio_class() -> bool {
+        IN_AUDIO_CLASS.try_with(Cell::get).unwrap_or(false)
+    }
+
+    /// Report a violation of real-time safety, if the current thread is in the audio threading class.
+    ///
+    /// The violation is printed to the standard error output. This function doesn't allocate memory and can therefore be used in allocators.
+    pub fn report_violation(violation: &str) {
+        if !in_audio_class() {
+            return;
+        }
+        // Leave the audio threading class while reporting, in case writing allocates.
+        IN_AUDIO_CLASS.with(|flag| flag.set(false));
+        VIOLATIONS.with(|count| count.set(count.get() + 1));
+        let _ = writeln!(
+            std::io::stderr(),
+            "Real-time safety violation: {} in the audio threading class",
+            violation
+        );
+        IN_AUDIO_CLASS.with(|flag| flag.set(true));
+    }
+
+    /// Resets the audio threading class flag, even if the function panics.
+    struct AudioClassGuard {
+        outer: bool,
+    }
+
+    impl Drop for AudioClassGuard {
+        fn drop(&mut self) {
+            let outer = self.outer;
+            IN_AUDIO_CLASS.with(|flag| flag.set(outer));
+        }
+    }
+
+    /// Call a function in the audio threading class.
+    ///
+    /// The plugin instance calls `run` with this function, and so do extensions for methods of the audio threading class, like the `work_response` method of the worker extension. Every violation of real-time safety committed by the function is reported, and if [`set_panic_on_violation`](fn.set_panic_on_violation.html) was called with `true`, this function panics after `f` has returned.
+    pub fn audio_class<R, F: FnOnce() -> R>(f: F) -> R {
+        let outer = in_audio_class();
+        let violations = VIOLATIONS.with(|count| count.replace(0));
+        let result = {
+            let _guard = AudioClassGuard { outer };
+            IN_AUDIO_CLASS.with(|flag| flag.set(true));
+            f()
+        };
+        let new_violations = VIOLATIONS.with(|count| count.replace(violations));
+        if new_violations > 0 && PANIC_ON_VIOLATION.load(Ordering::Relaxed) {
+            panic!(
+                "{} real-time safety violation(s) in the audio threading class",
+                new_violations
+            );
+        }
+        result
+    }
+
+    /// A mutual exclusion primitive that reports locks in the audio threading class.
+    ///
+    /// This is a thin wrapper around [`std::sync::Mutex`](https://doc.rust-lang.org/std/sync/struct.Mutex.html), whose locks can't be detected.
+    #[derive(Default, Debug)]
+    pub struct Mutex<T: ?Sized>(std::sync::Mutex<T>);
+
+    /// A guard of a locked [`Mutex`](struct.Mutex.html).
+    pub struct MutexGuard<'a, T: ?Sized>(std::sync::MutexGuard<'a, T>);
+
+    impl<T> Mutex<T> {
+        /// Create a new mutex.
+        pub fn new(value: T) -> Self {
+            Self(std::sync::Mutex::new(value))
+        }
+
+        /// Consume the mutex and return the inner value.
+        pub fn into_inner(self) -> LockResult<T> {
+            self.0.into_inner()
+        }
+    }
+
+    impl<T: ?Sized> Mutex<T> {
+        /// Lock the mutex, blocking the current thread until it is available.
+        ///
+        /// This is a violation in the audio threading class.
+        pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+            report_violation("mutex lock");
+            self.0
+                .lock()
+                .map(MutexGuard)
+                .map_err(|err| PoisonError::new(MutexGuard(err.into_inner())))
+        }
+
+        /// Try to lock the mutex without blocking.
+        ///
+        /// Since this never blocks, it is allowed in the audio threading class.
+        pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+            self.0.try_lock().map(MutexGuard).map_err(|err| match err {
+                std::sync::TryLockError::Poisoned(err) => std::sync::TryLockError::Poisoned(
+                    PoisonError::new(MutexGuard(err.into_inner())),
+                ),
+                std::sync::TryLockError::WouldBlock => std::sync::TryLockError::WouldBlock,
+            })
+        }
+    }
+
+    impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.0
+        }
+    }
+
+    impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.0
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // Panicking on violations is tested in a separate test binary, since the setting applies to the whole process.
+        #[test]
+        fn test_violations() {
+            assert!(!in_audio_class());
+            let vector: Vec<u8> = audio_class(|| {
+                assert!(in_audio_class());
+                Vec::with_capacity(16)
+            });
+            assert!(!in_audio_class());
+            assert_eq!(16, vector.capacity());
+
+            let mutex = Mutex::new(1);
+            audio_class(|| *mutex.try_lock().unwrap() += 1);
+            audio_class(|| *mutex.lock().unwrap() += 1);
+            assert!(!in_audio_class());
+            assert_eq!(3, mutex.into_inner().unwrap());
+        }
+    }
+}
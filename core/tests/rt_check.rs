@@ -0,0 +1,23 @@
+//! Tests of panics on real-time safety violations.
+//!
+//! Panicking on violations is a setting of the whole process, which would let other tests panic on their allocations. Therefore, these tests run in a binary of their own.
+#![cfg(feature = "rt-check")]
+
+use lv2_core::rt_check::*;
+use std::panic;
+
+#[test]
+fn test_panic_on_violation() {
+    let vector: Vec<u8> = Vec::with_capacity(16);
+    let mutex = Mutex::new(1);
+
+    set_panic_on_violation(true);
+    audio_class(|| *mutex.try_lock().unwrap() += 1);
+    let result = panic::catch_unwind(|| audio_class(|| *mutex.lock().unwrap() += 1));
+    assert!(result.is_err());
+    assert!(panic::catch_unwind(|| audio_class(|| drop(vector))).is_err());
+    set_panic_on_violation(false);
+
+    assert!(!in_audio_class());
+    assert_eq!(3, mutex.into_inner().unwrap());
+}
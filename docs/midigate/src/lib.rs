@@ -30,20 +30,13 @@ pub struct Midigate {
 
 impl Midigate {
     // A function to write a chunk of output, to be called from `run()`. If the gate is high, then the input will be passed through for this chunk, otherwise silence is written.
-    fn write_output(&mut self, ports: &mut Ports, offset: usize, mut len: usize) {
-        if ports.input.len() < offset + len {
-            len = ports.input.len() - offset;
-        }
-
+    fn write_output(&self, input: &[f32], output: &mut [f32]) {
         let active = if self.program == 0 {
             self.n_active_notes > 0
         } else {
             self.n_active_notes == 0
         };
 
-        let input = &ports.input[offset..offset + len];
-        let output = &mut ports.output[offset..offset + len];
-
         if active {
             output.copy_from_slice(input);
         } else {
@@ -68,9 +61,9 @@ impl Plugin for Midigate {
         })
     }
 
-    // This plugin works through the cycle in chunks starting at offset zero. The `offset` represents the current time within this this cycle, so the output from 0 to `offset` has already been written.
+    // This plugin works through the cycle in chunks: The `split_at_events` function splits the cycle at the time stamps of the incoming events, so that every chunk starts with the events that occur at its first frame.
     //
-    // MIDI events are read in a loop. In each iteration, the number of active notes (on note on and note off) or the program (on program change) is updated, then the output is written up until the current event time. Then `offset` is updated and the next event is processed. After the loop the final chunk from the last event to the end of the cycle is emitted.
+    // In every chunk, the number of active notes (on note on and note off) or the program (on program change) is updated first, then the output is written for the frames of the chunk. The audio ports are sliced to the range of the chunk, so no index arithmetic is needed.
     //
     // There is currently no standard way to describe MIDI programs in LV2, so the host has no way of knowing that these programs exist and should be presented to the user. A future version of LV2 will address this shortcoming.
     //
@@ -78,43 +71,37 @@ impl Plugin for Midigate {
     //
     // Note that this simple example simply writes input or zero for each sample based on the gate. A serious implementation would need to envelope the transition to avoid aliasing.
     fn run(&mut self, ports: &mut Ports, _: &mut ()) {
-        let mut offset: usize = 0;
-
         let control_sequence = ports
             .control
             .read(self.urids.atom.sequence, self.urids.unit.beat)
             .unwrap();
-
-        for (timestamp, message) in control_sequence {
-            let timestamp: usize = if let Some(timestamp) = timestamp.as_frames() {
-                timestamp as usize
-            } else {
-                continue;
-            };
-
-            let message = if let Some(message) = message.read(self.urids.midi.wmidi, ()) {
-                message
-            } else {
-                continue;
-            };
-
-            match message {
-                MidiMessage::NoteOn(_, _, _) => self.n_active_notes += 1,
-                MidiMessage::NoteOff(_, _, _) => self.n_active_notes -= 1,
-                MidiMessage::ProgramChange(_, program) => {
-                    let program: u8 = program.into();
-                    if program == 0 || program == 1 {
-                        self.program = program;
+        let sample_count = ports.input.len();
+
+        split_at_events(control_sequence, sample_count, ports, |ports, chunk| {
+            for (_, message) in chunk.events() {
+                let message = if let Some(message) = message.read(self.urids.midi.wmidi, ()) {
+                    message
+                } else {
+                    continue;
+                };
+
+                match message {
+                    MidiMessage::NoteOn(_, _, _) => self.n_active_notes += 1,
+                    MidiMessage::NoteOff(_, _, _) => {
+                        self.n_active_notes = self.n_active_notes.saturating_sub(1)
+                    }
+                    MidiMessage::ProgramChange(_, program) => {
+                        let program: u8 = program.into();
+                        if program == 0 || program == 1 {
+                            self.program = program;
+                        }
                     }
+                    _ => (),
                 }
-                _ => (),
             }
 
-            self.write_output(ports, offset, timestamp + offset);
-            offset += timestamp;
-        }
-
-        self.write_output(ports, offset, ports.input.len() - offset);
+            self.write_output(&ports.input, &mut ports.output);
+        });
     }
 
     // During it's runtime, the host might decide to deactivate the plugin. When the plugin is reactivated, the host calls this method which gives the plugin an opportunity to reset it's internal state.
@@ -0,0 +1,246 @@
+//! Support for the [LV2 Buf Size specification](https://lv2plug.in/ns/ext/buf-size/buf-size.html).
+//!
+//! The specification contains marker features with which a plugin can request guarantees about the block lengths the host uses, as well as option keys that tell the plugin the actual buffer sizes. Knowing them, the plugin can allocate its buffers when it's instantiated or activated instead of guessing them.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_options::buf_size::*;
+//! use lv2_options::*;
+//! use lv2_urid::*;
+//! use urid::*;
+//!
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     map: LV2Map<'a>,
+//!     options: OptionsList<'a>,
+//!     // Require that the number of samples in every `run` call lies within the announced bounds.
+//!     _bounded: BoundedBlockLength,
+//! }
+//!
+//! #[uri("urn:lv2_options:preallocating")]
+//! struct Preallocating {
+//!     buffer: Vec<f32>,
+//! }
+//!
+//! impl Plugin for Preallocating {
+//!     type Ports = ();
+//!     type InitFeatures = Features<'static>;
+//!     type AudioFeatures = ();
+//!
+//!     fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+//!         let urids: BufSizeURIDCollection = features.map.populate_collection()?;
+//!         let sizes = BufferSizes::from_options(&features.options, &urids);
+//!         Some(Self {
+//!             buffer: Vec::with_capacity(sizes.max_block_length? as usize),
+//!         })
+//!     }
+//!
+//!     fn run(&mut self, _: &mut (), _: &mut ()) {}
+//! }
+//! ```
+use crate::feature::OptionsList;
+use atom::scalar::Int;
+use core::feature::Feature;
+use core::prelude::*;
+use std::ffi::c_void;
+use urid::*;
+
+/// Marker feature to signal that the plugin requires the block length to be within the bounds of the `minBlockLength` and `maxBlockLength` options.
+pub struct BoundedBlockLength;
+
+unsafe impl UriBound for BoundedBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__boundedBlockLength;
+}
+
+unsafe impl Feature for BoundedBlockLength {
+    unsafe fn from_feature_ptr(_feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+/// Marker feature to signal that the plugin requires the block length to be the same in every `run` call.
+pub struct FixedBlockLength;
+
+unsafe impl UriBound for FixedBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__fixedBlockLength;
+}
+
+unsafe impl Feature for FixedBlockLength {
+    unsafe fn from_feature_ptr(_feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+/// Marker feature to signal that the plugin requires the block length to be a power of two.
+pub struct PowerOf2BlockLength;
+
+unsafe impl UriBound for PowerOf2BlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__powerOf2BlockLength;
+}
+
+unsafe impl Feature for PowerOf2BlockLength {
+    unsafe fn from_feature_ptr(_feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+/// Marker feature to signal that the plugin prefers coarse, regular block lengths.
+///
+/// This means that the host should not split a processing cycle into many small blocks, for example to handle events sample-accurately.
+pub struct CoarseBlockLength;
+
+unsafe impl UriBound for CoarseBlockLength {
+    const URI: &'static [u8] = b"http://lv2plug.in/ns/ext/buf-size#coarseBlockLength\0";
+}
+
+unsafe impl Feature for CoarseBlockLength {
+    unsafe fn from_feature_ptr(_feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+/// Option key of the minimal block length, in samples.
+pub struct MinBlockLength;
+
+unsafe impl UriBound for MinBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__minBlockLength;
+}
+
+/// Option key of the maximal block length, in samples.
+pub struct MaxBlockLength;
+
+unsafe impl UriBound for MaxBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__maxBlockLength;
+}
+
+/// Option key of the nominal block length, in samples.
+///
+/// This is the block length the host usually uses, but it may differ in some `run` calls.
+pub struct NominalBlockLength;
+
+unsafe impl UriBound for NominalBlockLength {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__nominalBlockLength;
+}
+
+/// Option key of the size of sequence port buffers, in bytes.
+pub struct SequenceSize;
+
+unsafe impl UriBound for SequenceSize {
+    const URI: &'static [u8] = sys::LV2_BUF_SIZE__sequenceSize;
+}
+
+/// Collection with the URIDs of all option keys in this module.
+#[derive(Clone, URIDCollection)]
+pub struct BufSizeURIDCollection {
+    pub min_block_length: URID<MinBlockLength>,
+    pub max_block_length: URID<MaxBlockLength>,
+    pub nominal_block_length: URID<NominalBlockLength>,
+    pub sequence_size: URID<SequenceSize>,
+    /// The type of all buffer size options.
+    pub int: URID<Int>,
+}
+
+/// The buffer sizes announced by the host.
+///
+/// Every size is optional since the host does not need to provide all of them. However, if the plugin requires the [`BoundedBlockLength`](struct.BoundedBlockLength.html) feature, the host has to provide the minimal and maximal block length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferSizes {
+    pub min_block_length: Option<u32>,
+    pub max_block_length: Option<u32>,
+    pub nominal_block_length: Option<u32>,
+    pub sequence_size: Option<u32>,
+}
+
+impl BufferSizes {
+    /// Read the buffer sizes from the options passed by the host.
+    ///
+    /// Sizes that are missing, have the wrong type or are negative are set to `None`.
+    pub fn from_options(options: &OptionsList, urids: &BufSizeURIDCollection) -> Self {
+        let read = |key: URID| -> Option<u32> {
+            options.read(key, urids.int, ()).and_then(|size| {
+                if size >= 0 {
+                    Some(size as u32)
+                } else {
+                    None
+                }
+            })
+        };
+        Self {
+            min_block_length: read(urids.min_block_length.into_general()),
+            max_block_length: read(urids.max_block_length.into_general()),
+            nominal_block_length: read(urids.nominal_block_length.into_general()),
+            sequence_size: read(urids.sequence_size.into_general()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buf_size::*;
+    use crate::option::*;
+    use std::ptr::null;
+
+    #[test]
+    fn test_buffer_sizes() {
+        let map = HashURIDMapper::new();
+        let urids: BufSizeURIDCollection = map.populate_collection().unwrap();
+        let float_urid = map.map_type::<atom::scalar::Float>().unwrap();
+
+        let min_block_length: i32 = 16;
+        let max_block_length: i32 = 4096;
+        let nominal_block_length: f32 = 512.0;
+        let sequence_size: i32 = -1;
+
+        let raw_options = [
+            *OptionValue::new(
+                Subject::Instance,
+                urids.min_block_length,
+                urids.int,
+                &min_block_length,
+            )
+            .as_raw(),
+            *OptionValue::new(
+                Subject::Instance,
+                urids.max_block_length,
+                urids.int,
+                &max_block_length,
+            )
+            .as_raw(),
+            *OptionValue::new(
+                Subject::Instance,
+                urids.nominal_block_length,
+                float_urid,
+                &nominal_block_length,
+            )
+            .as_raw(),
+            *OptionValue::new(
+                Subject::Instance,
+                urids.sequence_size,
+                urids.int,
+                &sequence_size,
+            )
+            .as_raw(),
+            sys::LV2_Options_Option {
+                context: 0,
+                subject: 0,
+                key: 0,
+                size: 0,
+                type_: 0,
+                value: null(),
+            },
+        ];
+        let options = unsafe { OptionsList::from_raw(&raw_options[0]) };
+
+        assert_eq!(
+            BufferSizes {
+                min_block_length: Some(16),
+                max_block_length: Some(4096),
+                nominal_block_length: None,
+                sequence_size: None,
+            },
+            BufferSizes::from_options(&options, &urids)
+        );
+    }
+}
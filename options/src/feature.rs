@@ -0,0 +1,167 @@
+use crate::option::*;
+use atom::Atom;
+use core::feature::Feature;
+use core::prelude::*;
+use std::ffi::c_void;
+use urid::*;
+
+/// Host feature that provides a list of options to the plugin.
+///
+/// The options are usually provided when the plugin is instantiated and contain information like the nominal block length or the sample rate. The list is terminated by an option with a zero key and a null value, which is not yielded by the iterator.
+#[derive(Clone, Copy)]
+pub struct OptionsList<'a> {
+    options: &'a sys::LV2_Options_Option,
+}
+
+unsafe impl<'a> UriBound for OptionsList<'a> {
+    const URI: &'static [u8] = sys::LV2_OPTIONS__options;
+}
+
+unsafe impl<'a> Feature for OptionsList<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        (feature as *const sys::LV2_Options_Option)
+            .as_ref()
+            .map(|options| Self { options })
+    }
+}
+
+impl<'a> OptionsList<'a> {
+    /// Create an options list from a reference to the first element of a raw, terminated options array.
+    ///
+    /// # Safety
+    ///
+    /// The referenced option has to be the first element of an array of options which is terminated by an option with a zero key and a null value. All values of these options have to be valid for the lifetime `'a`.
+    pub unsafe fn from_raw(options: &'a sys::LV2_Options_Option) -> Self {
+        Self { options }
+    }
+
+    /// Iterate over all options in the list.
+    pub fn iter(&self) -> OptionsIterator<'a> {
+        OptionsIterator {
+            next: self.options as *const sys::LV2_Options_Option,
+            lifetime: std::marker::PhantomData,
+        }
+    }
+
+    /// Find the option with the given key that applies to the plugin instance.
+    pub fn get<K: ?Sized>(&self, key: URID<K>) -> Option<OptionValue<'a>> {
+        self.get_for(Subject::Instance, key)
+    }
+
+    /// Find the option with the given key that applies to the given subject.
+    pub fn get_for<K: ?Sized>(&self, subject: Subject, key: URID<K>) -> Option<OptionValue<'a>> {
+        self.iter().find(|option| {
+            option.key() == Some(key.into_general()) && option.subject() == Some(subject)
+        })
+    }
+
+    /// Find the option with the given key that applies to the plugin instance and try to read it.
+    ///
+    /// This is a shortcut for [`get`](#method.get) and [`OptionValue::read`](struct.OptionValue.html#method.read).
+    pub fn read<'b, K: ?Sized, A: Atom<'a, 'b>>(
+        &self,
+        key: URID<K>,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Option<A::ReadHandle> {
+        self.get(key)
+            .and_then(|option| option.read(urid, parameter))
+    }
+}
+
+impl<'a> IntoIterator for OptionsList<'a> {
+    type Item = OptionValue<'a>;
+    type IntoIter = OptionsIterator<'a>;
+
+    fn into_iter(self) -> OptionsIterator<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over all options of an [`OptionsList`](struct.OptionsList.html).
+pub struct OptionsIterator<'a> {
+    next: *const sys::LV2_Options_Option,
+    lifetime: std::marker::PhantomData<&'a sys::LV2_Options_Option>,
+}
+
+impl<'a> Iterator for OptionsIterator<'a> {
+    type Item = OptionValue<'a>;
+
+    fn next(&mut self) -> Option<OptionValue<'a>> {
+        let option = unsafe { self.next.as_ref() }?;
+        if option.key == 0 && option.value.is_null() {
+            return None;
+        }
+        self.next = unsafe { self.next.add(1) };
+        Some(unsafe { OptionValue::from_raw(option) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::*;
+    use atom::AtomURIDCollection;
+    use std::ptr::null;
+
+    #[test]
+    fn test_options_list() {
+        let map = HashURIDMapper::new();
+        let urids: AtomURIDCollection = map.populate_collection().unwrap();
+        let block_length_key = URID::<()>::new(1000).unwrap();
+        let sample_rate_key = URID::<()>::new(1001).unwrap();
+
+        let block_length: i32 = 512;
+        let port_rate: f32 = 44100.0;
+        let instance_rate: f32 = 48000.0;
+
+        let raw_options = [
+            *OptionValue::new(
+                Subject::Instance,
+                block_length_key,
+                urids.int,
+                &block_length,
+            )
+            .as_raw(),
+            *OptionValue::new(Subject::Port(1), sample_rate_key, urids.float, &port_rate).as_raw(),
+            *OptionValue::new(
+                Subject::Instance,
+                sample_rate_key,
+                urids.float,
+                &instance_rate,
+            )
+            .as_raw(),
+            sys::LV2_Options_Option {
+                context: 0,
+                subject: 0,
+                key: 0,
+                size: 0,
+                type_: 0,
+                value: null(),
+            },
+        ];
+
+        let options = unsafe {
+            OptionsList::from_feature_ptr(
+                raw_options.as_ptr() as *const c_void,
+                ThreadingClass::Instantiation,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(3, options.iter().count());
+
+        assert_eq!(Some(512), options.read(block_length_key, urids.int, ()));
+        assert_eq!(None, options.read(block_length_key, urids.float, ()));
+        assert_eq!(
+            Some(48000.0),
+            options.read(sample_rate_key, urids.float, ())
+        );
+        assert_eq!(
+            Some(44100.0),
+            options
+                .get_for(Subject::Port(1), sample_rate_key)
+                .and_then(|option| option.read(urids.float, ()))
+        );
+        assert!(options.get(URID::<()>::new(1002).unwrap()).is_none());
+    }
+}
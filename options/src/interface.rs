@@ -0,0 +1,292 @@
+use crate::option::*;
+use core::extension::ExtensionDescriptor;
+use core::prelude::*;
+use std::fmt;
+use std::marker::PhantomData;
+use urid::*;
+
+/// Errors that may occur when options are retrieved or set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionsError {
+    /// Unknown or general error.
+    Unknown,
+    /// The subject of the option is invalid or unsupported.
+    BadSubject,
+    /// The key of the option is invalid or unsupported.
+    BadKey,
+    /// The value of the option is invalid or unsupported.
+    BadValue,
+}
+
+impl OptionsError {
+    /// Return the raw status flag of the error.
+    pub fn into_raw(self) -> sys::LV2_Options_Status {
+        match self {
+            OptionsError::Unknown => sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN,
+            OptionsError::BadSubject => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT,
+            OptionsError::BadKey => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY,
+            OptionsError::BadValue => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE,
+        }
+    }
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            OptionsError::Unknown => "unknown error".fmt(f),
+            OptionsError::BadSubject => "invalid or unsupported subject".fmt(f),
+            OptionsError::BadKey => "invalid or unsupported key".fmt(f),
+            OptionsError::BadValue => "invalid or unsupported value".fmt(f),
+        }
+    }
+}
+
+/// A plugin extension that lets the host retrieve and change the options of a running plugin.
+///
+/// Both methods are called in the instantiation threading class, which means that no other instance methods are called concurrently. The host may request or change several options at once; These methods are called once for every option.
+///
+/// In order to be used by the host, you need to export the [`OptionsDescriptor`](struct.OptionsDescriptor.html) in the `extension_data` method. You can do that with the `match_extensions` macro from the `lv2-core` crate.
+pub trait Options: Plugin {
+    /// Retrieve the value of an option.
+    ///
+    /// The returned option has to contain the value of the option with the given subject and key. Its value has to be owned by the plugin, which is assured by the lifetime of the return value.
+    fn get(&self, subject: Subject, key: URID) -> Result<OptionValue<'_>, OptionsError>;
+
+    /// Set the value of an option.
+    fn set(&mut self, option: OptionValue) -> Result<(), OptionsError>;
+}
+
+/// Raw wrapper of the [`Options`](trait.Options.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct OptionsDescriptor<P: Options> {
+    plugin: PhantomData<P>,
+}
+
+unsafe impl<P: Options> UriBound for OptionsDescriptor<P> {
+    const URI: &'static [u8] = sys::LV2_OPTIONS__interface;
+}
+
+impl<P: Options> OptionsDescriptor<P> {
+    /// Handle a get request by the host.
+    ///
+    /// The options array is iterated until the terminating option is reached and every requested value is filled in. Options that could not be retrieved are left untouched and their errors are combined in the returned status. If the plugin panics, the instance is poisoned and `LV2_OPTIONS_ERR_UNKNOWN` is returned.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_get(
+        instance: sys::LV2_Handle,
+        options: *mut sys::LV2_Options_Option,
+    ) -> u32 {
+        let instance = if let Some(instance) = (instance as *mut PluginInstance<P>).as_mut() {
+            instance
+        } else {
+            return sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN;
+        };
+
+        instance
+            .call_guarded("get", |instance| {
+                let plugin = instance.plugin_handle();
+                let mut status = sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS;
+                let mut option_ptr = options;
+                while let Some(option) = option_ptr.as_mut() {
+                    if option.key == 0 && option.value.is_null() {
+                        break;
+                    }
+
+                    let result = match (
+                        Subject::from_raw(option.context, option.subject),
+                        URID::new(option.key),
+                    ) {
+                        (None, _) => Err(OptionsError::BadSubject),
+                        (_, None) => Err(OptionsError::BadKey),
+                        (Some(subject), Some(key)) => plugin.get(subject, key),
+                    };
+
+                    match result {
+                        Ok(value) => {
+                            let value = value.as_raw();
+                            option.size = value.size;
+                            option.type_ = value.type_;
+                            option.value = value.value;
+                        }
+                        Err(error) => status |= error.into_raw(),
+                    }
+
+                    option_ptr = option_ptr.add(1);
+                }
+                status
+            })
+            .unwrap_or(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN)
+    }
+
+    /// Handle a set request by the host.
+    ///
+    /// The options array is iterated until the terminating option is reached and every option is passed to the plugin. The errors of all options are combined in the returned status. If the plugin panics, the instance is poisoned and `LV2_OPTIONS_ERR_UNKNOWN` is returned.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_set(
+        instance: sys::LV2_Handle,
+        options: *const sys::LV2_Options_Option,
+    ) -> u32 {
+        let instance = if let Some(instance) = (instance as *mut PluginInstance<P>).as_mut() {
+            instance
+        } else {
+            return sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN;
+        };
+
+        instance
+            .call_guarded("set", |instance| {
+                let plugin = instance.plugin_handle();
+                let mut status = sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS;
+                let mut option_ptr = options;
+                while let Some(option) = option_ptr.as_ref() {
+                    if option.key == 0 && option.value.is_null() {
+                        break;
+                    }
+
+                    if let Err(error) = plugin.set(OptionValue::from_raw(option)) {
+                        status |= error.into_raw();
+                    }
+
+                    option_ptr = option_ptr.add(1);
+                }
+                status
+            })
+            .unwrap_or(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN)
+    }
+}
+
+impl<P: Options> ExtensionDescriptor for OptionsDescriptor<P> {
+    type ExtensionInterface = sys::LV2_Options_Interface;
+
+    const INTERFACE: &'static sys::LV2_Options_Interface = &sys::LV2_Options_Interface {
+        get: Some(Self::extern_get),
+        set: Some(Self::extern_set),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use lv2_core::prelude::*;
+    use std::os::raw::c_char;
+    use urid::*;
+
+    #[uri("urn:lv2_options:panicking")]
+    struct Panicking;
+
+    impl Plugin for Panicking {
+        type Ports = ();
+        type InitFeatures = ();
+        type AudioFeatures = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _: &mut (), _: &mut ()) {}
+    }
+
+    impl Options for Panicking {
+        fn get(&self, _: Subject, _: URID) -> Result<OptionValue<'_>, OptionsError> {
+            panic!("get");
+        }
+
+        fn set(&mut self, _: OptionValue) -> Result<(), OptionsError> {
+            panic!("set");
+        }
+    }
+
+    const TERMINATOR: sys::LV2_Options_Option = sys::LV2_Options_Option {
+        context: 0,
+        subject: 0,
+        key: 0,
+        size: 0,
+        type_: 0,
+        value: std::ptr::null(),
+    };
+
+    fn instantiate() -> sys::LV2_Handle {
+        let descriptor = sys::LV2_Descriptor {
+            URI: Panicking::URI.as_ptr() as *const c_char,
+            instantiate: None,
+            connect_port: None,
+            activate: None,
+            run: None,
+            deactivate: None,
+            cleanup: None,
+            extension_data: None,
+        };
+        let features: [*const sys::LV2_Feature; 1] = [std::ptr::null()];
+        let handle = unsafe {
+            PluginInstance::<Panicking>::instantiate(
+                &descriptor,
+                44100.0,
+                b"/\0".as_ptr() as *const c_char,
+                features.as_ptr(),
+            )
+        };
+        assert!(!handle.is_null());
+        handle
+    }
+
+    fn is_poisoned(handle: sys::LV2_Handle) -> bool {
+        unsafe { (handle as *const PluginInstance<Panicking>).as_ref() }
+            .unwrap()
+            .is_poisoned()
+    }
+
+    #[test]
+    fn test_panicking_get() {
+        let handle = instantiate();
+        let mut options = [
+            sys::LV2_Options_Option {
+                key: 1,
+                ..TERMINATOR
+            },
+            TERMINATOR,
+        ];
+        let status =
+            unsafe { OptionsDescriptor::<Panicking>::extern_get(handle, options.as_mut_ptr()) };
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, status);
+        assert!(is_poisoned(handle));
+        unsafe { PluginInstance::<Panicking>::cleanup(handle) };
+    }
+
+    #[test]
+    fn test_panicking_set() {
+        let handle = instantiate();
+        let value: i32 = 42;
+        let options = [
+            sys::LV2_Options_Option {
+                key: 1,
+                type_: 1,
+                size: 4,
+                value: &value as *const i32 as *const std::ffi::c_void,
+                ..TERMINATOR
+            },
+            TERMINATOR,
+        ];
+        let status =
+            unsafe { OptionsDescriptor::<Panicking>::extern_set(handle, options.as_ptr()) };
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, status);
+        assert!(is_poisoned(handle));
+
+        // The poisoned instance isn't called anymore.
+        let mut options = [
+            sys::LV2_Options_Option {
+                key: 1,
+                ..TERMINATOR
+            },
+            TERMINATOR,
+        ];
+        let status =
+            unsafe { OptionsDescriptor::<Panicking>::extern_get(handle, options.as_mut_ptr()) };
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, status);
+        unsafe { PluginInstance::<Panicking>::cleanup(handle) };
+    }
+}
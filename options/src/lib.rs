@@ -0,0 +1,103 @@
+//! Extension for LV2 plugins to receive options from the host.
+//!
+//! Options are values like the nominal block length or the sample rate that the host passes to the plugin. Every option has a subject (usually the plugin instance), a key and an atom-typed value.
+//!
+//! Hosts pass their options to the plugin with the [`OptionsList`](struct.OptionsList.html) feature when the plugin is instantiated. If the options of the host change while the plugin is running, the host uses the [`Options`](trait.Options.html) extension to set the new values. This extension also lets the host query the current options of the plugin.
+//!
+//! The [`buf_size`](buf_size/index.html) module contains the option keys and features of the buffer size specification, which tell the plugin how long the blocks passed to `run` are and how big sequence buffers are.
+//!
+//! ## Example usage
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_core::prelude::*;
+//! use lv2_options::*;
+//! use lv2_urid::*;
+//! use urid::*;
+//!
+//! /// The key of the block length option.
+//! ///
+//! /// Usually, this would be a type from a crate that implements the specification of the option.
+//! struct NominalBlockLength;
+//!
+//! unsafe impl UriBound for NominalBlockLength {
+//!     const URI: &'static [u8] = lv2_sys::LV2_BUF_SIZE__nominalBlockLength;
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! struct URIDs {
+//!     atom: AtomURIDCollection,
+//!     block_length: URID<NominalBlockLength>,
+//! }
+//!
+//! /// A plugin that needs to know the nominal block length.
+//! struct BlockLengthAware {
+//!     block_length: i32,
+//!     urids: URIDs,
+//! }
+//!
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     map: LV2Map<'a>,
+//!     options: OptionsList<'a>,
+//! }
+//!
+//! unsafe impl UriBound for BlockLengthAware {
+//!     const URI: &'static [u8] = b"urn:lv2_options:block-length-aware\0";
+//! }
+//!
+//! impl Plugin for BlockLengthAware {
+//!     type Ports = ();
+//!     type InitFeatures = Features<'static>;
+//!     type AudioFeatures = ();
+//!
+//!     fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+//!         let urids: URIDs = features.map.populate_collection()?;
+//!         // Read the initial value of the option.
+//!         let block_length = features
+//!             .options
+//!             .read(urids.block_length, urids.atom.int, ())?;
+//!         Some(Self { block_length, urids })
+//!     }
+//!
+//!     fn run(&mut self, _: &mut (), _: &mut ()) {}
+//!
+//!     fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+//!         // Export the options extension. Otherwise, the host won't use it.
+//!         match_extensions!(uri, OptionsDescriptor<Self>)
+//!     }
+//! }
+//!
+//! impl Options for BlockLengthAware {
+//!     fn get(&self, subject: Subject, key: URID) -> Result<OptionValue<'_>, OptionsError> {
+//!         if key != self.urids.block_length {
+//!             return Err(OptionsError::BadKey);
+//!         }
+//!         Ok(OptionValue::new(subject, key, self.urids.atom.int, &self.block_length))
+//!     }
+//!
+//!     fn set(&mut self, option: OptionValue) -> Result<(), OptionsError> {
+//!         if option.key() != Some(self.urids.block_length.into_general()) {
+//!             return Err(OptionsError::BadKey);
+//!         }
+//!         self.block_length = option
+//!             .read(self.urids.atom.int, ())
+//!             .ok_or(OptionsError::BadValue)?;
+//!         Ok(())
+//!     }
+//! }
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+
+pub mod buf_size;
+
+mod feature;
+pub use feature::*;
+
+mod interface;
+pub use interface::*;
+
+mod option;
+pub use option::*;
@@ -0,0 +1,187 @@
+use atom::prelude::*;
+use atom::scalar::ScalarAtom;
+use atom::Atom;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::os::raw::c_void;
+use urid::*;
+
+/// The subject of an option, i.e. the thing the option applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subject {
+    /// The option applies to the plugin instance itself.
+    Instance,
+    /// The option applies to a resource, identified by its URID.
+    Resource(URID),
+    /// The option applies to a blank node, identified by its blank node ID.
+    Blank(u32),
+    /// The option applies to the port with the given index.
+    Port(u32),
+}
+
+impl Subject {
+    /// Create a subject from the raw context and subject fields of an option.
+    ///
+    /// Returns `None` if the context is unknown or if a resource subject is not a valid URID.
+    pub fn from_raw(context: sys::LV2_Options_Context, subject: u32) -> Option<Self> {
+        match context {
+            sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE => Some(Subject::Instance),
+            sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE => {
+                URID::new(subject).map(Subject::Resource)
+            }
+            sys::LV2_Options_Context_LV2_OPTIONS_BLANK => Some(Subject::Blank(subject)),
+            sys::LV2_Options_Context_LV2_OPTIONS_PORT => Some(Subject::Port(subject)),
+            _ => None,
+        }
+    }
+
+    /// Convert the subject into the raw context and subject fields of an option.
+    pub fn into_raw(self) -> (sys::LV2_Options_Context, u32) {
+        match self {
+            Subject::Instance => (sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE, 0),
+            Subject::Resource(urid) => (sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE, urid.get()),
+            Subject::Blank(id) => (sys::LV2_Options_Context_LV2_OPTIONS_BLANK, id),
+            Subject::Port(index) => (sys::LV2_Options_Context_LV2_OPTIONS_PORT, index),
+        }
+    }
+}
+
+/// A single option, consisting of a subject, a key and an atom-typed value.
+///
+/// The value is not owned by this struct; It either belongs to the host or, if the option was created by the plugin, to the plugin. The lifetime `'a` is the lifetime of the value.
+#[derive(Clone, Copy)]
+pub struct OptionValue<'a> {
+    raw: sys::LV2_Options_Option,
+    value: PhantomData<&'a [u8]>,
+}
+
+impl<'a> OptionValue<'a> {
+    /// Create a new option with a scalar value.
+    ///
+    /// The value is borrowed, which assures that it lives as long as the option.
+    pub fn new<K: ?Sized, A: ScalarAtom>(
+        subject: Subject,
+        key: URID<K>,
+        type_urid: URID<A>,
+        value: &'a A::InternalType,
+    ) -> Self {
+        let (context, subject) = subject.into_raw();
+        Self {
+            raw: sys::LV2_Options_Option {
+                context,
+                subject,
+                key: key.get(),
+                size: size_of::<A::InternalType>() as u32,
+                type_: type_urid.get(),
+                value: value as *const A::InternalType as *const c_void,
+            },
+            value: PhantomData,
+        }
+    }
+
+    /// Create an option from a raw option.
+    ///
+    /// # Safety
+    ///
+    /// The value pointer of the raw option has to be either null or point to `size` bytes of memory that are valid for the lifetime `'a`.
+    pub unsafe fn from_raw(raw: &sys::LV2_Options_Option) -> Self {
+        Self {
+            raw: *raw,
+            value: PhantomData,
+        }
+    }
+
+    /// Return the raw option.
+    pub fn as_raw(&self) -> &sys::LV2_Options_Option {
+        &self.raw
+    }
+
+    /// Return the subject of the option.
+    ///
+    /// Returns `None` if the host provided an invalid subject.
+    pub fn subject(&self) -> Option<Subject> {
+        Subject::from_raw(self.raw.context, self.raw.subject)
+    }
+
+    /// Return the key of the option.
+    pub fn key(&self) -> Option<URID> {
+        URID::new(self.raw.key)
+    }
+
+    /// Return the type URID of the option's value.
+    ///
+    /// This can be used to identify values without actually reading them.
+    pub fn type_urid(&self) -> Option<URID> {
+        URID::new(self.raw.type_)
+    }
+
+    /// Return the raw bytes of the option's value.
+    pub fn data(&self) -> Option<&'a [u8]> {
+        if self.raw.value.is_null() {
+            None
+        } else {
+            Some(unsafe {
+                std::slice::from_raw_parts(self.raw.value as *const u8, self.raw.size as usize)
+            })
+        }
+    }
+
+    /// Try to read the value of the option.
+    ///
+    /// The value of an option is typed like the body of an atom. Therefore, it is read with the URID of the expected atom type and an atom-specific parameter. If the value has a different type, `None` is returned.
+    pub fn read<'b, A: Atom<'a, 'b>>(
+        &self,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Option<A::ReadHandle> {
+        if self.raw.type_ != urid.get() {
+            return None;
+        }
+        self.data()
+            .and_then(|data| A::read(Space::from_slice(data), parameter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::option::*;
+    use atom::AtomURIDCollection;
+
+    #[test]
+    fn test_subject() {
+        for subject in [
+            Subject::Instance,
+            Subject::Resource(URID::new(42).unwrap()),
+            Subject::Blank(7),
+            Subject::Port(3),
+        ]
+        .iter()
+        {
+            let (context, raw) = subject.into_raw();
+            assert_eq!(Some(*subject), Subject::from_raw(context, raw));
+        }
+        assert_eq!(
+            None,
+            Subject::from_raw(sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE, 0)
+        );
+        assert_eq!(None, Subject::from_raw(42, 0));
+    }
+
+    #[test]
+    fn test_option_value() {
+        let map = HashURIDMapper::new();
+        let urids: AtomURIDCollection = map.populate_collection().unwrap();
+        let key = URID::<()>::new(1000).unwrap();
+
+        let value = 42.0f32;
+        let option = OptionValue::new(Subject::Port(2), key, urids.float, &value);
+
+        assert_eq!(Some(Subject::Port(2)), option.subject());
+        assert_eq!(Some(key), option.key());
+        assert_eq!(Some(urids.float.into_general()), option.type_urid());
+        assert_eq!(4, option.data().unwrap().len());
+
+        assert_eq!(Some(42.0), option.read(urids.float, ()));
+        assert_eq!(None, option.read(urids.int, ()));
+    }
+}
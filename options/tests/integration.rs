@@ -0,0 +1,214 @@
+use lv2_atom::prelude::*;
+use lv2_core::feature::{FeatureCollection, MissingFeatureError};
+use lv2_core::prelude::*;
+use lv2_options::*;
+use lv2_urid::*;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use std::pin::Pin;
+use std::ptr::null;
+use urid::*;
+
+struct BlockLength;
+
+unsafe impl UriBound for BlockLength {
+    const URI: &'static [u8] = lv2_sys::LV2_BUF_SIZE__nominalBlockLength;
+}
+
+#[derive(URIDCollection)]
+struct URIDs {
+    atom: AtomURIDCollection,
+    block_length: URID<BlockLength>,
+}
+
+struct Optionable {
+    block_length: i32,
+    urids: URIDs,
+}
+
+#[derive(FeatureCollection)]
+pub struct Features<'a> {
+    map: LV2Map<'a>,
+    options: OptionsList<'a>,
+}
+
+unsafe impl UriBound for Optionable {
+    const URI: &'static [u8] = b"urn:lv2_options:optionable\0";
+}
+
+impl Plugin for Optionable {
+    type Ports = ();
+    type InitFeatures = Features<'static>;
+    type AudioFeatures = ();
+
+    fn new(_plugin_info: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+        let urids: URIDs = features.map.populate_collection()?;
+        let block_length = features
+            .options
+            .read(urids.block_length, urids.atom.int, ())?;
+        Some(Self {
+            block_length,
+            urids,
+        })
+    }
+
+    fn run(&mut self, _: &mut (), _: &mut ()) {}
+
+    fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+        match_extensions!(uri, OptionsDescriptor<Self>)
+    }
+}
+
+impl Options for Optionable {
+    fn get(&self, subject: Subject, key: URID) -> Result<OptionValue<'_>, OptionsError> {
+        if subject != Subject::Instance {
+            return Err(OptionsError::BadSubject);
+        }
+        if key != self.urids.block_length {
+            return Err(OptionsError::BadKey);
+        }
+        Ok(OptionValue::new(
+            subject,
+            key,
+            self.urids.atom.int,
+            &self.block_length,
+        ))
+    }
+
+    fn set(&mut self, option: OptionValue) -> Result<(), OptionsError> {
+        if option.key() != Some(self.urids.block_length.into_general()) {
+            return Err(OptionsError::BadKey);
+        }
+        self.block_length = option
+            .read(self.urids.atom.int, ())
+            .ok_or(OptionsError::BadValue)?;
+        Ok(())
+    }
+}
+
+lv2_descriptors! {
+    Optionable
+}
+
+const TERMINATOR: lv2_sys::LV2_Options_Option = lv2_sys::LV2_Options_Option {
+    context: 0,
+    subject: 0,
+    key: 0,
+    size: 0,
+    type_: 0,
+    value: null(),
+};
+
+#[test]
+fn test_get_n_set() {
+    let mut mapper: Pin<Box<HostMap<HashURIDMapper>>> = Box::pin(HashURIDMapper::new().into());
+    let map_interface = mapper.as_mut().make_map_interface();
+    let map = LV2Map::new(&map_interface);
+    let urids: URIDs = map.populate_collection().unwrap();
+
+    let descriptor = unsafe { lv2_descriptor(0).as_ref().unwrap() };
+    let (get_fn, set_fn) = unsafe {
+        let extension_data_fn = descriptor.extension_data;
+        let uri = lv2_sys::LV2_OPTIONS__interface.as_ptr() as *const i8;
+        let extension = ((extension_data_fn.unwrap())(uri)
+            as *const lv2_sys::LV2_Options_Interface)
+            .as_ref()
+            .unwrap();
+        (extension.get.unwrap(), extension.set.unwrap())
+    };
+
+    // Instantiating the plugin with the options feature.
+    let initial_length: i32 = 512;
+    let initial_options = [
+        *OptionValue::new(
+            Subject::Instance,
+            urids.block_length,
+            urids.atom.int,
+            &initial_length,
+        )
+        .as_raw(),
+        TERMINATOR,
+    ];
+    let map_feature = lv2_sys::LV2_Feature {
+        URI: lv2_sys::LV2_URID__map.as_ptr() as *const c_char,
+        data: &map_interface as *const lv2_sys::LV2_URID_Map as *mut c_void,
+    };
+    let options_feature = lv2_sys::LV2_Feature {
+        URI: lv2_sys::LV2_OPTIONS__options.as_ptr() as *const c_char,
+        data: initial_options.as_ptr() as *mut c_void,
+    };
+    let features = [&map_feature as *const _, &options_feature, null()];
+    let handle = unsafe {
+        (descriptor.instantiate.unwrap())(
+            descriptor,
+            44100.0,
+            b"./\0".as_ptr() as *const c_char,
+            features.as_ptr(),
+        )
+    };
+    assert!(!handle.is_null());
+    let block_length = || unsafe {
+        (handle as *mut PluginInstance<Optionable>)
+            .as_mut()
+            .unwrap()
+            .plugin_handle()
+            .block_length
+    };
+    assert_eq!(512, block_length());
+
+    // Setting a new value.
+    let new_length: i32 = 1024;
+    let new_options = [
+        *OptionValue::new(
+            Subject::Instance,
+            urids.block_length,
+            urids.atom.int,
+            &new_length,
+        )
+        .as_raw(),
+        TERMINATOR,
+    ];
+    let status = unsafe { (set_fn)(handle, new_options.as_ptr()) };
+    assert_eq!(lv2_sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS, status);
+    assert_eq!(1024, block_length());
+
+    // Setting a value with the wrong type.
+    let wrong_value: f32 = 42.0;
+    let wrong_options = [
+        *OptionValue::new(
+            Subject::Instance,
+            urids.block_length,
+            urids.atom.float,
+            &wrong_value,
+        )
+        .as_raw(),
+        TERMINATOR,
+    ];
+    let status = unsafe { (set_fn)(handle, wrong_options.as_ptr()) };
+    assert_eq!(
+        lv2_sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE,
+        status
+    );
+    assert_eq!(1024, block_length());
+
+    // Getting the value and an unknown option.
+    let mut requests = [
+        lv2_sys::LV2_Options_Option {
+            key: urids.block_length.get(),
+            ..TERMINATOR
+        },
+        lv2_sys::LV2_Options_Option {
+            key: urids.atom.int.get(),
+            ..TERMINATOR
+        },
+        TERMINATOR,
+    ];
+    let status = unsafe { (get_fn)(handle, requests.as_mut_ptr()) };
+    assert_eq!(lv2_sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY, status);
+    assert_eq!(urids.atom.int, requests[0].type_);
+    assert_eq!(4, requests[0].size);
+    assert_eq!(1024, unsafe { *(requests[0].value as *const i32) });
+    assert!(requests[1].value.is_null());
+
+    unsafe { (descriptor.cleanup.unwrap())(handle) };
+}
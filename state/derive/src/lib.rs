@@ -0,0 +1,17 @@
+//! Procedural macros for `lv2-state`.
+#![recursion_limit = "128"]
+
+extern crate proc_macro;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+mod parameters_derive;
+
+use proc_macro::TokenStream;
+
+/// Implement the `Parameters` trait for a struct of parameters.
+#[proc_macro_derive(Parameters, attributes(parameter))]
+pub fn parameters_derive(input: TokenStream) -> TokenStream {
+    parameters_derive::parameters_derive_impl(input)
+}
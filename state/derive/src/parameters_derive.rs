@@ -0,0 +1,166 @@
+use proc_macro::TokenStream;
+use syn::export::Span;
+use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Field, Ident, LitStr, Type};
+
+/// A field of the struct that is annotated as a parameter.
+struct ParameterField<'a> {
+    identifier: &'a Ident,
+    parameter_type: &'a Type,
+    uri: LitStr,
+}
+
+impl<'a> ParameterField<'a> {
+    /// Create a `Self` instance from a field object.
+    ///
+    /// Returns `None` if the field isn't annotated with the `parameter` attribute.
+    fn from_input_field(input: &'a Field) -> Option<Self> {
+        let attribute = input
+            .attrs
+            .iter()
+            .find(|attribute| attribute.path.is_ident("parameter"))?;
+        let uri: LitStr = attribute
+            .parse_args()
+            .expect("The parameter attribute requires a URI string literal");
+        if uri.value().contains('\0') {
+            panic!("Unexpected Null terminator");
+        }
+        Some(ParameterField {
+            identifier: input.ident.as_ref().unwrap(),
+            parameter_type: &input.ty,
+            uri,
+        })
+    }
+
+    /// Create the mapping expression for the key of the parameter.
+    fn make_key_mapping(&self) -> impl ::quote::ToTokens {
+        let uri = &self.uri;
+        quote! {
+            map.map_str(#uri)?,
+        }
+    }
+
+    /// Create the matching arm to retrieve the value of the parameter.
+    fn make_get_matcher(&self, index: usize) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        let parameter_type = self.parameter_type;
+        quote! {
+            #index => Some(<#parameter_type as ParameterType>::into_value(self.#identifier)),
+        }
+    }
+
+    /// Create the matching arm to update the value of the parameter.
+    fn make_set_matcher(&self, index: usize) -> impl ::quote::ToTokens {
+        let identifier = self.identifier;
+        let parameter_type = self.parameter_type;
+        quote! {
+            #index => match <#parameter_type as ParameterType>::from_value(value) {
+                Some(value) => {
+                    self.#identifier = value;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+/// Representation of a struct we implement `Parameters` for.
+///
+/// The implementation creates a hidden struct that contains the URIDs of the parameter keys and implements `ParameterKeys`. The parameters are identified by the index of their field, not counting fields that aren't parameters.
+struct ParametersStruct<'a> {
+    struct_name: &'a Ident,
+    fields: Vec<ParameterField<'a>>,
+}
+
+impl<'a> ParametersStruct<'a> {
+    /// Return an `Ident` for the internal key collection.
+    fn internal_keys_name(&self) -> Ident {
+        Ident::new(
+            &format!("__lv2_state_parameters_derive_{}_Keys", self.struct_name),
+            Span::call_site(),
+        )
+    }
+
+    /// Construct a `Self` instance from a `DeriveInput`.
+    fn from_derive_input(input: &'a DeriveInput) -> Self {
+        let fields = match &input.data {
+            Data::Struct(DataStruct { fields, .. }) => fields
+                .iter()
+                .filter_map(ParameterField::from_input_field)
+                .collect(),
+            _ => panic!("Only structs can implement `Parameters`"),
+        };
+        ParametersStruct {
+            struct_name: &input.ident,
+            fields,
+        }
+    }
+
+    /// Implement `Parameters` for the struct.
+    fn make_implementation(&self) -> TokenStream {
+        let struct_name = self.struct_name;
+        let internal_keys_name = self.internal_keys_name();
+        let n_parameters = self.fields.len();
+
+        let key_mappings = self.fields.iter().map(ParameterField::make_key_mapping);
+        let get_matchers = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.make_get_matcher(i));
+        let set_matchers = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.make_set_matcher(i));
+
+        (quote! {
+            impl Parameters for #struct_name {
+                type Keys = #internal_keys_name;
+
+                fn get_parameter(&self, index: usize) -> Option<ParameterValue> {
+                    match index {
+                        #(#get_matchers)*
+                        _ => None,
+                    }
+                }
+
+                fn set_parameter(&mut self, index: usize, value: ParameterValue) -> bool {
+                    match index {
+                        #(#set_matchers)*
+                        _ => false,
+                    }
+                }
+            }
+
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            pub struct #internal_keys_name {
+                keys: [URID; #n_parameters],
+            }
+
+            impl URIDCollection for #internal_keys_name {
+                fn from_map<M: Map + ?Sized>(map: &M) -> Option<Self> {
+                    Some(Self {
+                        keys: [#(#key_mappings)*],
+                    })
+                }
+            }
+
+            impl ParameterKeys for #internal_keys_name {
+                fn key(&self, index: usize) -> Option<URID> {
+                    self.keys.get(index).copied()
+                }
+            }
+        })
+        .into()
+    }
+}
+
+/// Implement `Parameters` for a struct.
+#[inline]
+pub fn parameters_derive_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    let parameters = ParametersStruct::from_derive_input(&input);
+    parameters.make_implementation()
+}
@@ -43,7 +43,7 @@ unsafe impl<P: State> UriBound for StateDescriptor<P> {
 impl<P: State> StateDescriptor<P> {
     /// Handle a save request by the host.
     ///
-    /// This involves creating the plugin reference, constructing the store handle and discovering the required host features.
+    /// This involves creating the plugin reference, constructing the store handle and discovering the required host features. If the plugin panics, the instance is poisoned and `LV2_STATE_ERR_UNKNOWN` is returned.
     ///
     /// # Safety
     ///
@@ -61,8 +61,8 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS;
         }
 
-        let plugin: &P = if let Some(plugin) = (instance as *const P).as_ref() {
-            plugin
+        let instance = if let Some(instance) = (instance as *mut PluginInstance<P>).as_mut() {
+            instance
         } else {
             return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
         };
@@ -78,12 +78,17 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE;
         };
 
-        StateErr::into(plugin.save(store, features))
+        match instance.call_guarded("save", |instance| {
+            instance.plugin_handle().save(store, features)
+        }) {
+            Some(result) => StateErr::into(result),
+            None => sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN,
+        }
     }
 
     /// Handle a restore request by the host.
     ///
-    /// This involves creating the plugin reference, constructing the retrieve handle and discovering the required host features.
+    /// This involves creating the plugin reference, constructing the retrieve handle and discovering the required host features. If the plugin panics, the instance is poisoned and `LV2_STATE_ERR_UNKNOWN` is returned.
     ///
     /// # Safety
     ///
@@ -101,8 +106,8 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS;
         }
 
-        let plugin: &mut P = if let Some(plugin) = (instance as *mut P).as_mut() {
-            plugin
+        let instance = if let Some(instance) = (instance as *mut PluginInstance<P>).as_mut() {
+            instance
         } else {
             return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
         };
@@ -118,7 +123,12 @@ impl<P: State> StateDescriptor<P> {
             return sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE;
         };
 
-        StateErr::into(plugin.restore(store, features))
+        match instance.call_guarded("restore", |instance| {
+            instance.plugin_handle().restore(store, features)
+        }) {
+            Some(result) => StateErr::into(result),
+            None => sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN,
+        }
     }
 }
 
@@ -136,6 +146,7 @@ mod tests {
     use crate::*;
     use lv2_core::prelude::*;
     use lv2_urid::*;
+    use std::os::raw::c_char;
     use urid::*;
 
     #[uri("urn:stateful")]
@@ -174,10 +185,62 @@ mod tests {
         }
     }
 
+    #[uri("urn:panicking")]
+    struct Panicking;
+
+    impl Plugin for Panicking {
+        type InitFeatures = ();
+        type AudioFeatures = ();
+        type Ports = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self)
+        }
+
+        #[cfg_attr(tarpaulin, skip)]
+        fn run(&mut self, _: &mut (), _: &mut ()) {}
+    }
+
+    impl State for Panicking {
+        type StateFeatures = ();
+
+        fn save(&self, _: StoreHandle, _: ()) -> Result<(), StateErr> {
+            panic!("save");
+        }
+
+        fn restore(&mut self, _: RetrieveHandle, _: ()) -> Result<(), StateErr> {
+            panic!("restore");
+        }
+    }
+
+    fn instantiate<P: Plugin>() -> sys::LV2_Handle {
+        let descriptor = sys::LV2_Descriptor {
+            URI: P::URI.as_ptr() as *const c_char,
+            instantiate: None,
+            connect_port: None,
+            activate: None,
+            run: None,
+            deactivate: None,
+            cleanup: None,
+            extension_data: None,
+        };
+        let features: [*const sys::LV2_Feature; 1] = [std::ptr::null()];
+        let handle = unsafe {
+            PluginInstance::<P>::instantiate(
+                &descriptor,
+                44100.0,
+                b"/\0".as_ptr() as *const c_char,
+                features.as_ptr(),
+            )
+        };
+        assert!(!handle.is_null());
+        handle
+    }
+
     #[test]
     fn test_illegal_paths() {
         type Descriptor = StateDescriptor<Stateful>;
-        let mut plugin = Stateful;
+        let handle = instantiate::<Stateful>();
 
         assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS, unsafe {
             Descriptor::extern_save(
@@ -221,7 +284,7 @@ mod tests {
 
         assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE, unsafe {
             Descriptor::extern_save(
-                &mut plugin as *mut Stateful as sys::LV2_Handle,
+                handle,
                 None,
                 std::ptr::null_mut(),
                 sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
@@ -231,12 +294,50 @@ mod tests {
 
         assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE, unsafe {
             Descriptor::extern_restore(
-                &mut plugin as *mut Stateful as sys::LV2_Handle,
+                handle,
+                None,
+                std::ptr::null_mut(),
+                sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
+                std::ptr::null_mut(),
+            )
+        });
+
+        unsafe { PluginInstance::<Stateful>::cleanup(handle) };
+    }
+
+    #[test]
+    fn test_panicking_state() {
+        type Descriptor = StateDescriptor<Panicking>;
+        let is_poisoned = |handle: sys::LV2_Handle| {
+            unsafe { (handle as *const PluginInstance<Panicking>).as_ref() }
+                .unwrap()
+                .is_poisoned()
+        };
+
+        let handle = instantiate::<Panicking>();
+        assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_save(
+                handle,
+                None,
+                std::ptr::null_mut(),
+                sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
+                std::ptr::null_mut(),
+            )
+        });
+        assert!(is_poisoned(handle));
+        unsafe { PluginInstance::<Panicking>::cleanup(handle) };
+
+        let handle = instantiate::<Panicking>();
+        assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_restore(
+                handle,
                 None,
                 std::ptr::null_mut(),
                 sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
                 std::ptr::null_mut(),
             )
         });
+        assert!(is_poisoned(handle));
+        unsafe { PluginInstance::<Panicking>::cleanup(handle) };
     }
 }
@@ -82,12 +82,17 @@ extern crate lv2_sys as sys;
 mod interface;
 pub use interface::*;
 
+mod parameters;
+pub use parameters::*;
+
 mod raw;
 pub use raw::*;
 
 mod storage;
 pub use storage::Storage;
 
+pub use lv2_state_derive::*;
+
 /// Kinds of errors that may occur in the crate.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StateErr {
@@ -0,0 +1,559 @@
+use crate::raw::*;
+use crate::StateErr;
+use atom::patch::*;
+use atom::prelude::*;
+use atom::sequence::SequenceWriter;
+use urid::*;
+
+/// The value of a parameter.
+///
+/// Parameters are stored and transmitted as scalar atoms and this enum contains a variant for every supported atom type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParameterValue {
+    Bool(bool),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+}
+
+impl ParameterValue {
+    /// Try to read a parameter value from an atom.
+    ///
+    /// `None` is returned if the atom isn't one of the supported scalar atoms.
+    pub fn read(atom: UnidentifiedAtom, urids: &AtomURIDCollection) -> Option<Self> {
+        let type_urid = atom.type_urid()?;
+        if type_urid == urids.bool {
+            atom.read(urids.bool, ())
+                .map(|value| ParameterValue::Bool(value != 0))
+        } else if type_urid == urids.int {
+            atom.read(urids.int, ()).map(ParameterValue::Int)
+        } else if type_urid == urids.long {
+            atom.read(urids.long, ()).map(ParameterValue::Long)
+        } else if type_urid == urids.float {
+            atom.read(urids.float, ()).map(ParameterValue::Float)
+        } else if type_urid == urids.double {
+            atom.read(urids.double, ()).map(ParameterValue::Double)
+        } else {
+            None
+        }
+    }
+
+    /// Try to read a parameter value from a stored property.
+    pub fn retrieve(
+        property: &StatePropertyReader,
+        urids: &AtomURIDCollection,
+    ) -> Result<Self, StateErr> {
+        let type_urid = property.type_();
+        if type_urid == urids.bool {
+            property
+                .read(urids.bool, ())
+                .map(|value| ParameterValue::Bool(value != 0))
+        } else if type_urid == urids.int {
+            property.read(urids.int, ()).map(ParameterValue::Int)
+        } else if type_urid == urids.long {
+            property.read(urids.long, ()).map(ParameterValue::Long)
+        } else if type_urid == urids.float {
+            property.read(urids.float, ()).map(ParameterValue::Float)
+        } else if type_urid == urids.double {
+            property.read(urids.double, ()).map(ParameterValue::Double)
+        } else {
+            Err(StateErr::BadType)
+        }
+    }
+
+    /// Write the value to a drafted property.
+    pub fn store(
+        self,
+        mut property: StatePropertyWriter,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), StateErr> {
+        match self {
+            ParameterValue::Bool(value) => property.init(urids.bool, value as i32).map(|_| ()),
+            ParameterValue::Int(value) => property.init(urids.int, value).map(|_| ()),
+            ParameterValue::Long(value) => property.init(urids.long, value).map(|_| ()),
+            ParameterValue::Float(value) => property.init(urids.float, value).map(|_| ()),
+            ParameterValue::Double(value) => property.init(urids.double, value).map(|_| ()),
+        }
+    }
+
+    /// Write a `patch:Set` message with the value to a sequence.
+    pub fn write_patch_set(
+        self,
+        writer: &mut SequenceWriter,
+        stamp: TimeStamp,
+        urids: &PatchURIDCollection,
+        key: URID,
+    ) -> Option<()> {
+        let atom = &urids.atom;
+        match self {
+            ParameterValue::Bool(value) => {
+                writer.write_patch_set(stamp, urids, None, key, atom.bool, value as i32)
+            }
+            ParameterValue::Int(value) => {
+                writer.write_patch_set(stamp, urids, None, key, atom.int, value)
+            }
+            ParameterValue::Long(value) => {
+                writer.write_patch_set(stamp, urids, None, key, atom.long, value)
+            }
+            ParameterValue::Float(value) => {
+                writer.write_patch_set(stamp, urids, None, key, atom.float, value)
+            }
+            ParameterValue::Double(value) => {
+                writer.write_patch_set(stamp, urids, None, key, atom.double, value)
+            }
+        }
+    }
+}
+
+/// A type that can be used as a parameter.
+///
+/// Numeric parameters accept values of any numeric atom type, which are converted with `as`, since hosts don't always send the exact type of the parameter.
+pub trait ParameterType: Copy {
+    /// Convert the parameter to a value.
+    fn into_value(self) -> ParameterValue;
+
+    /// Try to convert a value to the parameter type.
+    fn from_value(value: ParameterValue) -> Option<Self>;
+}
+
+impl ParameterType for bool {
+    fn into_value(self) -> ParameterValue {
+        ParameterValue::Bool(self)
+    }
+
+    fn from_value(value: ParameterValue) -> Option<Self> {
+        match value {
+            ParameterValue::Bool(value) => Some(value),
+            ParameterValue::Int(value) => Some(value != 0),
+            ParameterValue::Long(value) => Some(value != 0),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! make_numeric_parameter {
+    ($type:ty, $variant:ident) => {
+        impl ParameterType for $type {
+            fn into_value(self) -> ParameterValue {
+                ParameterValue::$variant(self)
+            }
+
+            #[allow(clippy::unnecessary_cast)]
+            fn from_value(value: ParameterValue) -> Option<Self> {
+                match value {
+                    ParameterValue::Bool(_) => None,
+                    ParameterValue::Int(value) => Some(value as $type),
+                    ParameterValue::Long(value) => Some(value as $type),
+                    ParameterValue::Float(value) => Some(value as $type),
+                    ParameterValue::Double(value) => Some(value as $type),
+                }
+            }
+        }
+    };
+}
+
+make_numeric_parameter!(i32, Int);
+make_numeric_parameter!(i64, Long);
+make_numeric_parameter!(f32, Float);
+make_numeric_parameter!(f64, Double);
+
+/// A collection of the URIDs of all parameter keys.
+///
+/// This trait is implemented by the `Parameters` derive macro.
+pub trait ParameterKeys: URIDCollection {
+    /// Return the key of the parameter with the given index, or `None` if there is no such parameter.
+    fn key(&self, index: usize) -> Option<URID>;
+
+    /// Return the index of the parameter with the given key.
+    fn index_of(&self, key: URID) -> Option<usize> {
+        let mut index = 0;
+        while let Some(parameter_key) = self.key(index) {
+            if parameter_key == key {
+                return Some(index);
+            }
+            index += 1;
+        }
+        None
+    }
+
+    /// Iterate over the keys of all parameters.
+    fn keys(&self) -> ParameterKeysIter<'_, Self> {
+        ParameterKeysIter {
+            keys: self,
+            index: 0,
+        }
+    }
+}
+
+/// Iterator over the keys of all parameters.
+pub struct ParameterKeysIter<'a, K: ParameterKeys> {
+    keys: &'a K,
+    index: usize,
+}
+
+impl<'a, K: ParameterKeys> Iterator for ParameterKeysIter<'a, K> {
+    type Item = URID;
+
+    fn next(&mut self) -> Option<URID> {
+        let key = self.keys.key(self.index)?;
+        self.index += 1;
+        Some(key)
+    }
+}
+
+/// The URIDs required to handle parameters.
+///
+/// It contains the patch URIDs as well as the keys of the parameters. It's usually named by the [`ParameterURIDs`](type.ParameterURIDs.html) alias.
+pub struct ParameterURIDCollection<K: ParameterKeys> {
+    pub patch: PatchURIDCollection,
+    pub keys: K,
+}
+
+impl<K: ParameterKeys> URIDCollection for ParameterURIDCollection<K> {
+    fn from_map<M: Map + ?Sized>(map: &M) -> Option<Self> {
+        Some(Self {
+            patch: map.populate_collection()?,
+            keys: map.populate_collection()?,
+        })
+    }
+}
+
+/// The URIDs required to handle the parameters of `P`.
+pub type ParameterURIDs<P> = ParameterURIDCollection<<P as Parameters>::Keys>;
+
+/// A collection of plugin parameters.
+///
+/// Parameters are properties of a plugin that are exposed to the host and the UI via `patch:Set` and `patch:Get` messages and are stored in the state of the plugin. Every parameter is identified by a URI, which has to be declared as a `lv2:Parameter` in the plugin's data file.
+///
+/// This trait should be implemented with the `Parameters` derive macro: Every field of the struct that is annotated with a `#[parameter("...")]` attribute containing the URI of the parameter is a parameter. The field types have to implement [`ParameterType`](trait.ParameterType.html) and other fields are ignored. The derive macro also requires `lv2_state::*` and `urid::*` to be in scope.
+///
+/// # Example
+///
+/// ```
+/// use lv2_atom::prelude::*;
+/// use lv2_atom::sequence::*;
+/// use lv2_core::prelude::*;
+/// use lv2_state::*;
+/// use lv2_units::prelude::*;
+/// use urid::*;
+///
+/// #[derive(Parameters)]
+/// struct AmpParameters {
+///     #[parameter("urn:my-plugin:gain")]
+///     gain: f32,
+///     #[parameter("urn:my-plugin:mute")]
+///     mute: bool,
+/// }
+///
+/// #[derive(PortCollection)]
+/// struct Ports {
+///     control: InputPort<AtomPort>,
+///     notify: OutputPort<AtomPort>,
+/// }
+///
+/// #[uri("urn:my-plugin")]
+/// struct Amp {
+///     parameters: AmpParameters,
+///     urids: ParameterURIDs<AmpParameters>,
+///     units: UnitURIDCollection,
+/// }
+///
+/// impl Plugin for Amp {
+///     type Ports = Ports;
+///     type InitFeatures = ();
+///     type AudioFeatures = ();
+///
+///     # fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+///     #     None
+///     # }
+///     #
+///     fn run(&mut self, ports: &mut Ports, _: &mut ()) {
+///         let sequence = self.urids.patch.atom.sequence;
+///         let input = ports.control.read(sequence, self.units.beat).unwrap();
+///         let mut output = ports
+///             .notify
+///             .init(sequence, TimeStampURID::Frames(self.units.frame))
+///             .unwrap();
+///
+///         for (timestamp, atom) in input {
+///             // Update the parameters and answer requests for them.
+///             self.parameters
+///                 .handle_patch(atom, &self.urids, &mut output, timestamp);
+///         }
+///     }
+/// }
+///
+/// impl State for Amp {
+///     type StateFeatures = ();
+///
+///     fn save(&self, mut store: StoreHandle, _: ()) -> Result<(), StateErr> {
+///         self.parameters.save(&mut store, &self.urids)
+///     }
+///
+///     fn restore(&mut self, store: RetrieveHandle, _: ()) -> Result<(), StateErr> {
+///         self.parameters.restore(&store, &self.urids)
+///     }
+/// }
+/// ```
+pub trait Parameters: Sized {
+    /// The collection of the parameter keys.
+    type Keys: ParameterKeys;
+
+    /// Return the value of the parameter with the given index.
+    fn get_parameter(&self, index: usize) -> Option<ParameterValue>;
+
+    /// Set the value of the parameter with the given index.
+    ///
+    /// Returns `false` if there is no such parameter or if the value can not be converted to the type of the parameter.
+    fn set_parameter(&mut self, index: usize, value: ParameterValue) -> bool;
+
+    /// Handle a patch message that is addressed to the plugin.
+    ///
+    /// `patch:Set` messages for one of the parameters update the parameter and the change is notified by writing a `patch:Set` message to the output sequence. `patch:Get` messages are answered with `patch:Set` messages for the requested parameter, or all parameters if no property was requested.
+    ///
+    /// Returns `true` if the atom was a message for the parameters. Notifications that don't fit into the output sequence are dropped.
+    fn handle_patch(
+        &mut self,
+        atom: UnidentifiedAtom,
+        urids: &ParameterURIDs<Self>,
+        output: &mut SequenceWriter,
+        stamp: TimeStamp,
+    ) -> bool {
+        match PatchMessage::read(atom, &urids.patch) {
+            Some(PatchMessage::Set {
+                subject: None,
+                property,
+                value,
+            }) => {
+                let index = if let Some(index) = urids.keys.index_of(property) {
+                    index
+                } else {
+                    return false;
+                };
+                let value = if let Some(value) = ParameterValue::read(value, &urids.patch.atom) {
+                    value
+                } else {
+                    return false;
+                };
+                if self.set_parameter(index, value) {
+                    self.notify(index, urids, output, stamp);
+                    true
+                } else {
+                    false
+                }
+            }
+            Some(PatchMessage::Get {
+                subject: None,
+                property: Some(property),
+            }) => match urids.keys.index_of(property) {
+                Some(index) => {
+                    self.notify(index, urids, output, stamp);
+                    true
+                }
+                None => false,
+            },
+            Some(PatchMessage::Get {
+                subject: None,
+                property: None,
+            }) => {
+                self.notify_all(urids, output, stamp);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Write a `patch:Set` message with the current value of a parameter to the output sequence.
+    fn notify(
+        &self,
+        index: usize,
+        urids: &ParameterURIDs<Self>,
+        output: &mut SequenceWriter,
+        stamp: TimeStamp,
+    ) -> Option<()> {
+        let key = urids.keys.key(index)?;
+        self.get_parameter(index)?
+            .write_patch_set(output, stamp, &urids.patch, key)
+    }
+
+    /// Write `patch:Set` messages with the current values of all parameters to the output sequence.
+    fn notify_all(
+        &self,
+        urids: &ParameterURIDs<Self>,
+        output: &mut SequenceWriter,
+        stamp: TimeStamp,
+    ) -> Option<()> {
+        for index in 0..urids.keys.keys().count() {
+            self.notify(index, urids, output, stamp)?;
+        }
+        Some(())
+    }
+
+    /// Store all parameters.
+    ///
+    /// This method can be used to implement [`State::save`](trait.State.html#tymethod.save). All drafted properties are committed.
+    fn save(&self, store: &mut StoreHandle, urids: &ParameterURIDs<Self>) -> Result<(), StateErr> {
+        for (index, key) in urids.keys.keys().enumerate() {
+            if let Some(value) = self.get_parameter(index) {
+                value.store(store.draft(key), &urids.patch.atom)?;
+            }
+        }
+        store.commit_all()
+    }
+
+    /// Restore all parameters.
+    ///
+    /// This method can be used to implement [`State::restore`](trait.State.html#tymethod.restore). Parameters that haven't been stored keep their current value.
+    fn restore(
+        &mut self,
+        store: &RetrieveHandle,
+        urids: &ParameterURIDs<Self>,
+    ) -> Result<(), StateErr> {
+        for (index, key) in urids.keys.keys().enumerate() {
+            let property = match store.retrieve(key) {
+                Ok(property) => property,
+                Err(StateErr::NoProperty) => continue,
+                Err(e) => return Err(e),
+            };
+            let value = ParameterValue::retrieve(&property, &urids.patch.atom)?;
+            if !self.set_parameter(index, value) {
+                return Err(StateErr::BadType);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use atom::space::*;
+    use lv2_units::prelude::*;
+    use urid::*;
+
+    #[derive(Parameters)]
+    struct TestParameters {
+        #[parameter("urn:lv2-state:test:gain")]
+        gain: f32,
+        #[parameter("urn:lv2-state:test:mute")]
+        mute: bool,
+        #[allow(dead_code)]
+        internal: u8,
+        #[parameter("urn:lv2-state:test:steps")]
+        steps: i32,
+    }
+
+    impl Default for TestParameters {
+        fn default() -> Self {
+            Self {
+                gain: 1.0,
+                mute: false,
+                internal: 0,
+                steps: 4,
+            }
+        }
+    }
+
+    #[test]
+    fn test_keys() {
+        let map = HashURIDMapper::new();
+        let urids: ParameterURIDs<TestParameters> = map.populate_collection().unwrap();
+
+        let steps = map.map_str("urn:lv2-state:test:steps").unwrap();
+        assert_eq!(3, urids.keys.keys().count());
+        assert_eq!(Some(steps), urids.keys.key(2));
+        assert_eq!(Some(2), urids.keys.index_of(steps));
+        assert_eq!(None, urids.keys.key(3));
+    }
+
+    #[test]
+    fn test_state() {
+        let map = HashURIDMapper::new();
+        let urids: ParameterURIDs<TestParameters> = map.populate_collection().unwrap();
+        let mut storage = Storage::default();
+
+        let parameters = TestParameters {
+            gain: 0.5,
+            mute: true,
+            ..Default::default()
+        };
+        parameters
+            .save(&mut storage.store_handle(), &urids)
+            .unwrap();
+
+        let mut restored = TestParameters::default();
+        restored
+            .restore(&storage.retrieve_handle(), &urids)
+            .unwrap();
+        assert_eq!(0.5, restored.gain);
+        assert!(restored.mute);
+        assert_eq!(4, restored.steps);
+    }
+
+    #[test]
+    fn test_handle_patch() {
+        let map = HashURIDMapper::new();
+        let urids: ParameterURIDs<TestParameters> = map.populate_collection().unwrap();
+        let units: UnitURIDCollection = map.populate_collection().unwrap();
+        let atom_urids = &urids.patch.atom;
+        let mut parameters = TestParameters::default();
+
+        // A set message with an integer for the gain.
+        let mut message_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(message_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    atom_urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.patch.set_class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(
+                    urids.patch.property,
+                    None,
+                    atom_urids.urid,
+                    urids.keys.key(0).unwrap(),
+                )
+                .unwrap();
+            writer
+                .init(urids.patch.value, None, atom_urids.int, 2)
+                .unwrap();
+        }
+
+        let mut output_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(output_space.as_mut());
+            let mut output = (&mut space as &mut dyn MutSpace)
+                .init(atom_urids.sequence, TimeStampURID::Frames(units.frame))
+                .unwrap();
+            let message = UnidentifiedAtom::new(Space::from_slice(message_space.as_ref()));
+            assert!(parameters.handle_patch(message, &urids, &mut output, TimeStamp::Frames(0)));
+        }
+        assert_eq!(2.0, parameters.gain);
+
+        // The change is notified.
+        let (body, _) = Space::from_slice(output_space.as_ref())
+            .split_atom_body(atom_urids.sequence)
+            .unwrap();
+        let mut sequence = Sequence::read(body, units.beat).unwrap();
+        let (_, atom) = sequence.next().unwrap();
+        match PatchMessage::read(atom, &urids.patch).unwrap() {
+            PatchMessage::Set {
+                subject: None,
+                property,
+                value,
+            } => {
+                assert_eq!(urids.keys.key(0).unwrap(), property);
+                assert_eq!(Some(2.0), value.read(atom_urids.float, ()));
+            }
+            _ => panic!("Invalid notification"),
+        }
+        assert!(sequence.next().is_none());
+    }
+}
@@ -2,9 +2,8 @@ use lv2_atom::prelude::*;
 use lv2_core::feature::{FeatureCollection, MissingFeatureError};
 use lv2_core::prelude::*;
 use lv2_state::*;
+use lv2_test_host::*;
 use lv2_urid::*;
-use std::path::Path;
-use std::pin::Pin;
 use urid::*;
 
 struct Stateful {
@@ -78,37 +77,29 @@ lv2_descriptors! {
     Stateful
 }
 
-fn create_plugin(mapper: Pin<&mut HostMap<HashURIDMapper>>) -> Stateful {
-    let plugin = {
-        // Faking the map's lifetime.
-        let interface = mapper.make_map_interface();
-        let interface = &interface as *const lv2_sys::LV2_URID_Map;
-        let interface = unsafe { interface.as_ref().unwrap() };
-        let map = LV2Map::new(interface);
-
-        // Constructing the plugin.
-        Stateful::new(
-            &PluginInfo::new(Stateful::uri(), Path::new("./"), 44100.0),
-            &mut Features { map: map },
-        )
-        .unwrap()
-    };
-
+fn create_plugin(host: &TestHost) -> TestInstance<'_> {
+    let instance = host.instantiate::<Stateful>().unwrap();
+    let plugin = plugin(&instance);
     assert_eq!(42.0, plugin.internal);
     assert_eq!(0, plugin.audio.len());
+    instance
+}
 
-    plugin
+fn plugin<'a>(instance: &TestInstance) -> &'a mut Stateful {
+    unsafe { (instance.handle() as *mut PluginInstance<Stateful>).as_mut() }
+        .unwrap()
+        .plugin_handle()
 }
 
 #[test]
 fn test_save_n_restore() {
-    let mut mapper: Pin<Box<HostMap<HashURIDMapper>>> = Box::pin(HashURIDMapper::new().into());
+    let host = TestHost::new();
     let mut storage = lv2_state::Storage::default();
 
+    let mut first_plugin = create_plugin(&host);
     let (store_fn, restore_fn) = unsafe {
-        let extension_data_fn = lv2_descriptor(0).as_ref().unwrap().extension_data;
-        let uri = lv2_sys::LV2_STATE__interface.as_ptr() as *const i8;
-        let extension = ((extension_data_fn.unwrap())(uri) as *const lv2_sys::LV2_State_Interface)
+        let extension = (first_plugin.extension_data(StateDescriptor::<Stateful>::uri())
+            as *const lv2_sys::LV2_State_Interface)
             .as_ref()
             .unwrap();
         (extension.save.unwrap(), extension.restore.unwrap())
@@ -116,16 +107,14 @@ fn test_save_n_restore() {
     assert!(store_fn == StateDescriptor::<Stateful>::extern_save);
     assert!(restore_fn == StateDescriptor::<Stateful>::extern_restore);
 
-    let mut first_plugin = create_plugin(mapper.as_mut());
-
-    first_plugin.run(&mut (), &mut ());
+    first_plugin.run(0);
 
-    assert_eq!(17.0, first_plugin.internal);
-    assert_eq!(32, first_plugin.audio.len());
+    assert_eq!(17.0, plugin(&first_plugin).internal);
+    assert_eq!(32, plugin(&first_plugin).audio.len());
 
     unsafe {
         (store_fn)(
-            &mut first_plugin as *mut Stateful as lv2_sys::LV2_Handle,
+            first_plugin.handle(),
             Some(lv2_state::Storage::extern_store),
             &mut storage as *mut lv2_state::Storage as lv2_sys::LV2_State_Handle,
             lv2_sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
@@ -133,11 +122,11 @@ fn test_save_n_restore() {
         )
     };
 
-    let mut second_plugin = create_plugin(mapper.as_mut());
+    let second_plugin = create_plugin(&host);
 
     unsafe {
         (restore_fn)(
-            &mut second_plugin as *mut Stateful as lv2_sys::LV2_Handle,
+            second_plugin.handle(),
             Some(lv2_state::Storage::extern_retrieve),
             &mut storage as *mut lv2_state::Storage as lv2_sys::LV2_State_Handle,
             lv2_sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
@@ -145,6 +134,6 @@ fn test_save_n_restore() {
         )
     };
 
-    assert_eq!(17.0, second_plugin.internal);
-    assert_eq!(32, second_plugin.audio.len());
+    assert_eq!(17.0, plugin(&second_plugin).internal);
+    assert_eq!(32, plugin(&second_plugin).audio.len());
 }
@@ -0,0 +1,4 @@
+fn main() {
+    println!("cargo:rerun-if-changed=src/log.c");
+    cc::Build::new().file("src/log.c").compile("lv2_test_host_log");
+}
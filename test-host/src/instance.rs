@@ -0,0 +1,352 @@
+use crate::TestHost;
+use atom::prelude::*;
+use atom::sequence::{SequenceIterator, SequenceWriter};
+use atom::space::*;
+use core::port::{PortDirection, PortInfo};
+use std::collections::BTreeMap;
+use std::ffi::c_void;
+use std::mem::size_of;
+use std::os::raw::c_char;
+use urid::*;
+
+/// The capacity of atom ports that are connected automatically, in bytes.
+const DEFAULT_ATOM_CAPACITY: usize = 8192;
+
+/// A buffer that is connected to a port.
+enum PortBuffer {
+    Control(Box<f32>),
+    Audio(Vec<f32>),
+    AtomInput(Vec<u64>),
+    AtomOutput(Vec<u64>),
+}
+
+impl PortBuffer {
+    /// Create a new atom buffer with the given capacity in bytes.
+    fn new_atom(capacity: usize) -> Vec<u64> {
+        // The buffer consists of `u64`s to properly align the atoms. The capacity is rounded up.
+        vec![0; (capacity + size_of::<u64>() - 1) >> 3]
+    }
+
+    /// Return a pointer to the buffer.
+    fn as_ptr(&mut self) -> *mut c_void {
+        match self {
+            PortBuffer::Control(value) => value.as_mut() as *mut f32 as *mut c_void,
+            PortBuffer::Audio(buffer) => buffer.as_mut_ptr() as *mut c_void,
+            PortBuffer::AtomInput(buffer) | PortBuffer::AtomOutput(buffer) => {
+                buffer.as_mut_ptr() as *mut c_void
+            }
+        }
+    }
+}
+
+/// View an atom buffer as bytes.
+fn atom_bytes(buffer: &[u64]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, buffer.len() * 8) }
+}
+
+/// View an atom buffer as mutable bytes.
+fn atom_bytes_mut(buffer: &mut [u64]) -> &mut [u8] {
+    unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8) }
+}
+
+/// A plugin instance, created by a [`TestHost`](struct.TestHost.html).
+///
+/// The instance owns the buffers of its ports, which can be accessed by their port index. Audio and CV buffers have the block length of the host and atom buffers are either inputs, which contain a sequence that is written by the test, or outputs, which are prepared for the plugin before every `run` call.
+///
+/// The instance is activated by the first `run` call if it hasn't been activated before. When the instance is dropped, it is deactivated and cleaned up.
+pub struct TestInstance<'a> {
+    host: &'a TestHost,
+    descriptor: &'a sys::LV2_Descriptor,
+    // The descriptor of a plugin type is created by the host and owned by the instance.
+    _owned_descriptor: Option<Box<sys::LV2_Descriptor>>,
+    handle: sys::LV2_Handle,
+    ports: BTreeMap<u32, PortBuffer>,
+    active: bool,
+    // Some plugins keep the feature structs, which therefore have to outlive the instance.
+    _features: Vec<sys::LV2_Feature>,
+}
+
+impl<'a> TestInstance<'a> {
+    /// Instantiate the plugin of the descriptor.
+    ///
+    /// # Safety
+    ///
+    /// The descriptor has to be valid. If the descriptor is owned, the reference has to point to the owned descriptor.
+    pub(crate) unsafe fn new(
+        host: &'a TestHost,
+        descriptor: &'a sys::LV2_Descriptor,
+        owned_descriptor: Option<Box<sys::LV2_Descriptor>>,
+    ) -> Option<Self> {
+        let features = host.make_features();
+        let mut feature_ptrs: Vec<*const sys::LV2_Feature> =
+            features.iter().map(|feature| feature as *const _).collect();
+        feature_ptrs.push(std::ptr::null());
+
+        let handle = (descriptor.instantiate?)(
+            descriptor,
+            host.sample_rate(),
+            host.bundle_path().as_ptr(),
+            feature_ptrs.as_ptr(),
+        );
+        if handle.is_null() {
+            return None;
+        }
+
+        Some(Self {
+            host,
+            descriptor,
+            _owned_descriptor: owned_descriptor,
+            handle,
+            ports: BTreeMap::new(),
+            active: false,
+            _features: features,
+        })
+    }
+
+    /// Connect a buffer to a port and store it.
+    fn connect(&mut self, index: u32, mut buffer: PortBuffer) {
+        if let Some(connect_port) = self.descriptor.connect_port {
+            unsafe { (connect_port)(self.handle, index, buffer.as_ptr()) };
+        }
+        self.ports.insert(index, buffer);
+    }
+
+    /// Connect all ports according to their port information.
+    ///
+    /// The index of a port is its position in the list.
+    pub fn connect_ports(&mut self, infos: &[PortInfo]) {
+        let class_of = |info: &PortInfo, class: &[u8]| {
+            info.classes
+                .iter()
+                .any(|uri| uri.to_bytes_with_nul() == class)
+        };
+        for (index, info) in infos.iter().enumerate() {
+            let index = index as u32;
+            if class_of(info, sys::LV2_CORE__ControlPort) {
+                self.connect_control(index, info.default.unwrap_or(0.0));
+            } else if class_of(info, sys::LV2_CORE__AudioPort) {
+                self.connect_audio(index);
+            } else if class_of(info, sys::LV2_CORE__CVPort) {
+                self.connect_cv(index);
+            } else if class_of(info, sys::LV2_ATOM__AtomPort) {
+                if info.direction == Some(PortDirection::Output) {
+                    self.connect_atom_output(index, DEFAULT_ATOM_CAPACITY);
+                } else {
+                    self.connect_atom_input(index, DEFAULT_ATOM_CAPACITY);
+                }
+            }
+        }
+    }
+
+    /// Connect a control port to a new value.
+    pub fn connect_control(&mut self, index: u32, value: f32) {
+        self.connect(index, PortBuffer::Control(Box::new(value)));
+    }
+
+    /// Connect an audio port to a new buffer of the host's block length.
+    pub fn connect_audio(&mut self, index: u32) {
+        let buffer = vec![0.0; self.host.block_length() as usize];
+        self.connect(index, PortBuffer::Audio(buffer));
+    }
+
+    /// Connect a CV port to a new buffer of the host's block length.
+    pub fn connect_cv(&mut self, index: u32) {
+        self.connect_audio(index);
+    }
+
+    /// Connect an atom input port to a new buffer with an empty sequence.
+    pub fn connect_atom_input(&mut self, index: u32, capacity: usize) {
+        let mut buffer = PortBuffer::new_atom(capacity);
+        self.write_empty_sequence(&mut buffer);
+        self.connect(index, PortBuffer::AtomInput(buffer));
+    }
+
+    /// Connect an atom output port to a new buffer.
+    ///
+    /// Before every `run` call, the buffer is prepared as a chunk that spans the whole buffer.
+    pub fn connect_atom_output(&mut self, index: u32, capacity: usize) {
+        let buffer = PortBuffer::new_atom(capacity);
+        self.connect(index, PortBuffer::AtomOutput(buffer));
+    }
+
+    /// Disconnect a port by connecting it to a null pointer.
+    pub fn disconnect(&mut self, index: u32) {
+        if let Some(connect_port) = self.descriptor.connect_port {
+            unsafe { (connect_port)(self.handle, index, std::ptr::null_mut()) };
+        }
+        self.ports.remove(&index);
+    }
+
+    /// Return the value of a control port.
+    pub fn control(&self, index: u32) -> Option<f32> {
+        match self.ports.get(&index) {
+            Some(PortBuffer::Control(value)) => Some(**value),
+            _ => None,
+        }
+    }
+
+    /// Set the value of a control port.
+    ///
+    /// Returns `false` if the port isn't connected to a control buffer.
+    pub fn set_control(&mut self, index: u32, value: f32) -> bool {
+        match self.ports.get_mut(&index) {
+            Some(PortBuffer::Control(control)) => {
+                **control = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Return the buffer of an audio or CV port.
+    pub fn audio(&self, index: u32) -> Option<&[f32]> {
+        match self.ports.get(&index) {
+            Some(PortBuffer::Audio(buffer)) => Some(buffer.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Return the mutable buffer of an audio or CV port.
+    pub fn audio_mut(&mut self, index: u32) -> Option<&mut [f32]> {
+        match self.ports.get_mut(&index) {
+            Some(PortBuffer::Audio(buffer)) => Some(buffer.as_mut_slice()),
+            _ => None,
+        }
+    }
+
+    /// Write a sequence of events to an atom input port.
+    ///
+    /// The time stamps of the sequence are measured in frames. The sequence is passed to the plugin in the next `run` call and cleared afterwards.
+    ///
+    /// Returns `false` if the port isn't connected to an atom input buffer or if the sequence couldn't be written.
+    pub fn write_sequence<F: FnOnce(&mut SequenceWriter)>(&mut self, index: u32, f: F) -> bool {
+        let urids = self.host.urids();
+        let buffer = match self.ports.get_mut(&index) {
+            Some(PortBuffer::AtomInput(buffer)) => buffer,
+            _ => return false,
+        };
+        let mut space = RootMutSpace::new(atom_bytes_mut(buffer));
+        let writer = (&mut space as &mut dyn MutSpace).init(
+            urids.atom.sequence,
+            TimeStampURID::Frames(urids.units.frame),
+        );
+        match writer {
+            Some(mut writer) => {
+                f(&mut writer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Read the sequence that the plugin has written to an atom output port.
+    ///
+    /// Returns `None` if the port isn't connected to an atom output buffer or if the plugin didn't write a sequence.
+    pub fn read_sequence(&self, index: u32) -> Option<SequenceIterator<'_>> {
+        let urids = self.host.urids();
+        let buffer = match self.ports.get(&index) {
+            Some(PortBuffer::AtomOutput(buffer)) => buffer,
+            _ => return None,
+        };
+        let space = Space::from_slice(atom_bytes(buffer));
+        let (atom, _) = space.split_atom()?;
+        UnidentifiedAtom::new(atom).read(urids.atom.sequence, urids.units.beat)
+    }
+
+    /// Activate the instance, if it isn't active yet.
+    pub fn activate(&mut self) {
+        if !self.active {
+            if let Some(activate) = self.descriptor.activate {
+                unsafe { (activate)(self.handle) };
+            }
+            self.active = true;
+        }
+    }
+
+    /// Deactivate the instance, if it is active.
+    pub fn deactivate(&mut self) {
+        if self.active {
+            if let Some(deactivate) = self.descriptor.deactivate {
+                unsafe { (deactivate)(self.handle) };
+            }
+            self.active = false;
+        }
+    }
+
+    /// Run the instance for the given number of frames.
+    ///
+    /// The atom output ports are prepared before the plugin is run and the atom input ports are cleared afterwards.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `frames` is greater than the block length of the host.
+    pub fn run(&mut self, frames: u32) {
+        assert!(
+            frames <= self.host.block_length(),
+            "The number of frames exceeds the block length"
+        );
+        self.activate();
+
+        let urids = self.host.urids();
+        for buffer in self.ports.values_mut() {
+            if let PortBuffer::AtomOutput(buffer) = buffer {
+                let capacity = buffer.len() * 8 - size_of::<sys::LV2_Atom>();
+                let mut space = RootMutSpace::new(atom_bytes_mut(buffer));
+                (&mut space as &mut dyn MutSpace)
+                    .init(urids.atom.chunk, ())
+                    .and_then(|mut writer| writer.allocate(capacity, false))
+                    .unwrap();
+            }
+        }
+
+        if let Some(run) = self.descriptor.run {
+            unsafe { (run)(self.handle, frames) };
+        }
+
+        let mut ports = std::mem::take(&mut self.ports);
+        for buffer in ports.values_mut() {
+            if let PortBuffer::AtomInput(buffer) = buffer {
+                self.write_empty_sequence(buffer);
+            }
+        }
+        self.ports = ports;
+    }
+
+    /// Return the extension data of the plugin for the given URI.
+    ///
+    /// The returned pointer is null if the plugin doesn't support the extension.
+    pub fn extension_data(&self, uri: &Uri) -> *const c_void {
+        match self.descriptor.extension_data {
+            Some(extension_data) => unsafe { (extension_data)(uri.as_ptr() as *const c_char) },
+            None => std::ptr::null(),
+        }
+    }
+
+    /// Return the raw handle of the instance.
+    ///
+    /// This handle is passed to the functions of extension interfaces.
+    pub fn handle(&self) -> sys::LV2_Handle {
+        self.handle
+    }
+
+    /// Write an empty sequence to an atom buffer.
+    fn write_empty_sequence(&self, buffer: &mut [u64]) {
+        let urids = self.host.urids();
+        let mut space = RootMutSpace::new(atom_bytes_mut(buffer));
+        (&mut space as &mut dyn MutSpace)
+            .init(
+                urids.atom.sequence,
+                TimeStampURID::Frames(urids.units.frame),
+            )
+            .unwrap();
+    }
+}
+
+impl<'a> Drop for TestInstance<'a> {
+    fn drop(&mut self) {
+        self.deactivate();
+        if let Some(cleanup) = self.descriptor.cleanup {
+            unsafe { (cleanup)(self.handle) };
+        }
+    }
+}
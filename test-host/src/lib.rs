@@ -0,0 +1,279 @@
+//! An in-process LV2 host to test plugins end to end.
+//!
+//! Testing a plugin usually requires a host that instantiates the plugin, provides host features, connects the ports and runs it. This crate provides such a host, which runs the plugin in the test's process and gives the test direct access to the port buffers.
+//!
+//! The [`TestHost`](struct.TestHost.html) owns the host features, most notably the URID map, URID unmap and log features, and creates [`TestInstance`s](struct.TestInstance.html). Plugins can either be instantiated from their type or from a compiled library, using the [`PluginLibrary`](struct.PluginLibrary.html). If a plugin is instantiated from its type, the ports are connected automatically, using the port information of the plugin's [`PortCollection`](../lv2_core/port/trait.PortCollection.html).
+//!
+//! # Example
+//!
+//!     use lv2_core::prelude::*;
+//!     use lv2_test_host::*;
+//!     use urid::*;
+//!
+//!     #[derive(PortCollection)]
+//!     struct Ports {
+//!         #[port(default = 2.0)]
+//!         gain: InputPort<Control>,
+//!         input: InputPort<Audio>,
+//!         output: OutputPort<Audio>,
+//!     }
+//!
+//!     #[uri("urn:lv2-test-host:amp")]
+//!     struct Amp;
+//!
+//!     impl Plugin for Amp {
+//!         type Ports = Ports;
+//!         type InitFeatures = ();
+//!         type AudioFeatures = ();
+//!
+//!         fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+//!             Some(Self)
+//!         }
+//!
+//!         fn run(&mut self, ports: &mut Ports, _: &mut ()) {
+//!             for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+//!                 *output = *input * *ports.gain;
+//!             }
+//!         }
+//!     }
+//!
+//!     let host = TestHost::new();
+//!     let mut amp = host.instantiate::<Amp>().unwrap();
+//!
+//!     amp.audio_mut(1).unwrap()[..4].copy_from_slice(&[1.0, 2.0, 3.0, 4.0]);
+//!     amp.run(4);
+//!     assert_eq!(&[2.0, 4.0, 6.0, 8.0], &amp.audio(2).unwrap()[..4]);
+//!
+//!     amp.set_control(0, 0.5);
+//!     amp.run(4);
+//!     assert_eq!(&[0.5, 1.0, 1.5, 2.0], &amp.audio(2).unwrap()[..4]);
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+extern crate lv2_units as units;
+
+mod instance;
+mod log;
+
+pub use instance::*;
+
+use atom::AtomURIDCollection;
+use core::prelude::*;
+use log::HostLog;
+use lv2_urid::*;
+use std::ffi::{c_void, CStr, CString};
+use std::path::Path;
+use std::pin::Pin;
+use units::UnitURIDCollection;
+use urid::*;
+
+/// URIDs used by the host to prepare and read atom ports.
+#[derive(URIDCollection)]
+pub(crate) struct HostURIDs {
+    pub atom: AtomURIDCollection,
+    pub units: UnitURIDCollection,
+}
+
+/// An in-process host for plugins.
+///
+/// The host owns the host features and creates plugin instances that borrow them. By default, it provides the URID map, URID unmap and log features, a sample rate of 44100 Hz and a maximal block length of 1024 frames. All messages sent to the log are collected and can be retrieved with [`log_messages`](#method.log_messages).
+pub struct TestHost {
+    // The map interfaces contain pointers to the mapper, which therefore has to be pinned.
+    _mapper: Pin<Box<HostMap<HashURIDMapper>>>,
+    map_interface: Box<sys::LV2_URID_Map>,
+    unmap_interface: Box<sys::LV2_URID_Unmap>,
+    // The log interface contains a pointer to the boxed log.
+    log: Box<HostLog>,
+    log_interface: Box<sys::LV2_Log_Log>,
+    features: Vec<(UriBuf, *mut c_void)>,
+    sample_rate: f64,
+    block_length: u32,
+    bundle_path: CString,
+    urids: HostURIDs,
+}
+
+impl TestHost {
+    /// Create a new host.
+    pub fn new() -> Self {
+        let mut mapper: Pin<Box<HostMap<HashURIDMapper>>> = Box::pin(HashURIDMapper::new().into());
+        let map_interface = Box::new(mapper.as_mut().make_map_interface());
+        let unmap_interface = Box::new(mapper.as_mut().make_unmap_interface());
+        let urids = LV2Map::new(map_interface.as_ref())
+            .populate_collection()
+            .unwrap();
+        let mut log = HostLog::new();
+        let log_interface = Box::new(log.make_interface());
+        Self {
+            _mapper: mapper,
+            map_interface,
+            unmap_interface,
+            log,
+            log_interface,
+            features: Vec::new(),
+            sample_rate: 44100.0,
+            block_length: 1024,
+            bundle_path: CString::new("./").unwrap(),
+            urids,
+        }
+    }
+
+    /// Set the sample rate that is passed to new instances.
+    pub fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    /// Return the sample rate that is passed to new instances.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Set the maximal number of frames per `run` call of new instances.
+    ///
+    /// The audio and CV buffers of new instances have this length.
+    pub fn set_block_length(&mut self, block_length: u32) {
+        self.block_length = block_length;
+    }
+
+    /// Return the maximal number of frames per `run` call of new instances.
+    pub fn block_length(&self) -> u32 {
+        self.block_length
+    }
+
+    /// Set the bundle path that is passed to new instances.
+    pub fn set_bundle_path(&mut self, path: &Path) {
+        self.bundle_path = CString::new(path.to_str().unwrap()).unwrap();
+    }
+
+    /// Add a host feature that is passed to new instances.
+    ///
+    /// # Safety
+    ///
+    /// The data pointer has to be valid for the feature and has to outlive all instances of the host.
+    pub unsafe fn add_feature(&mut self, uri: &Uri, data: *mut c_void) {
+        self.features.push((uri.to_owned(), data));
+    }
+
+    /// Return the URID map of the host.
+    pub fn map(&self) -> LV2Map<'_> {
+        LV2Map::new(self.map_interface.as_ref())
+    }
+
+    /// Return the URID unmap of the host.
+    pub fn unmap(&self) -> LV2Unmap<'_> {
+        LV2Unmap::new(self.unmap_interface.as_ref())
+    }
+
+    /// Return all messages that were sent to the log, together with the URIDs of their entry types.
+    pub fn log_messages(&self) -> Vec<(URID, String)> {
+        self.log.messages()
+    }
+
+    /// Instantiate a plugin type.
+    ///
+    /// All ports are connected to new buffers, according to the port information of the plugin's port collection. Control ports are initialized with their default value and atom ports have a capacity of 8192 bytes.
+    ///
+    /// Returns `None` if the plugin could not be instantiated, for example because a required feature is missing.
+    pub fn instantiate<P: Plugin>(&self) -> Option<TestInstance<'_>> {
+        let descriptor = Box::new(sys::LV2_Descriptor {
+            URI: P::URI.as_ptr() as *const std::os::raw::c_char,
+            instantiate: Some(PluginInstance::<P>::instantiate),
+            connect_port: Some(PluginInstance::<P>::connect_port),
+            activate: Some(PluginInstance::<P>::activate),
+            run: Some(PluginInstance::<P>::run),
+            deactivate: Some(PluginInstance::<P>::deactivate),
+            cleanup: Some(PluginInstance::<P>::cleanup),
+            extension_data: Some(PluginInstance::<P>::extension_data),
+        });
+        // The reference stays valid since the box is moved into the instance.
+        let descriptor_ref = unsafe { &*(descriptor.as_ref() as *const sys::LV2_Descriptor) };
+        let mut instance = unsafe { TestInstance::new(self, descriptor_ref, Some(descriptor))? };
+        instance.connect_ports(&<P::Ports as PortCollection>::port_infos());
+        Some(instance)
+    }
+
+    /// Instantiate a plugin from a raw descriptor.
+    ///
+    /// The ports aren't connected, this has to be done with the `connect_*` methods of the instance.
+    ///
+    /// # Safety
+    ///
+    /// The descriptor has to be valid.
+    pub unsafe fn instantiate_descriptor<'a>(
+        &'a self,
+        descriptor: &'a sys::LV2_Descriptor,
+    ) -> Option<TestInstance<'a>> {
+        TestInstance::new(self, descriptor, None)
+    }
+
+    /// Create the list of feature structs, including the URID map, URID unmap and log.
+    pub(crate) fn make_features(&self) -> Vec<sys::LV2_Feature> {
+        let mut features = vec![
+            sys::LV2_Feature {
+                URI: sys::LV2_URID__map.as_ptr() as *const std::os::raw::c_char,
+                data: self.map_interface.as_ref() as *const _ as *mut c_void,
+            },
+            sys::LV2_Feature {
+                URI: sys::LV2_URID__unmap.as_ptr() as *const std::os::raw::c_char,
+                data: self.unmap_interface.as_ref() as *const _ as *mut c_void,
+            },
+            sys::LV2_Feature {
+                URI: sys::LV2_LOG__log.as_ptr() as *const std::os::raw::c_char,
+                data: self.log_interface.as_ref() as *const _ as *mut c_void,
+            },
+        ];
+        features.extend(self.features.iter().map(|(uri, data)| sys::LV2_Feature {
+            URI: uri.as_ptr(),
+            data: *data,
+        }));
+        features
+    }
+
+    pub(crate) fn bundle_path(&self) -> &CStr {
+        self.bundle_path.as_c_str()
+    }
+
+    pub(crate) fn urids(&self) -> &HostURIDs {
+        &self.urids
+    }
+}
+
+impl Default for TestHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A compiled plugin library.
+///
+/// This loads a shared library and retrieves the plugin descriptors via the `lv2_descriptor` function. The descriptors can then be instantiated with [`TestHost::instantiate_descriptor`](struct.TestHost.html#method.instantiate_descriptor).
+pub struct PluginLibrary {
+    library: libloading::Library,
+}
+
+impl PluginLibrary {
+    /// Load the library at the given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, libloading::Error> {
+        libloading::Library::new(path.as_ref()).map(|library| Self { library })
+    }
+
+    /// Return all plugin descriptors of the library.
+    pub fn descriptors(&self) -> Vec<&sys::LV2_Descriptor> {
+        type DescriptorFn = unsafe extern "C" fn(u32) -> *const sys::LV2_Descriptor;
+        let descriptor_fn = match unsafe { self.library.get::<DescriptorFn>(b"lv2_descriptor\0") } {
+            Ok(descriptor_fn) => descriptor_fn,
+            Err(_) => return Vec::new(),
+        };
+        (0..)
+            .map(|index| unsafe { descriptor_fn(index).as_ref() })
+            .take_while(Option::is_some)
+            .map(Option::unwrap)
+            .collect()
+    }
+
+    /// Return the descriptor of the plugin with the given URI.
+    pub fn descriptor(&self, uri: &Uri) -> Option<&sys::LV2_Descriptor> {
+        self.descriptors()
+            .into_iter()
+            .find(|descriptor| unsafe { Uri::from_ptr(descriptor.URI) } == uri)
+    }
+}
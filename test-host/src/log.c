@@ -0,0 +1,30 @@
+// Variadic entry points of the host's log feature.
+//
+// Stable Rust can't define variadic functions, so the messages are formatted here and passed to a
+// callback, which is stored at the beginning of the log handle.
+
+#include <stdarg.h>
+#include <stdint.h>
+#include <stdio.h>
+
+typedef int (*lv2_test_host_log_callback)(void* handle, uint32_t type, const char* message, int len);
+
+int lv2_test_host_log_vprintf(void* handle, uint32_t type, const char* fmt, va_list ap) {
+    char message[4096];
+    int len = vsnprintf(message, sizeof(message), fmt, ap);
+    if (len < 0) {
+        return len;
+    }
+    if (len >= (int)sizeof(message)) {
+        len = sizeof(message) - 1;
+    }
+    return (*(lv2_test_host_log_callback*)handle)(handle, type, message, len);
+}
+
+int lv2_test_host_log_printf(void* handle, uint32_t type, const char* fmt, ...) {
+    va_list ap;
+    va_start(ap, fmt);
+    int result = lv2_test_host_log_vprintf(handle, type, fmt, ap);
+    va_end(ap);
+    return result;
+}
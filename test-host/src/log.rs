@@ -0,0 +1,77 @@
+use std::ffi::c_void;
+use std::os::raw::{c_char, c_int};
+use std::sync::Mutex;
+use urid::*;
+
+/// The type of `va_list` parameters, which decay to pointers on some platforms.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+type VaList = *mut sys::__va_list_tag;
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+type VaList = sys::va_list;
+
+extern "C" {
+    fn lv2_test_host_log_printf(
+        handle: sys::LV2_Log_Handle,
+        type_: sys::LV2_URID,
+        fmt: *const c_char,
+        ...
+    ) -> c_int;
+
+    fn lv2_test_host_log_vprintf(
+        handle: sys::LV2_Log_Handle,
+        type_: sys::LV2_URID,
+        fmt: *const c_char,
+        ap: VaList,
+    ) -> c_int;
+}
+
+type MessageCallback =
+    unsafe extern "C" fn(*mut c_void, sys::LV2_URID, *const c_char, c_int) -> c_int;
+
+/// The handle of the host's log feature, which collects all messages.
+///
+/// The formatting functions are written in C and expect the message callback at the beginning of the handle.
+#[repr(C)]
+pub(crate) struct HostLog {
+    callback: MessageCallback,
+    messages: Mutex<Vec<(URID, String)>>,
+}
+
+impl HostLog {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            callback: Self::push_message,
+            messages: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Create the feature struct for a boxed log.
+    pub fn make_interface(self: &mut Box<Self>) -> sys::LV2_Log_Log {
+        sys::LV2_Log_Log {
+            handle: self.as_mut() as *mut Self as *mut c_void,
+            printf: Some(lv2_test_host_log_printf),
+            vprintf: Some(lv2_test_host_log_vprintf),
+        }
+    }
+
+    pub fn messages(&self) -> Vec<(URID, String)> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    unsafe extern "C" fn push_message(
+        handle: *mut c_void,
+        type_: sys::LV2_URID,
+        message: *const c_char,
+        len: c_int,
+    ) -> c_int {
+        let log = &*(handle as *const Self);
+        let message = std::slice::from_raw_parts(message as *const u8, len as usize);
+        match (URID::new(type_), log.messages.lock()) {
+            (Some(type_), Ok(mut messages)) => {
+                messages.push((type_, String::from_utf8_lossy(message).into_owned()));
+                len
+            }
+            _ => -1,
+        }
+    }
+}
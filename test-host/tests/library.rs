@@ -0,0 +1,54 @@
+use lv2_test_host::*;
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use urid::Uri;
+
+/// Build the amp example of the book and return the path of its library.
+///
+/// The example is built into a separate target directory, since the target directory of the workspace is locked while the tests run.
+fn build_amp() -> PathBuf {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("plugins");
+    let status = Command::new(env!("CARGO"))
+        .current_dir(&workspace)
+        .args(["build", "-p", "amp", "--target-dir"])
+        .arg(&target_dir)
+        .status()
+        .expect("Couldn't run cargo");
+    assert!(status.success(), "Couldn't build the amp example");
+    target_dir
+        .join("debug")
+        .join(format!("{}amp{}", DLL_PREFIX, DLL_SUFFIX))
+}
+
+#[test]
+fn test_plugin_library() {
+    let library = PluginLibrary::load(build_amp()).unwrap();
+    let uri =
+        Uri::from_bytes_with_nul(b"https://github.com/RustAudio/rust-lv2/tree/master/docs/amp\0")
+            .unwrap();
+    assert_eq!(1, library.descriptors().len());
+    assert!(library
+        .descriptor(Uri::from_bytes_with_nul(b"urn:lv2-test-host:unknown\0").unwrap())
+        .is_none());
+    let descriptor = library.descriptor(uri).unwrap();
+
+    let host = TestHost::new();
+    let mut amp = unsafe { host.instantiate_descriptor(descriptor).unwrap() };
+    amp.connect_control(0, 20.0);
+    amp.connect_audio(1);
+    amp.connect_audio(2);
+    amp.activate();
+
+    amp.audio_mut(1).unwrap()[..2].copy_from_slice(&[0.5, -0.25]);
+    amp.run(2);
+    let output = amp.audio(2).unwrap();
+    assert!((output[0] - 5.0).abs() < 1e-4);
+    assert!((output[1] + 2.5).abs() < 1e-4);
+
+    amp.set_control(0, 0.0);
+    amp.run(2);
+    assert_eq!(&[0.5, -0.25], &amp.audio(2).unwrap()[..2]);
+    amp.deactivate();
+}
@@ -0,0 +1,625 @@
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+
+use atom::prelude::*;
+use core::feature::{Log, LogURIDCollection};
+use core::prelude::*;
+use lv2_test_host::*;
+use lv2_urid::*;
+use urid::*;
+
+#[derive(PortCollection)]
+struct AmpPorts {
+    #[port(default = 2.0)]
+    gain: InputPort<Control>,
+    input: InputPort<Audio>,
+    output: OutputPort<Audio>,
+    level: OutputPort<Control>,
+}
+
+#[uri("urn:lv2-test-host:amp")]
+struct Amp {
+    activated: bool,
+}
+
+impl Plugin for Amp {
+    type Ports = AmpPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(plugin_info: &PluginInfo, _: &mut ()) -> Option<Self> {
+        assert_eq!(48000.0, plugin_info.sample_rate());
+        Some(Self { activated: false })
+    }
+
+    fn activate(&mut self, _: &mut ()) {
+        self.activated = true;
+    }
+
+    fn run(&mut self, ports: &mut AmpPorts, _: &mut ()) {
+        assert!(self.activated);
+        let mut level: f32 = 0.0;
+        for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+            *output = *input * *ports.gain;
+            level = level.max(output.abs());
+        }
+        **ports.level = level;
+    }
+
+    fn deactivate(&mut self, _: &mut ()) {
+        self.activated = false;
+    }
+}
+
+#[derive(PortCollection)]
+struct AtomPorts {
+    input: InputPort<AtomPort>,
+    output: OutputPort<AtomPort>,
+}
+
+#[derive(FeatureCollection)]
+struct Features<'a> {
+    map: LV2Map<'a>,
+}
+
+#[derive(URIDCollection)]
+struct URIDs {
+    atom: AtomURIDCollection,
+    units: lv2_units::UnitURIDCollection,
+}
+
+#[uri("urn:lv2-test-host:doubler")]
+struct Doubler {
+    urids: URIDs,
+}
+
+impl Plugin for Doubler {
+    type Ports = AtomPorts;
+    type InitFeatures = Features<'static>;
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+        Some(Self {
+            urids: features.map.populate_collection()?,
+        })
+    }
+
+    fn run(&mut self, ports: &mut AtomPorts, _: &mut ()) {
+        let input = ports
+            .input
+            .read(self.urids.atom.sequence, self.urids.units.beat)
+            .unwrap();
+        let mut output = ports
+            .output
+            .init(
+                self.urids.atom.sequence,
+                TimeStampURID::Frames(self.urids.units.frame),
+            )
+            .unwrap();
+        for (stamp, atom) in input {
+            if let Some(value) = atom.read(self.urids.atom.int, ()) {
+                output.init(stamp, self.urids.atom.int, value * 2).unwrap();
+            }
+        }
+    }
+}
+
+lv2_descriptors!(Amp = "Test Amplifier", Doubler);
+
+#[test]
+fn test_amp() {
+    let mut host = TestHost::new();
+    host.set_sample_rate(48000.0);
+    host.set_block_length(16);
+    let mut amp = host.instantiate::<Amp>().unwrap();
+
+    assert_eq!(Some(2.0), amp.control(0));
+    assert_eq!(16, amp.audio(1).unwrap().len());
+
+    amp.audio_mut(1).unwrap()[..4].copy_from_slice(&[1.0, -2.0, 3.0, 0.5]);
+    amp.run(4);
+    assert_eq!(&[2.0, -4.0, 6.0, 1.0], &amp.audio(2).unwrap()[..4]);
+    assert_eq!(Some(6.0), amp.control(3));
+
+    assert!(amp.set_control(0, 0.5));
+    assert!(!amp.set_control(1, 0.5));
+    amp.run(4);
+    assert_eq!(&[0.5, -1.0, 1.5, 0.25], &amp.audio(2).unwrap()[..4]);
+}
+
+#[test]
+#[should_panic]
+fn test_block_length() {
+    let mut host = TestHost::new();
+    host.set_sample_rate(48000.0);
+    host.set_block_length(16);
+    host.instantiate::<Amp>().unwrap().run(17);
+}
+
+#[test]
+fn test_atom_sequences() {
+    let host = TestHost::new();
+    let urids: URIDs = host.map().populate_collection().unwrap();
+    let mut doubler = host.instantiate::<Doubler>().unwrap();
+
+    assert!(doubler.write_sequence(0, |writer| {
+        writer
+            .init(TimeStamp::Frames(0), urids.atom.int, 21)
+            .unwrap();
+        writer
+            .init(TimeStamp::Frames(5), urids.atom.float, 1.0)
+            .unwrap();
+        writer
+            .init(TimeStamp::Frames(7), urids.atom.int, -4)
+            .unwrap();
+    }));
+    assert!(!doubler.write_sequence(1, |_| ()));
+    doubler.run(8);
+
+    let events: Vec<(i64, i32)> = doubler
+        .read_sequence(1)
+        .unwrap()
+        .map(|(stamp, atom)| {
+            (
+                stamp.as_frames().unwrap(),
+                atom.read(urids.atom.int, ()).unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(vec![(0, 42), (7, -8)], events);
+
+    // The input sequence is cleared after every run.
+    doubler.run(8);
+    assert_eq!(0, doubler.read_sequence(1).unwrap().count());
+}
+
+#[test]
+fn test_descriptor() {
+    let mut host = TestHost::new();
+    host.set_sample_rate(48000.0);
+    let descriptor = unsafe { lv2_descriptor(0).as_ref().unwrap() };
+    let mut amp = unsafe { host.instantiate_descriptor(descriptor).unwrap() };
+
+    assert_eq!(None, amp.control(0));
+    amp.connect_control(0, 3.0);
+    amp.connect_audio(1);
+    amp.connect_audio(2);
+    amp.connect_control(3, 0.0);
+
+    amp.audio_mut(1).unwrap()[0] = 1.0;
+    amp.run(1);
+    assert_eq!(3.0, amp.audio(2).unwrap()[0]);
+    assert!(amp
+        .extension_data(Uri::from_bytes_with_nul(b"urn:lv2-test-host:unknown\0").unwrap())
+        .is_null());
+}
+
+#[test]
+fn test_description_names() {
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::c_char;
+
+    unsafe extern "C" fn store(data: *mut c_void, turtle: *const c_char) {
+        *(data as *mut std::string::String) = CStr::from_ptr(turtle).to_str().unwrap().to_owned();
+    }
+
+    let mut turtle = std::string::String::new();
+    unsafe {
+        assert!(lv2_rs_plugin_description(
+            0,
+            store,
+            &mut turtle as *mut _ as *mut c_void
+        ))
+    };
+    assert!(turtle.contains("<http://usefulinc.com/ns/doap#name> \"Test Amplifier\""));
+
+    unsafe {
+        assert!(lv2_rs_plugin_description(
+            1,
+            store,
+            &mut turtle as *mut _ as *mut c_void
+        ))
+    };
+    assert!(turtle.contains("<http://usefulinc.com/ns/doap#name> \"Doubler\""));
+
+    unsafe {
+        assert!(!lv2_rs_plugin_description(
+            2,
+            store,
+            &mut turtle as *mut _ as *mut c_void
+        ))
+    };
+}
+
+#[derive(PortCollection)]
+struct DuckerPorts {
+    #[flatten]
+    input: InputGroup<Audio, Stereo>,
+    #[flatten]
+    #[port(sidechain_of = "input")]
+    sidechain: InputGroup<Audio, Mono>,
+    #[flatten]
+    output: OutputGroup<Audio, Stereo>,
+}
+
+#[uri("urn:lv2-test-host:ducker")]
+struct Ducker;
+
+impl Plugin for Ducker {
+    type Ports = DuckerPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut DuckerPorts, _: &mut ()) {
+        let sidechain = ports.sidechain.center().unwrap();
+        for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+            for ((input, sidechain), output) in
+                input.iter().zip(sidechain.iter()).zip(output.iter_mut())
+            {
+                *output = *input * (1.0 - sidechain);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_groups() {
+    let host = TestHost::new();
+    let mut ducker = host.instantiate::<Ducker>().unwrap();
+
+    ducker.audio_mut(0).unwrap()[..2].copy_from_slice(&[1.0, 1.0]);
+    ducker.audio_mut(1).unwrap()[..2].copy_from_slice(&[2.0, 2.0]);
+    ducker.audio_mut(2).unwrap()[..2].copy_from_slice(&[0.0, 0.5]);
+    ducker.run(2);
+    assert_eq!(&[1.0, 0.5], &ducker.audio(3).unwrap()[..2]);
+    assert_eq!(&[2.0, 1.0], &ducker.audio(4).unwrap()[..2]);
+}
+
+#[derive(PortCollection)]
+struct MixerPorts {
+    #[port(default = 1.0)]
+    levels: [Option<InputPort<Control>>; 3],
+    inputs: [InputPort<Audio>; 3],
+    outputs: [OutputPort<Audio>; 3],
+}
+
+#[uri("urn:lv2-test-host:mixer")]
+struct Mixer;
+
+impl Plugin for Mixer {
+    type Ports = MixerPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut MixerPorts, _: &mut ()) {
+        let levels = ports.levels.iter();
+        for (level, (input, output)) in levels.zip(zip_ports(&ports.inputs, &mut ports.outputs)) {
+            let level = level.as_ref().map_or(0.0, |level| **level);
+            for (input, output) in input.iter().zip(output.iter_mut()) {
+                *output = *input * level;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_port_arrays() {
+    let host = TestHost::new();
+    let mut mixer = host.instantiate::<Mixer>().unwrap();
+
+    mixer.set_control(1, 0.5);
+    mixer.disconnect(2);
+    for index in 3..6 {
+        mixer.audio_mut(index).unwrap()[..2].copy_from_slice(&[1.0, 2.0]);
+    }
+    mixer.run(2);
+    assert_eq!(&[1.0, 2.0], &mixer.audio(6).unwrap()[..2]);
+    assert_eq!(&[0.5, 1.0], &mixer.audio(7).unwrap()[..2]);
+    assert_eq!(&[0.0, 0.0], &mixer.audio(8).unwrap()[..2]);
+}
+
+#[derive(PortCollection)]
+struct GainPorts {
+    #[port(default = 2.0)]
+    gain: InputPort<Control>,
+    output: OutputPort<Control>,
+}
+
+#[derive(PortCollection)]
+struct NestedPorts {
+    #[flatten]
+    first: GainPorts,
+    input: InputPort<Control>,
+    #[flatten]
+    #[port(symbol = "second")]
+    second: GainPorts,
+}
+
+#[uri("urn:lv2-test-host:nested")]
+struct Nested;
+
+impl Plugin for Nested {
+    type Ports = NestedPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut NestedPorts, _: &mut ()) {
+        for gain_ports in [&mut ports.first, &mut ports.second].iter_mut() {
+            **gain_ports.output = *ports.input * *gain_ports.gain;
+        }
+    }
+}
+
+#[test]
+fn test_flattened_collections() {
+    let infos = NestedPorts::port_infos();
+    let symbols: Vec<&str> = infos.iter().map(|info| info.symbol.as_str()).collect();
+    assert_eq!(
+        vec!["gain", "output", "input", "second_gain", "second_output"],
+        symbols
+    );
+
+    let host = TestHost::new();
+    let mut nested = host.instantiate::<Nested>().unwrap();
+    nested.set_control(2, 3.0);
+    nested.set_control(3, 0.5);
+    nested.run(1);
+    assert_eq!(Some(6.0), nested.control(1));
+    assert_eq!(Some(1.5), nested.control(4));
+}
+
+#[derive(PortCollection)]
+struct CrasherPorts {
+    input: InputPort<Audio>,
+    output: OutputPort<Audio>,
+    level: OutputPort<Control>,
+}
+
+#[uri("urn:lv2-test-host:crasher")]
+struct Crasher;
+
+impl Plugin for Crasher {
+    type Ports = CrasherPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(plugin_info: &PluginInfo, _: &mut ()) -> Option<Self> {
+        assert!(plugin_info.sample_rate() > 0.0, "Invalid sample rate");
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut CrasherPorts, _: &mut ()) {
+        for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+            *output = *input;
+            assert!(*input >= 0.0, "Negative input");
+        }
+        **ports.level = 1.0;
+    }
+}
+
+#[test]
+fn test_panics() {
+    let mut host = TestHost::new();
+    let mut crasher = host.instantiate::<Crasher>().unwrap();
+
+    crasher.audio_mut(0).unwrap()[..2].copy_from_slice(&[1.0, 2.0]);
+    crasher.run(2);
+    assert_eq!(&[1.0, 2.0], &crasher.audio(1).unwrap()[..2]);
+    assert_eq!(Some(1.0), crasher.control(2));
+
+    // The panic is caught and the outputs are silenced.
+    crasher.audio_mut(0).unwrap()[..2].copy_from_slice(&[1.0, -2.0]);
+    crasher.run(2);
+    assert_eq!(&[0.0, 0.0], &crasher.audio(1).unwrap()[..2]);
+    assert_eq!(Some(0.0), crasher.control(2));
+
+    // The instance is poisoned and stays silent.
+    crasher.audio_mut(0).unwrap()[..2].copy_from_slice(&[1.0, 2.0]);
+    crasher.run(2);
+    assert_eq!(&[0.0, 0.0], &crasher.audio(1).unwrap()[..2]);
+    assert_eq!(Some(0.0), crasher.control(2));
+    drop(crasher);
+
+    host.set_sample_rate(0.0);
+    assert!(host.instantiate::<Crasher>().is_none());
+}
+
+/// A port collection that is implemented manually.
+struct ManualPorts {
+    input: InputPort<Audio>,
+    output: OutputPort<Audio>,
+}
+
+#[derive(Default)]
+struct ManualCache {
+    pointers: [Option<std::ptr::NonNull<std::ffi::c_void>>; 2],
+}
+
+impl PortPointerCache for ManualCache {
+    fn connect(&mut self, index: u32, pointer: *mut std::ffi::c_void) {
+        if let Some(slot) = self.pointers.get_mut(index as usize) {
+            *slot = std::ptr::NonNull::new(pointer);
+        }
+    }
+}
+
+impl PortCollection for ManualPorts {
+    type Cache = ManualCache;
+
+    const PORT_COUNT: u32 = 2;
+
+    unsafe fn from_connections(cache: &ManualCache, sample_count: u32) -> Option<Self> {
+        Some(Self {
+            input: InputPort::from_raw(cache.pointers[0]?.as_ptr(), sample_count)?,
+            output: OutputPort::from_raw(cache.pointers[1]?.as_ptr(), sample_count)?,
+        })
+    }
+
+    // Required to silence the output if the plugin panics.
+    fn port_infos() -> Vec<PortInfo> {
+        vec![
+            <InputPort<Audio>>::port_info("input"),
+            <OutputPort<Audio>>::port_info("output"),
+        ]
+    }
+}
+
+#[uri("urn:lv2-test-host:manual-crasher")]
+struct ManualCrasher;
+
+impl Plugin for ManualCrasher {
+    type Ports = ManualPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut ManualPorts, _: &mut ()) {
+        ports.output.copy_from_slice(&ports.input);
+        assert!(
+            ports.input.iter().all(|sample| *sample >= 0.0),
+            "Negative input"
+        );
+    }
+}
+
+#[test]
+fn test_panics_with_manual_ports() {
+    let host = TestHost::new();
+    let mut crasher = host.instantiate::<ManualCrasher>().unwrap();
+
+    crasher.audio_mut(0).unwrap()[..2].copy_from_slice(&[1.0, 2.0]);
+    crasher.run(2);
+    assert_eq!(&[1.0, 2.0], &crasher.audio(1).unwrap()[..2]);
+
+    // The output is found with `port_infos` and silenced.
+    crasher.audio_mut(0).unwrap()[..2].copy_from_slice(&[1.0, -2.0]);
+    crasher.run(2);
+    assert_eq!(&[0.0, 0.0], &crasher.audio(1).unwrap()[..2]);
+}
+
+#[derive(PortCollection)]
+struct LookaheadPorts {
+    input: InputPort<Audio>,
+    output: OutputPort<Audio>,
+    latency: LatencyOutput,
+}
+
+#[uri("urn:lv2-test-host:lookahead")]
+struct Lookahead;
+
+impl Plugin for Lookahead {
+    type Ports = LookaheadPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut LookaheadPorts, _: &mut ()) {
+        // The latency is only set when there is something to process.
+        if ports.input.is_empty() {
+            return;
+        }
+        ports.latency.set(64.0);
+        ports.output.copy_from_slice(&ports.input);
+    }
+}
+
+#[test]
+fn test_latency() {
+    let infos = LookaheadPorts::port_infos();
+    assert!(infos[2].properties.contains(&PortProperty::ReportsLatency));
+
+    let host = TestHost::new();
+    let mut lookahead = host.instantiate::<Lookahead>().unwrap();
+    lookahead.run(0);
+    assert_eq!(Some(0.0), lookahead.control(2));
+    lookahead.run(4);
+    assert_eq!(Some(64.0), lookahead.control(2));
+
+    // The framework writes the latency to a new buffer, even if the plugin doesn't.
+    lookahead.connect_control(2, 0.0);
+    lookahead.run(0);
+    assert_eq!(Some(64.0), lookahead.control(2));
+}
+
+#[derive(PortCollection)]
+struct LoggerPorts {
+    input: InputPort<Control>,
+}
+
+#[derive(FeatureCollection)]
+struct LoggerFeatures<'a> {
+    log: Log<'a>,
+    map: LV2Map<'a>,
+}
+
+#[derive(FeatureCollection)]
+struct LoggerAudioFeatures<'a> {
+    log: Log<'a>,
+}
+
+#[uri("urn:lv2-test-host:logger")]
+struct Logger {
+    urids: LogURIDCollection,
+}
+
+impl Plugin for Logger {
+    type Ports = LoggerPorts;
+    type InitFeatures = LoggerFeatures<'static>;
+    type AudioFeatures = LoggerAudioFeatures<'static>;
+
+    fn new(_: &PluginInfo, features: &mut LoggerFeatures<'static>) -> Option<Self> {
+        let urids: LogURIDCollection = features.map.populate_collection()?;
+        features.log.print(urids.note, "Hello World!").ok()?;
+        Some(Self { urids })
+    }
+
+    fn run(&mut self, ports: &mut LoggerPorts, features: &mut LoggerAudioFeatures<'static>) {
+        core::log!(
+            features.log,
+            self.urids.trace,
+            "input {} with {}% of {}",
+            *ports.input,
+            100,
+            "ä".repeat(2)
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_log() {
+    let host = TestHost::new();
+    let urids: LogURIDCollection = host.map().populate_collection().unwrap();
+    let mut logger = host.instantiate::<Logger>().unwrap();
+    logger.set_control(0, 0.5);
+    logger.run(1);
+
+    assert_eq!(
+        vec![
+            (urids.note.into_general(), "Hello World!".to_owned()),
+            (
+                urids.trace.into_general(),
+                "input 0.5 with 100% of ää".to_owned()
+            ),
+        ],
+        host.log_messages()
+    );
+}
@@ -0,0 +1,391 @@
+extern crate clap;
+extern crate libloading;
+
+use std::env;
+use std::ffi::{c_void, CStr};
+use std::fs;
+use std::io;
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Spellings of the `lv2:binary` predicate.
+const BINARY_PREDICATES: [&str; 2] = ["lv2:binary", "<http://lv2plug.in/ns/lv2core#binary>"];
+
+/// The plugins of a library.
+struct PluginLibrary {
+    library: libloading::Library,
+}
+
+impl PluginLibrary {
+    /// Load the library at the given path.
+    fn load(path: &Path) -> Result<Self, String> {
+        libloading::Library::new(path)
+            .map(|library| Self { library })
+            .map_err(|err| format!("Couldn't load {}: {}", path.display(), err))
+    }
+
+    /// Return the URIs of all plugins in the library.
+    fn plugin_uris(&self) -> Result<Vec<String>, String> {
+        // Only the first field of the descriptor is read, which is the URI of the plugin.
+        type DescriptorFn = unsafe extern "C" fn(u32) -> *const *const c_char;
+        let descriptor_fn = unsafe { self.library.get::<DescriptorFn>(b"lv2_descriptor\0") }
+            .map_err(|_| String::from("The library doesn't export `lv2_descriptor`"))?;
+
+        let mut uris = Vec::new();
+        loop {
+            let descriptor = unsafe { descriptor_fn(uris.len() as u32) };
+            if descriptor.is_null() {
+                break;
+            }
+            let uri = unsafe { CStr::from_ptr(*descriptor) };
+            uris.push(uri.to_string_lossy().into_owned());
+        }
+        Ok(uris)
+    }
+
+    /// Return the Turtle descriptions of all plugins in the library.
+    ///
+    /// This requires the library to be created with `lv2_descriptors!`.
+    fn descriptions(&self) -> Result<Vec<String>, String> {
+        type Callback = unsafe extern "C" fn(*mut c_void, *const c_char);
+        type DescriptionFn = unsafe extern "C" fn(u32, Callback, *mut c_void) -> bool;
+
+        unsafe extern "C" fn push_description(data: *mut c_void, turtle: *const c_char) {
+            let descriptions = &mut *(data as *mut Vec<String>);
+            descriptions.push(CStr::from_ptr(turtle).to_string_lossy().into_owned());
+        }
+
+        let description_fn = unsafe {
+            self.library
+                .get::<DescriptionFn>(b"lv2_rs_plugin_description\0")
+        }
+        .map_err(|_| {
+            String::from("The library doesn't export plugin descriptions, please provide a bundle")
+        })?;
+
+        let mut descriptions: Vec<String> = Vec::new();
+        loop {
+            let data = &mut descriptions as *mut Vec<String> as *mut c_void;
+            if !unsafe { description_fn(descriptions.len() as u32, push_description, data) } {
+                break;
+            }
+        }
+        // The description function also fails if a description couldn't be created.
+        let plugin_count = self.plugin_uris()?.len();
+        if descriptions.len() < plugin_count {
+            return Err(format!(
+                "Couldn't describe plugin {} of the library",
+                descriptions.len()
+            ));
+        }
+        Ok(descriptions)
+    }
+}
+
+/// Return the file name of the library that is built for the package.
+fn library_name(package: &str, target: Option<&str>) -> String {
+    let (prefix, suffix) = match target {
+        Some(target) if target.contains("windows") => ("", ".dll"),
+        Some(target) if target.contains("apple") => ("lib", ".dylib"),
+        Some(_) => ("lib", ".so"),
+        None => (env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX),
+    };
+    format!("{}{}{}", prefix, package.replace('-', "_"), suffix)
+}
+
+/// Replace the objects of all `lv2:binary` statements with the given library name.
+fn fix_binary(turtle: &str, library: &str) -> String {
+    let mut fixed = String::with_capacity(turtle.len());
+    let mut rest = turtle;
+    while let Some((start, predicate)) = BINARY_PREDICATES
+        .iter()
+        .filter_map(|predicate| rest.find(predicate).map(|start| (start, predicate)))
+        .min()
+    {
+        let object_start = start + predicate.len();
+        fixed.push_str(&rest[..object_start]);
+        rest = &rest[object_start..];
+
+        let whitespace = rest.len() - rest.trim_start().len();
+        let object = &rest[whitespace..];
+        if let (true, Some(end)) = (object.starts_with('<'), object.find('>')) {
+            fixed.push_str(&rest[..whitespace]);
+            fixed.push('<');
+            fixed.push_str(library);
+            fixed.push('>');
+            rest = &object[end + 1..];
+        }
+    }
+    fixed.push_str(rest);
+    fixed
+}
+
+/// Create the manifest of a bundle.
+fn make_manifest(uris: &[String], library: &str, description: &str) -> String {
+    uris.iter()
+        .map(|uri| {
+            format!(
+                "<{}>\n    a <http://lv2plug.in/ns/lv2core#Plugin> ;\n    <http://lv2plug.in/ns/lv2core#binary> <{}> ;\n    <http://www.w3.org/2000/01/rdf-schema#seeAlso> <{}> .\n",
+                uri, library, description
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Check that the manifest lists all plugins of the library.
+fn validate_manifest(manifest: &str, uris: &[String]) -> Result<(), String> {
+    let missing: Vec<&str> = uris
+        .iter()
+        .filter(|uri| !manifest.contains(&format!("<{}>", uri)))
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "The manifest doesn't list the plugins {}",
+            missing.join(", ")
+        ))
+    }
+}
+
+/// Copy the contents of a directory recursively.
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let destination = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &destination)?;
+        } else {
+            fs::copy(&path, &destination)?;
+        }
+    }
+    Ok(())
+}
+
+/// Return the default installation directory for LV2 bundles.
+fn default_install_dir() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("LV2_PATH") {
+        if let Some(dir) = env::split_paths(&path).next() {
+            return Some(dir);
+        }
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".lv2"))
+}
+
+/// Build the plugin crate.
+fn build(package: &str, target: Option<&str>, release: bool) -> Result<(), String> {
+    let mut command = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".into()));
+    command.args(["build", "-p", package]);
+    if release {
+        command.arg("--release");
+    }
+    if let Some(target) = target {
+        command.args(["--target", target]);
+    }
+    match command.status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(format!("Building {} failed", package)),
+        Err(err) => Err(format!("Couldn't run cargo: {}", err)),
+    }
+}
+
+/// Build and assemble the bundle and return its path.
+fn make_bundle(matches: &clap::ArgMatches) -> Result<PathBuf, String> {
+    let package = matches.value_of("package").unwrap();
+    let target = matches.value_of("target");
+    let release = !matches.is_present("debug");
+    let template = matches.value_of("bundle").map(PathBuf::from);
+
+    build(package, target, release)?;
+
+    let mut library_path =
+        PathBuf::from(env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".into()));
+    if let Some(target) = target {
+        library_path.push(target);
+    }
+    library_path.push(if release { "release" } else { "debug" });
+    let library = library_name(package, target);
+    library_path.push(&library);
+
+    let bundle_name = match &template {
+        Some(template) => template
+            .file_name()
+            .ok_or("The bundle path is invalid")?
+            .to_owned(),
+        None => format!("{}.lv2", package).into(),
+    };
+    let bundle = PathBuf::from(matches.value_of("out").unwrap()).join(bundle_name);
+    if bundle.exists() {
+        fs::remove_dir_all(&bundle).map_err(|err| err.to_string())?;
+    }
+
+    let plugins = PluginLibrary::load(&library_path);
+    match template {
+        Some(template) => {
+            copy_dir(&template, &bundle).map_err(|err| err.to_string())?;
+            for entry in fs::read_dir(&bundle).map_err(|err| err.to_string())? {
+                let path = entry.map_err(|err| err.to_string())?.path();
+                if path.extension().is_some_and(|extension| extension == "ttl") {
+                    let turtle = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+                    fs::write(&path, fix_binary(&turtle, &library))
+                        .map_err(|err| err.to_string())?;
+                }
+            }
+            let manifest = fs::read_to_string(bundle.join("manifest.ttl"))
+                .map_err(|_| String::from("The bundle doesn't contain a manifest"))?;
+            match plugins {
+                Ok(plugins) => validate_manifest(&manifest, &plugins.plugin_uris()?)?,
+                // Libraries for other targets can not be loaded.
+                Err(err) => eprintln!("Skipping validation: {}", err),
+            }
+        }
+        None => {
+            let plugins = plugins?;
+            let description = format!("{}.ttl", package);
+            fs::create_dir_all(&bundle).map_err(|err| err.to_string())?;
+            fs::write(
+                bundle.join("manifest.ttl"),
+                make_manifest(&plugins.plugin_uris()?, &library, &description),
+            )
+            .map_err(|err| err.to_string())?;
+            fs::write(
+                bundle.join(&description),
+                plugins.descriptions()?.join("\n"),
+            )
+            .map_err(|err| err.to_string())?;
+        }
+    }
+    fs::copy(&library_path, bundle.join(&library)).map_err(|err| err.to_string())?;
+    Ok(bundle)
+}
+
+/// Install the bundle and return the path of the installed bundle.
+///
+/// If no directory is given, the default installation directory is used.
+fn install(bundle: &Path, install_dir: Option<&str>) -> Result<PathBuf, String> {
+    let install_dir = match install_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => default_install_dir().ok_or("No installation directory found")?,
+    };
+    let destination = install_dir.join(bundle.file_name().unwrap());
+    if destination.exists() {
+        fs::remove_dir_all(&destination)
+            .map_err(|err| format!("Couldn't remove the installed bundle: {}", err))?;
+    }
+    copy_dir(bundle, &destination)
+        .map_err(|err| format!("Couldn't install the bundle: {}", err))?;
+    Ok(destination)
+}
+
+fn main() {
+    let matches = clap::App::new("bundletool")
+        .author("© 2020 Jan-Oliver 'Janonard' Opdenhövel")
+        .about("Build and install LV2 plugin bundles")
+        .version("0.1.0")
+        .arg(
+            clap::Arg::with_name("package")
+                .help("The plugin crate to build")
+                .required(true)
+                .short("p")
+                .long("package")
+                .value_name("PACKAGE")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("bundle")
+                .help("The directory with the hand-written Turtle files")
+                .short("b")
+                .long("bundle")
+                .value_name("DIR")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("out")
+                .help("The directory to create the bundle in")
+                .short("o")
+                .long("out")
+                .value_name("DIR")
+                .default_value("target/lv2")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("install")
+                .help("Install the bundle into the given directory, LV2_PATH or ~/.lv2 by default")
+                .short("i")
+                .long("install")
+                .value_name("DIR")
+                .takes_value(true)
+                .min_values(0),
+        )
+        .arg(
+            clap::Arg::with_name("target")
+                .help("The target to build the plugin for")
+                .long("target")
+                .value_name("TRIPLE")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("debug")
+                .help("Build the plugin without optimizations")
+                .long("debug"),
+        )
+        .get_matches();
+
+    let result = make_bundle(&matches).and_then(|bundle| {
+        println!("Created {}", bundle.display());
+        if matches.is_present("install") {
+            let destination = install(&bundle, matches.value_of("install"))?;
+            println!("Installed {}", destination.display());
+        }
+        Ok(())
+    });
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_binary() {
+        let turtle = "<urn:a> lv2:binary <amp.so> .\n<urn:b>\n    <http://lv2plug.in/ns/lv2core#binary>  <old.dll> ;\n    rdfs:seeAlso <b.ttl> .\n";
+        assert_eq!(
+            "<urn:a> lv2:binary <libamp.so> .\n<urn:b>\n    <http://lv2plug.in/ns/lv2core#binary>  <libamp.so> ;\n    rdfs:seeAlso <b.ttl> .\n",
+            fix_binary(turtle, "libamp.so")
+        );
+        assert_eq!("lv2:binary", fix_binary("lv2:binary", "libamp.so"));
+    }
+
+    #[test]
+    fn test_library_name() {
+        assert_eq!(
+            "libeg_amp.so",
+            library_name("eg-amp", Some("x86_64-unknown-linux-gnu"))
+        );
+        assert_eq!(
+            "eg_amp.dll",
+            library_name("eg-amp", Some("x86_64-pc-windows-gnu"))
+        );
+        assert_eq!(
+            "libeg_amp.dylib",
+            library_name("eg-amp", Some("x86_64-apple-darwin"))
+        );
+    }
+
+    #[test]
+    fn test_manifest() {
+        let uris = vec![String::from("urn:a"), String::from("urn:b")];
+        let manifest = make_manifest(&uris, "liba.so", "a.ttl");
+        assert!(manifest.contains("<urn:b>\n    a <http://lv2plug.in/ns/lv2core#Plugin> ;"));
+        assert!(manifest.contains("<http://lv2plug.in/ns/lv2core#binary> <liba.so> ;"));
+        assert!(validate_manifest(&manifest, &uris).is_ok());
+        assert!(validate_manifest(&manifest, &[String::from("urn:c")]).is_err());
+    }
+}
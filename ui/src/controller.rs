@@ -0,0 +1,215 @@
+//! Communication between the UI and the plugin.
+use atom::space::Space;
+use atom::UnidentifiedAtom;
+use std::ffi::c_void;
+use std::mem::size_of;
+use urid::*;
+
+/// Port protocol for atoms that are sent as events.
+///
+/// Atoms sent with this protocol are delivered to the plugin as events in the sequence of the port.
+pub struct EventTransfer;
+
+unsafe impl UriBound for EventTransfer {
+    const URI: &'static [u8] = sys::LV2_ATOM__eventTransfer;
+}
+
+/// Port protocol for atoms that are sent as a whole.
+///
+/// Atoms sent with this protocol replace the content of the port.
+pub struct AtomTransfer;
+
+unsafe impl UriBound for AtomTransfer {
+    const URI: &'static [u8] = sys::LV2_ATOM__atomTransfer;
+}
+
+/// Marker trait for port protocols that transfer atoms.
+pub trait AtomProtocol: UriBound {}
+
+impl AtomProtocol for EventTransfer {}
+
+impl AtomProtocol for AtomTransfer {}
+
+/// Collection with the URIDs of the port protocols.
+#[derive(Clone, URIDCollection)]
+pub struct ProtocolURIDCollection {
+    pub event_transfer: URID<EventTransfer>,
+    pub atom_transfer: URID<AtomTransfer>,
+}
+
+/// Errors potentially generated by the writing methods of the [`UIController`](struct.UIController.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteError {
+    /// No write function was provided by the host.
+    ///
+    /// This can only happen with a faulty host.
+    NoCallback,
+    /// The data to send is larger than what the host can handle.
+    TooLarge,
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            WriteError::NoCallback => "no callback".fmt(f),
+            WriteError::TooLarge => "data too large".fmt(f),
+        }
+    }
+}
+
+/// Handle to send data from the UI to the input ports of the plugin.
+///
+/// The controller is created by the framework when the UI is instantiated and passed to [`PluginUI::new`](trait.PluginUI.html#tymethod.new). It stays valid as long as the UI lives.
+pub struct UIController {
+    write_function: sys::LV2UI_Write_Function,
+    controller: sys::LV2UI_Controller,
+}
+
+impl UIController {
+    /// Create a new controller from the raw write function and controller pointer.
+    ///
+    /// # Safety
+    ///
+    /// The controller pointer has to be valid for the write function as long as the created controller lives.
+    pub unsafe fn new(
+        write_function: sys::LV2UI_Write_Function,
+        controller: sys::LV2UI_Controller,
+    ) -> Self {
+        Self {
+            write_function,
+            controller,
+        }
+    }
+
+    /// Send raw data to an input port of the plugin, using the given port protocol.
+    pub fn write_raw<P: ?Sized>(
+        &self,
+        port_index: u32,
+        protocol: URID<P>,
+        data: &[u8],
+    ) -> Result<(), WriteError> {
+        self.write_internal(port_index, protocol.get(), data)
+    }
+
+    /// Set the value of an input control port of the plugin.
+    pub fn write_control(&self, port_index: u32, value: f32) -> Result<(), WriteError> {
+        let data = unsafe {
+            std::slice::from_raw_parts(&value as *const f32 as *const u8, size_of::<f32>())
+        };
+        self.write_internal(port_index, 0, data)
+    }
+
+    /// Send an atom to an atom input port of the plugin.
+    ///
+    /// The protocol is either [`EventTransfer`](struct.EventTransfer.html) or [`AtomTransfer`](struct.AtomTransfer.html).
+    pub fn write_atom<P: AtomProtocol>(
+        &self,
+        port_index: u32,
+        protocol: URID<P>,
+        atom: UnidentifiedAtom,
+    ) -> Result<(), WriteError> {
+        let data = atom.space().split_atom().and_then(|(atom, _)| atom.data());
+        self.write_internal(port_index, protocol.get(), data.unwrap_or(&[]))
+    }
+
+    fn write_internal(
+        &self,
+        port_index: u32,
+        protocol: u32,
+        data: &[u8],
+    ) -> Result<(), WriteError> {
+        let write_function = self.write_function.ok_or(WriteError::NoCallback)?;
+        if data.len() > u32::MAX as usize {
+            return Err(WriteError::TooLarge);
+        }
+        unsafe {
+            (write_function)(
+                self.controller,
+                port_index,
+                data.len() as u32,
+                protocol,
+                data.as_ptr() as *const c_void,
+            )
+        };
+        Ok(())
+    }
+}
+
+/// An update of a plugin port, sent by the host to the UI.
+#[derive(Clone, Copy)]
+pub enum PortEvent<'a> {
+    /// The new value of a control port.
+    Control(f32),
+    /// Data with a different port protocol, for example atoms that were sent or received by the plugin.
+    Other { protocol: URID, data: &'a [u8] },
+}
+
+impl<'a> PortEvent<'a> {
+    /// Create a port event from the raw arguments of the `port_event` function.
+    ///
+    /// Returns `None` if the data is invalid for the given protocol.
+    ///
+    /// # Safety
+    ///
+    /// The buffer has to be valid for the given size and the lifetime `'a`.
+    pub unsafe fn from_raw(buffer_size: u32, format: u32, buffer: *const c_void) -> Option<Self> {
+        if buffer.is_null() {
+            return None;
+        }
+        let data = std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize);
+        match URID::new(format) {
+            None => Space::from_slice(data)
+                .split_type::<f32>()
+                .map(|(value, _)| PortEvent::Control(*value)),
+            Some(protocol) => Some(PortEvent::Other { protocol, data }),
+        }
+    }
+
+    /// Try to interpret the event as an atom that was sent with the given protocol.
+    pub fn atom<P: AtomProtocol>(&self, protocol: URID<P>) -> Option<UnidentifiedAtom<'a>> {
+        match *self {
+            PortEvent::Other {
+                protocol: event_protocol,
+                data,
+            } if event_protocol == protocol => Some(UnidentifiedAtom::new(Space::from_slice(data))),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::ffi::c_void;
+    use urid::*;
+
+    #[test]
+    fn test_port_event() {
+        let map = HashURIDMapper::new();
+        let urids: ProtocolURIDCollection = map.populate_collection().unwrap();
+
+        let value: f32 = 42.0;
+        let event = unsafe { PortEvent::from_raw(4, 0, &value as *const f32 as *const c_void) };
+        match event {
+            Some(PortEvent::Control(value)) => assert_eq!(42.0, value),
+            _ => panic!("Invalid port event"),
+        }
+
+        assert!(
+            unsafe { PortEvent::from_raw(2, 0, &value as *const f32 as *const c_void) }.is_none()
+        );
+        assert!(unsafe { PortEvent::from_raw(4, 0, std::ptr::null()) }.is_none());
+
+        let data = [0u64; 2];
+        let event = unsafe {
+            PortEvent::from_raw(
+                16,
+                urids.event_transfer.get(),
+                data.as_ptr() as *const c_void,
+            )
+        }
+        .unwrap();
+        assert!(event.atom(urids.event_transfer).is_some());
+        assert!(event.atom(urids.atom_transfer).is_none());
+    }
+}
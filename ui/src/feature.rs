@@ -0,0 +1,313 @@
+//! Host features for plugin UIs.
+use core::feature::*;
+use std::ffi::{c_void, CString};
+use std::os::raw::c_int;
+use std::ptr::null;
+use urid::*;
+
+/// Errors potentially generated by the methods of the UI host features.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestError {
+    /// The function pointer of the feature is null.
+    ///
+    /// This can only happen with a faulty host.
+    NoCallback,
+    /// The host could not fulfill the request.
+    Refused,
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            RequestError::NoCallback => "no callback".fmt(f),
+            RequestError::Refused => "request refused by host".fmt(f),
+        }
+    }
+}
+
+/// Host feature to look up the index of a plugin port by it's symbol.
+///
+/// The index of a port is determined by the order of the fields in the plugin's `PortCollection`, but the symbol is declared in the plugin's data file. Using this feature, a UI can refer to ports by their symbol and does not need to know the order of the ports.
+#[repr(transparent)]
+pub struct PortMap<'a> {
+    internal: &'a sys::LV2UI_Port_Map,
+}
+
+unsafe impl<'a> UriBound for PortMap<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__portMap;
+}
+
+unsafe impl<'a> Feature for PortMap<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        (feature as *const sys::LV2UI_Port_Map)
+            .as_ref()
+            .map(|internal| Self { internal })
+    }
+}
+
+impl<'a> PortMap<'a> {
+    /// Return the index of the port with the given symbol.
+    ///
+    /// `None` is returned if the plugin has no port with this symbol.
+    pub fn port_index(&self, symbol: &str) -> Option<u32> {
+        let port_index = self.internal.port_index?;
+        let symbol = CString::new(symbol).ok()?;
+        match unsafe { (port_index)(self.internal.handle, symbol.as_ptr()) } {
+            u32::MAX => None,
+            index => Some(index),
+        }
+    }
+}
+
+/// Host feature to subscribe to updates of plugin ports.
+///
+/// A UI is only notified about changes of control input ports by default. This feature lets a UI request notifications for other ports too, for example control output ports or atom ports, using the protocol of the port.
+#[repr(transparent)]
+pub struct PortSubscribe<'a> {
+    internal: &'a sys::LV2UI_Port_Subscribe,
+}
+
+unsafe impl<'a> UriBound for PortSubscribe<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__portSubscribe;
+}
+
+unsafe impl<'a> Feature for PortSubscribe<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        (feature as *const sys::LV2UI_Port_Subscribe)
+            .as_ref()
+            .map(|internal| Self { internal })
+    }
+}
+
+impl<'a> PortSubscribe<'a> {
+    /// Subscribe to updates of a port, using the given port protocol.
+    pub fn subscribe<P: ?Sized>(
+        &self,
+        port_index: u32,
+        protocol: URID<P>,
+    ) -> Result<(), RequestError> {
+        Self::call(
+            self.internal.subscribe,
+            self.internal.handle,
+            port_index,
+            protocol.get(),
+        )
+    }
+
+    /// Subscribe to the value updates of a control port.
+    pub fn subscribe_control(&self, port_index: u32) -> Result<(), RequestError> {
+        Self::call(self.internal.subscribe, self.internal.handle, port_index, 0)
+    }
+
+    /// Cancel a previous subscription to a port.
+    pub fn unsubscribe<P: ?Sized>(
+        &self,
+        port_index: u32,
+        protocol: URID<P>,
+    ) -> Result<(), RequestError> {
+        Self::call(
+            self.internal.unsubscribe,
+            self.internal.handle,
+            port_index,
+            protocol.get(),
+        )
+    }
+
+    /// Cancel a previous subscription to the value updates of a control port.
+    pub fn unsubscribe_control(&self, port_index: u32) -> Result<(), RequestError> {
+        Self::call(
+            self.internal.unsubscribe,
+            self.internal.handle,
+            port_index,
+            0,
+        )
+    }
+
+    fn call(
+        function: Option<
+            unsafe extern "C" fn(
+                sys::LV2UI_Feature_Handle,
+                u32,
+                u32,
+                *const *const sys::LV2_Feature,
+            ) -> u32,
+        >,
+        handle: sys::LV2UI_Feature_Handle,
+        port_index: u32,
+        protocol: u32,
+    ) -> Result<(), RequestError> {
+        let function = function.ok_or(RequestError::NoCallback)?;
+        let features: [*const sys::LV2_Feature; 1] = [null()];
+        match unsafe { (function)(handle, port_index, protocol, features.as_ptr()) } {
+            0 => Ok(()),
+            _ => Err(RequestError::Refused),
+        }
+    }
+}
+
+/// Host feature to notify the host about user interaction with a control.
+///
+/// A UI calls [`touch`](#method.touch) when the user starts and stops to change a control, for example when a knob is grabbed and released. The host can use this to record automation.
+#[repr(transparent)]
+pub struct Touch<'a> {
+    internal: &'a sys::LV2UI_Touch,
+}
+
+unsafe impl<'a> UriBound for Touch<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__touch;
+}
+
+unsafe impl<'a> Feature for Touch<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        (feature as *const sys::LV2UI_Touch)
+            .as_ref()
+            .map(|internal| Self { internal })
+    }
+}
+
+impl<'a> Touch<'a> {
+    /// Notify the host that a control has been grabbed or released.
+    pub fn touch(&self, port_index: u32, grabbed: bool) -> Result<(), RequestError> {
+        let touch = self.internal.touch.ok_or(RequestError::NoCallback)?;
+        unsafe { (touch)(self.internal.handle, port_index, grabbed) };
+        Ok(())
+    }
+}
+
+/// Host feature to request a new size for the UI's widget.
+#[repr(transparent)]
+pub struct Resize<'a> {
+    internal: &'a sys::LV2UI_Resize,
+}
+
+unsafe impl<'a> UriBound for Resize<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__resize;
+}
+
+unsafe impl<'a> Feature for Resize<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        (feature as *const sys::LV2UI_Resize)
+            .as_ref()
+            .map(|internal| Self { internal })
+    }
+}
+
+impl<'a> Resize<'a> {
+    /// Request the host to resize the UI's widget to the given size, in pixels.
+    pub fn resize(&self, width: i32, height: i32) -> Result<(), RequestError> {
+        let ui_resize = self.internal.ui_resize.ok_or(RequestError::NoCallback)?;
+        match unsafe { (ui_resize)(self.internal.handle, width as c_int, height as c_int) } {
+            0 => Ok(()),
+            _ => Err(RequestError::Refused),
+        }
+    }
+}
+
+/// Host feature that provides the parent widget of the UI.
+///
+/// The UI should embed it's widget into the parent. The actual type of the parent depends on the type of the UI, for example an X11 window ID for X11 UIs.
+pub struct Parent {
+    widget: sys::LV2UI_Widget,
+}
+
+unsafe impl UriBound for Parent {
+    const URI: &'static [u8] = sys::LV2_UI__parent;
+}
+
+unsafe impl Feature for Parent {
+    unsafe fn from_feature_ptr(feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+        if feature.is_null() {
+            None
+        } else {
+            Some(Self {
+                widget: feature as sys::LV2UI_Widget,
+            })
+        }
+    }
+}
+
+impl Parent {
+    /// Return the parent widget.
+    pub fn widget(&self) -> sys::LV2UI_Widget {
+        self.widget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::feature::*;
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::c_char;
+
+    unsafe extern "C" fn port_index(_: sys::LV2UI_Feature_Handle, symbol: *const c_char) -> u32 {
+        match CStr::from_ptr(symbol).to_bytes() {
+            b"gain" => 0,
+            b"input" => 1,
+            b"output" => 2,
+            _ => u32::MAX,
+        }
+    }
+
+    #[test]
+    fn test_port_map() {
+        let raw = sys::LV2UI_Port_Map {
+            handle: std::ptr::null_mut(),
+            port_index: Some(port_index),
+        };
+        let port_map = unsafe {
+            PortMap::from_feature_ptr(
+                &raw as *const sys::LV2UI_Port_Map as *const c_void,
+                ThreadingClass::Other,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(Some(0), port_map.port_index("gain"));
+        assert_eq!(Some(2), port_map.port_index("output"));
+        assert_eq!(None, port_map.port_index("sidechain"));
+        assert_eq!(None, port_map.port_index("in\0put"));
+    }
+
+    unsafe extern "C" fn subscribe(
+        handle: sys::LV2UI_Feature_Handle,
+        port_index: u32,
+        protocol: u32,
+        _: *const *const sys::LV2_Feature,
+    ) -> u32 {
+        let subscriptions = (handle as *mut Vec<(u32, u32)>).as_mut().unwrap();
+        if port_index > 3 {
+            return 1;
+        }
+        subscriptions.push((port_index, protocol));
+        0
+    }
+
+    #[test]
+    fn test_port_subscribe() {
+        let mut subscriptions: Vec<(u32, u32)> = Vec::new();
+        let raw = sys::LV2UI_Port_Subscribe {
+            handle: &mut subscriptions as *mut Vec<(u32, u32)> as *mut c_void,
+            subscribe: Some(subscribe),
+            unsubscribe: None,
+        };
+        let port_subscribe = unsafe {
+            PortSubscribe::from_feature_ptr(
+                &raw as *const sys::LV2UI_Port_Subscribe as *const c_void,
+                ThreadingClass::Other,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(Ok(()), port_subscribe.subscribe_control(2));
+        assert_eq!(
+            Err(RequestError::Refused),
+            port_subscribe.subscribe_control(4)
+        );
+        assert_eq!(
+            Err(RequestError::NoCallback),
+            port_subscribe.unsubscribe_control(2)
+        );
+        assert_eq!(vec![(2, 0)], subscriptions);
+    }
+}
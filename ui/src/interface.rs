@@ -0,0 +1,181 @@
+//! Extensions of plugin UIs.
+use crate::plugin_ui::{PluginUI, PluginUIInstance};
+use core::extension::ExtensionDescriptor;
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+use urid::*;
+
+/// A UI extension that lets the host drive the UI from it's own event loop.
+///
+/// The host regularly calls [`idle`](#tymethod.idle) from the UI thread, which can be used to process events of the toolkit. This is also the only way to drive UIs that don't have a widget, for example UIs that open a window of their own or headless UIs.
+pub trait Idle: PluginUI {
+    /// Run a single iteration of the UI's event loop.
+    ///
+    /// Return `true` if the UI is still running and `false` if it has been closed, for example by the user.
+    fn idle(&mut self) -> bool;
+}
+
+/// Raw wrapper of the [`Idle`](trait.Idle.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct IdleDescriptor<U: Idle> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: Idle> UriBound for IdleDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__idleInterface;
+}
+
+impl<U: Idle> IdleDescriptor<U> {
+    /// Call the idle method of the UI.
+    ///
+    /// If the UI panics, the instance is poisoned and 1 is returned.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_idle(handle: sys::LV2UI_Handle) -> c_int {
+        let instance = (handle as *mut PluginUIInstance<U>).as_mut();
+        match instance.and_then(|instance| {
+            instance.call_guarded("idle", |instance| instance.ui_handle().idle())
+        }) {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+}
+
+impl<U: Idle> ExtensionDescriptor for IdleDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Idle_Interface;
+
+    const INTERFACE: &'static sys::LV2UI_Idle_Interface = &sys::LV2UI_Idle_Interface {
+        idle: Some(Self::extern_idle),
+    };
+}
+
+/// A UI extension to show and hide a UI that opens a window of it's own.
+///
+/// UIs with this extension are driven by the [`Idle`](trait.Idle.html) extension, which therefore has to be implemented too.
+pub trait Show: Idle {
+    /// Show the window of the UI.
+    ///
+    /// Return `true` if the window was shown successfully.
+    fn show(&mut self) -> bool;
+
+    /// Hide the window of the UI.
+    ///
+    /// Return `true` if the window was hidden successfully.
+    fn hide(&mut self) -> bool;
+}
+
+/// Raw wrapper of the [`Show`](trait.Show.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct ShowDescriptor<U: Show> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: Show> UriBound for ShowDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__showInterface;
+}
+
+impl<U: Show> ShowDescriptor<U> {
+    /// Call the show method of the UI.
+    ///
+    /// If the UI panics, the instance is poisoned and 1 is returned.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_show(handle: sys::LV2UI_Handle) -> c_int {
+        let instance = (handle as *mut PluginUIInstance<U>).as_mut();
+        match instance.and_then(|instance| {
+            instance.call_guarded("show", |instance| instance.ui_handle().show())
+        }) {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Call the hide method of the UI.
+    ///
+    /// If the UI panics, the instance is poisoned and 1 is returned.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_hide(handle: sys::LV2UI_Handle) -> c_int {
+        let instance = (handle as *mut PluginUIInstance<U>).as_mut();
+        match instance.and_then(|instance| {
+            instance.call_guarded("hide", |instance| instance.ui_handle().hide())
+        }) {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+}
+
+impl<U: Show> ExtensionDescriptor for ShowDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Show_Interface;
+
+    const INTERFACE: &'static sys::LV2UI_Show_Interface = &sys::LV2UI_Show_Interface {
+        show: Some(Self::extern_show),
+        hide: Some(Self::extern_hide),
+    };
+}
+
+/// A UI extension that lets the host resize the UI's widget.
+///
+/// This is the counterpart of the [`Resize`](struct.Resize.html) feature, which lets the UI request a new size from the host.
+pub trait Resizable: PluginUI {
+    /// Resize the widget of the UI to the given size, in pixels.
+    ///
+    /// Return `true` if the widget was resized successfully.
+    fn resize(&mut self, width: i32, height: i32) -> bool;
+}
+
+/// Raw wrapper of the [`Resizable`](trait.Resizable.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct ResizeDescriptor<U: Resizable> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: Resizable> UriBound for ResizeDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__resize;
+}
+
+impl<U: Resizable> ResizeDescriptor<U> {
+    /// Call the resize method of the UI.
+    ///
+    /// If the UI panics, the instance is poisoned and 1 is returned.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_resize(
+        handle: sys::LV2UI_Feature_Handle,
+        width: c_int,
+        height: c_int,
+    ) -> c_int {
+        let instance = (handle as *mut PluginUIInstance<U>).as_mut();
+        match instance.and_then(|instance| {
+            instance.call_guarded("resize", |instance| {
+                instance.ui_handle().resize(width, height)
+            })
+        }) {
+            Some(true) => 0,
+            _ => 1,
+        }
+    }
+}
+
+impl<U: Resizable> ExtensionDescriptor for ResizeDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Resize;
+
+    // The handle is provided by the host when calling the function, it is therefore null here.
+    const INTERFACE: &'static sys::LV2UI_Resize = &sys::LV2UI_Resize {
+        handle: std::ptr::null_mut(),
+        ui_resize: Some(Self::extern_resize),
+    };
+}
@@ -0,0 +1,83 @@
+//! Extension for LV2 plugins to provide user interfaces.
+//!
+//! A plugin UI is a separate object, usually living in a separate library, that controls a plugin via the host. It is described by the [`PluginUI`](trait.PluginUI.html) trait, which is the UI counterpart of the `Plugin` trait: The UI is created with a [`UIController`](struct.UIController.html), which lets it send control values and atoms to the plugin's input ports, and is notified about updates of the plugin's ports via [`port_event`](trait.PluginUI.html#method.port_event).
+//!
+//! Additional host features, like the [`PortMap`](struct.PortMap.html) to find ports by their symbol, are requested with the UI's `InitFeatures`, just like a plugin's features. UI extensions, like the [`Idle`](trait.Idle.html) interface, are provided via [`extension_data`](trait.PluginUI.html#method.extension_data) and the `match_extensions` macro.
+//!
+//! This crate is independent of any toolkit: A UI returns a pointer to its widget, which has to match the UI type declared in the UI's data file.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_ui::prelude::*;
+//! use urid::*;
+//!
+//! /// A UI that mirrors the gain of an amplifier.
+//! #[uri("urn:rust-lv2-book:eg-amp-rs#ui")]
+//! struct AmpUI {
+//!     controller: UIController,
+//!     gain: f32,
+//! }
+//!
+//! impl PluginUI for AmpUI {
+//!     type InitFeatures = ();
+//!
+//!     fn new(_: &PluginUIInfo, controller: UIController, _: &mut ()) -> Option<Self> {
+//!         Some(Self {
+//!             controller,
+//!             gain: 0.0,
+//!         })
+//!     }
+//!
+//!     fn port_event(&mut self, port_index: u32, event: PortEvent) {
+//!         // The gain is the first port of the plugin.
+//!         if let (0, PortEvent::Control(gain)) = (port_index, event) {
+//!             self.gain = gain;
+//!         }
+//!     }
+//! }
+//!
+//! impl AmpUI {
+//!     /// Something like a callback of a slider.
+//!     fn set_gain(&mut self, gain: f32) {
+//!         self.gain = gain;
+//!         let _ = self.controller.write_control(0, gain);
+//!     }
+//! }
+//!
+//! lv2ui_descriptors!(AmpUI);
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+
+mod controller;
+pub use controller::*;
+
+mod feature;
+pub use feature::*;
+
+mod interface;
+pub use interface::*;
+
+mod plugin_ui;
+pub use plugin_ui::*;
+
+/// Prelude of `lv2_ui` for wildcard usage.
+pub mod prelude {
+    pub use crate::controller::{
+        AtomProtocol, AtomTransfer, EventTransfer, PortEvent, ProtocolURIDCollection, UIController,
+        WriteError,
+    };
+    pub use crate::feature::{Parent, PortMap, PortSubscribe, RequestError, Resize, Touch};
+    pub use crate::interface::{
+        Idle, IdleDescriptor, Resizable, ResizeDescriptor, Show, ShowDescriptor,
+    };
+    pub use crate::lv2ui_descriptors;
+    pub use crate::plugin_ui::{
+        PluginUI, PluginUIInfo, PluginUIInstance, PluginUIInstanceDescriptor,
+    };
+    pub use sys::LV2UI_Descriptor;
+    pub use urid::UriBound;
+}
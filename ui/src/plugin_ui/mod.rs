@@ -0,0 +1,283 @@
+//! Types to create plugin UIs.
+pub(crate) mod info;
+
+pub use info::PluginUIInfo;
+
+use crate::controller::*;
+use core::feature::*;
+use core::plugin::catch_panic;
+use std::any::Any;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use urid::{Uri, UriBound};
+
+/// The central trait to describe LV2 plugin UIs.
+///
+/// A UI is a separate object that talks to the plugin via the host: It sends data to the input ports of the plugin with the [`UIController`](struct.UIController.html) and is notified about updates of the plugin's ports via the [`port_event`](#method.port_event) method.
+///
+/// Just like plugins, the host will not directly talk to the UI. Instead, it will create and talk to the [`PluginUIInstance`](struct.PluginUIInstance.html), which dereferences raw pointers, does safety checks and then calls the corresponding UI methods.
+pub trait PluginUI: UriBound + Sized + 'static {
+    /// The host features used by this UI.
+    ///
+    /// They are retrieved in the `Other` threading class, since UIs run in a separate thread of their own.
+    type InitFeatures: FeatureCollection<'static>;
+
+    /// Create a new instance of the UI.
+    ///
+    /// The controller is used to send data to the plugin and may be stored by the UI. If the UI can not be created, `None` is returned.
+    fn new(
+        ui_info: &PluginUIInfo,
+        controller: UIController,
+        features: &mut Self::InitFeatures,
+    ) -> Option<Self>;
+
+    /// Clean up the UI before it is dropped.
+    ///
+    /// After this method was called, the host won't access the widget of the UI anymore.
+    fn cleanup(&mut self, _features: &mut Self::InitFeatures) {}
+
+    /// Handle an update of a plugin port.
+    ///
+    /// By default, the host only sends updates of control input ports.
+    fn port_event(&mut self, _port_index: u32, _event: PortEvent) {}
+
+    /// Return the widget of the UI.
+    ///
+    /// The actual type of the widget depends on the type of the UI, as declared in the UI's data file. Headless UIs, which only use the idle interface, don't have a widget and return a null pointer, which is the default.
+    fn widget(&self) -> sys::LV2UI_Widget {
+        std::ptr::null_mut()
+    }
+
+    /// Return additional UI extension data.
+    ///
+    /// This works just like the plugin's [`extension_data`](../lv2_core/plugin/trait.Plugin.html#method.extension_data). The easiest way to implement it is the `match_extensions` macro.
+    fn extension_data(_uri: &Uri) -> Option<&'static dyn Any> {
+        None
+    }
+}
+
+/// UI wrapper which translates between the host and the UI.
+///
+/// This struct is `repr(C)` and has the UI as it's first field. Therefore, a valid `*mut PluginUIInstance<T>` is also a valid `*mut T`.
+///
+/// Just like the plugin's instance, every call into the UI is guarded with [`call_guarded`](#method.call_guarded): If the UI panics, the panic is caught and the instance is marked as poisoned. A poisoned UI isn't called anymore.
+#[repr(C)]
+pub struct PluginUIInstance<T: PluginUI> {
+    /// The UI instance.
+    instance: T,
+    /// All features that may be used by the UI.
+    features: T::InitFeatures,
+    /// Whether the UI has panicked.
+    poisoned: bool,
+}
+
+impl<T: PluginUI> PluginUIInstance<T> {
+    /// Instantiate the UI.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn instantiate(
+        descriptor: *const sys::LV2UI_Descriptor,
+        plugin_uri: *const c_char,
+        bundle_path: *const c_char,
+        write_function: sys::LV2UI_Write_Function,
+        controller: sys::LV2UI_Controller,
+        widget: *mut sys::LV2UI_Widget,
+        features: *const *const sys::LV2_Feature,
+    ) -> sys::LV2UI_Handle {
+        // Collect the supported features.
+        let host_features = FeatureCache::from_raw(features);
+        let mut features_cache = host_features.clone();
+
+        if descriptor.is_null() || plugin_uri.is_null() || bundle_path.is_null() {
+            report_instantiation_error(
+                &host_features,
+                format_args!("Failed to initialize UI: Null pointer passed by host"),
+            );
+            return std::ptr::null_mut();
+        }
+
+        // Dereference the UI info.
+        let ui_info = match PluginUIInfo::from_raw(descriptor, plugin_uri, bundle_path) {
+            Ok(info) => info,
+            Err(e) => {
+                report_instantiation_error(
+                    &host_features,
+                    format_args!("Failed to initialize UI: Illegal info from host: {:?}", e),
+                );
+                return std::ptr::null_mut();
+            }
+        };
+
+        let mut features =
+            match T::InitFeatures::from_cache(&mut features_cache, ThreadingClass::Other) {
+                Ok(f) => f,
+                Err(e) => {
+                    report_instantiation_error(
+                        &host_features,
+                        format_args!("{}; {}", e, features_cache.dump()),
+                    );
+                    return std::ptr::null_mut();
+                }
+            };
+
+        // Instantiate the UI.
+        let controller = UIController::new(write_function, controller);
+        let instance = catch_panic("instantiate", || {
+            let instance = T::new(&ui_info, controller, &mut features)?;
+            if let Some(widget) = widget.as_mut() {
+                *widget = instance.widget();
+            }
+            Some(instance)
+        });
+        match instance {
+            Some(Some(instance)) => {
+                let instance = Box::new(Self {
+                    instance,
+                    features,
+                    poisoned: false,
+                });
+                Box::leak(instance) as *mut Self as sys::LV2UI_Handle
+            }
+            _ => std::ptr::null_mut(),
+        }
+    }
+
+    /// Clean the UI.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn cleanup(instance: sys::LV2UI_Handle) {
+        let mut instance = Box::from_raw(instance as *mut Self);
+        instance.call_guarded("cleanup", |instance| {
+            instance.instance.cleanup(&mut instance.features)
+        });
+        catch_panic("cleanup", || drop(instance));
+    }
+
+    /// Call `port_event`.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn port_event(
+        instance: sys::LV2UI_Handle,
+        port_index: u32,
+        buffer_size: u32,
+        format: u32,
+        buffer: *const c_void,
+    ) {
+        let instance = &mut *(instance as *mut Self);
+        if let Some(event) = PortEvent::from_raw(buffer_size, format, buffer) {
+            instance.call_guarded("port_event", |instance| {
+                instance.instance.port_event(port_index, event)
+            });
+        }
+    }
+
+    /// Dereference the URI, call the `extension_data` function and return the pointer.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn extension_data(uri: *const c_char) -> *const c_void {
+        let uri = Uri::from_ptr(uri);
+        match catch_panic("extension_data", || T::extension_data(uri)) {
+            Some(Some(data)) => data as *const _ as *const c_void,
+            _ => std::ptr::null(),
+        }
+    }
+
+    /// Call a function with the instance and catch a panic.
+    ///
+    /// If the function panics, the instance is poisoned and the panic message is printed to the standard error output, together with the name of the function. This method is used by all functions of the C interface that call the UI and should also be used by extensions.
+    ///
+    /// Returns `None` if the function panicked or if the instance is already poisoned, in which case the function isn't called at all.
+    pub fn call_guarded<R, F: FnOnce(&mut Self) -> R>(
+        &mut self,
+        function: &str,
+        f: F,
+    ) -> Option<R> {
+        if self.poisoned {
+            return None;
+        }
+        let result = catch_panic(function, || f(&mut *self));
+        if result.is_none() {
+            self.poisoned = true;
+        }
+        result
+    }
+
+    /// Return `true` if the UI has panicked and isn't called anymore.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Retrieve the internal UI.
+    pub fn ui_handle(&mut self) -> &mut T {
+        &mut self.instance
+    }
+
+    /// Retrieve the UI and its features.
+    ///
+    /// This method can be used by extensions to call an extension method and provide it the host features.
+    pub fn features_handle(&mut self) -> (&mut T, &mut T::InitFeatures) {
+        (&mut self.instance, &mut self.features)
+    }
+}
+
+#[doc(hidden)]
+pub unsafe trait PluginUIInstanceDescriptor: PluginUI {
+    const DESCRIPTOR: sys::LV2UI_Descriptor;
+}
+
+/// Generate external symbols for LV2 plugin UIs.
+///
+/// This macro is the UI counterpart of `lv2_descriptors`: It takes a list of types that implement [`PluginUI`](trait.PluginUI.html) and creates the `lv2ui_descriptor` function, which tells the host about the UIs in the library.
+#[macro_export]
+macro_rules! lv2ui_descriptors {
+    ($($ui:ty),* $(,)?) => {
+        $(
+            unsafe impl $crate::prelude::PluginUIInstanceDescriptor for $ui {
+                const DESCRIPTOR: $crate::prelude::LV2UI_Descriptor = $crate::prelude::LV2UI_Descriptor {
+                    URI: <Self as $crate::prelude::UriBound>::URI.as_ptr() as *const u8 as *const ::std::os::raw::c_char,
+                    instantiate: Some($crate::prelude::PluginUIInstance::<Self>::instantiate),
+                    cleanup: Some($crate::prelude::PluginUIInstance::<Self>::cleanup),
+                    port_event: Some($crate::prelude::PluginUIInstance::<Self>::port_event),
+                    extension_data: Some($crate::prelude::PluginUIInstance::<Self>::extension_data),
+                };
+            }
+        )*
+
+        /// Return a raw pointer to the UI descriptor with the given index.
+        ///
+        /// This function is used by the host to discover UIs in the library. The host calls it with an ascending index and stores every returned descriptor,
+        /// until a null pointer is returned.
+        ///
+        /// # Safety
+        ///
+        /// This function is primarily unsafe because it's a method that's directly called by the host. It doesn't actually do anything that unsafe.
+        ///
+        /// The returned pointer references a constant and there is valid as long as the library is loaded.
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2ui_descriptor(index: u32) -> *const $crate::prelude::LV2UI_Descriptor {
+            let descriptors: &[&'static $crate::prelude::LV2UI_Descriptor] = &[
+                $(&<$ui as $crate::prelude::PluginUIInstanceDescriptor>::DESCRIPTOR),*
+            ];
+            match descriptors.get(index as usize) {
+                Some(descriptor) => *descriptor,
+                None => ::std::ptr::null(),
+            }
+        }
+    };
+}
@@ -0,0 +1,306 @@
+use lv2_atom::prelude::*;
+use lv2_atom::space::RootMutSpace;
+use lv2_core::prelude::*;
+use lv2_ui::prelude::*;
+use std::any::Any;
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+use urid::*;
+
+#[derive(Clone, URIDCollection)]
+struct URIDs {
+    atom: AtomURIDCollection,
+    protocols: ProtocolURIDCollection,
+}
+
+/// A UI without a widget that forwards every port event to the plugin.
+#[uri("urn:lv2_ui:headless")]
+struct Headless {
+    controller: UIController,
+    urids: URIDs,
+    received_values: Vec<f32>,
+    idle_cycles: u32,
+}
+
+impl PluginUI for Headless {
+    type InitFeatures = ();
+
+    fn new(info: &PluginUIInfo, controller: UIController, _: &mut ()) -> Option<Self> {
+        assert_eq!(
+            b"urn:lv2_ui:plugin\0",
+            info.plugin_uri().to_bytes_with_nul()
+        );
+        assert_eq!(Self::uri(), info.ui_uri());
+        Some(Self {
+            controller,
+            urids: HashURIDMapper::new().populate_collection()?,
+            received_values: Vec::new(),
+            idle_cycles: 0,
+        })
+    }
+
+    fn port_event(&mut self, port_index: u32, event: PortEvent) {
+        match event {
+            PortEvent::Control(value) => {
+                self.received_values.push(value);
+                self.controller
+                    .write_control(port_index + 1, value * 2.0)
+                    .unwrap();
+            }
+            event => {
+                let atom = event.atom(self.urids.protocols.event_transfer).unwrap();
+                self.controller
+                    .write_atom(port_index + 1, self.urids.protocols.event_transfer, atom)
+                    .unwrap();
+            }
+        }
+    }
+
+    fn extension_data(uri: &Uri) -> Option<&'static dyn Any> {
+        match_extensions!(uri, IdleDescriptor<Self>)
+    }
+}
+
+impl Idle for Headless {
+    fn idle(&mut self) -> bool {
+        // The UI closes itself after two cycles.
+        self.idle_cycles += 1;
+        self.idle_cycles < 2
+    }
+}
+
+/// A UI that panics in every method.
+#[uri("urn:lv2_ui:panicking")]
+struct Panicking;
+
+impl PluginUI for Panicking {
+    type InitFeatures = ();
+
+    fn new(_: &PluginUIInfo, _: UIController, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn port_event(&mut self, _: u32, _: PortEvent) {
+        panic!("port_event");
+    }
+
+    fn extension_data(_: &Uri) -> Option<&'static dyn Any> {
+        panic!("extension_data");
+    }
+}
+
+impl Idle for Panicking {
+    fn idle(&mut self) -> bool {
+        panic!("idle");
+    }
+}
+
+impl Show for Panicking {
+    fn show(&mut self) -> bool {
+        panic!("show");
+    }
+
+    fn hide(&mut self) -> bool {
+        panic!("hide");
+    }
+}
+
+impl Resizable for Panicking {
+    fn resize(&mut self, _: i32, _: i32) -> bool {
+        panic!("resize");
+    }
+}
+
+lv2ui_descriptors!(Headless, Panicking);
+
+/// Data the fake host has received from the UI.
+#[derive(Default)]
+struct Received {
+    writes: Vec<(u32, u32, Vec<u8>)>,
+}
+
+unsafe extern "C" fn write_function(
+    controller: *mut c_void,
+    port_index: u32,
+    buffer_size: u32,
+    port_protocol: u32,
+    buffer: *const c_void,
+) {
+    let received = (controller as *mut Received).as_mut().unwrap();
+    let data = std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize);
+    received
+        .writes
+        .push((port_index, port_protocol, data.to_owned()));
+}
+
+#[test]
+fn test_headless_ui() {
+    let descriptor = unsafe { lv2ui_descriptor(0).as_ref() }.unwrap();
+    assert!(unsafe { lv2ui_descriptor(2) }.is_null());
+    assert_eq!(Headless::uri(), unsafe { CStr::from_ptr(descriptor.URI) });
+
+    let mut received = Received::default();
+    let mut widget: *mut c_void = std::ptr::NonNull::dangling().as_ptr();
+    let features: [*const lv2_sys::LV2_Feature; 1] = [std::ptr::null()];
+
+    let handle = unsafe {
+        (descriptor.instantiate.unwrap())(
+            descriptor,
+            b"urn:lv2_ui:plugin\0".as_ptr() as *const c_char,
+            b"/\0".as_ptr() as *const c_char,
+            Some(write_function),
+            &mut received as *mut Received as *mut c_void,
+            &mut widget,
+            features.as_ptr(),
+        )
+    };
+    assert!(!handle.is_null());
+    assert!(widget.is_null());
+
+    // Sending a control value.
+    let value: f32 = 21.0;
+    unsafe {
+        (descriptor.port_event.unwrap())(
+            handle,
+            0,
+            std::mem::size_of::<f32>() as u32,
+            0,
+            &value as *const f32 as *const c_void,
+        )
+    };
+    assert_eq!(
+        vec![21.0],
+        unsafe { (handle as *mut Headless).as_ref() }
+            .unwrap()
+            .received_values
+    );
+    assert_eq!(1, received.writes.len());
+    assert_eq!((1, 0), (received.writes[0].0, received.writes[0].1));
+    assert_eq!(
+        42.0f32.to_ne_bytes().as_ref(),
+        received.writes[0].2.as_slice()
+    );
+
+    // Sending an atom.
+    let urids: URIDs = unsafe { (handle as *mut Headless).as_ref() }
+        .unwrap()
+        .urids
+        .clone();
+    let mut atom_buffer = vec![0u64; 4];
+    {
+        let raw =
+            unsafe { std::slice::from_raw_parts_mut(atom_buffer.as_mut_ptr() as *mut u8, 32) };
+        let mut space = RootMutSpace::new(raw);
+        (&mut space as &mut dyn MutSpace)
+            .init(urids.atom.int, 42)
+            .unwrap();
+    }
+    unsafe {
+        (descriptor.port_event.unwrap())(
+            handle,
+            2,
+            16,
+            urids.protocols.event_transfer.get(),
+            atom_buffer.as_ptr() as *const c_void,
+        )
+    };
+    assert_eq!(2, received.writes.len());
+    let (port_index, protocol, data) = &received.writes[1];
+    assert_eq!(3, *port_index);
+    assert_eq!(urids.protocols.event_transfer, *protocol);
+    let atom = UnidentifiedAtom::new(Space::from_slice(data));
+    assert_eq!(Some(42), atom.read(urids.atom.int, ()));
+
+    // Driving the UI with the idle interface.
+    let idle_interface = unsafe {
+        ((descriptor.extension_data.unwrap())(
+            lv2_sys::LV2_UI__idleInterface.as_ptr() as *const c_char
+        ) as *const lv2_sys::LV2UI_Idle_Interface)
+            .as_ref()
+    }
+    .unwrap();
+    let idle = idle_interface.idle.unwrap();
+    assert_eq!(0, unsafe { (idle)(handle) });
+    assert_eq!(1, unsafe { (idle)(handle) });
+
+    // The show interface is not supported.
+    assert!(unsafe {
+        (descriptor.extension_data.unwrap())(
+            lv2_sys::LV2_UI__showInterface.as_ptr() as *const c_char
+        )
+    }
+    .is_null());
+
+    unsafe { (descriptor.cleanup.unwrap())(handle) };
+}
+
+#[test]
+fn test_panicking_ui() {
+    let descriptor = unsafe { lv2ui_descriptor(1).as_ref() }.unwrap();
+    assert_eq!(Panicking::uri(), unsafe { CStr::from_ptr(descriptor.URI) });
+
+    let instantiate = || {
+        let features: [*const lv2_sys::LV2_Feature; 1] = [std::ptr::null()];
+        let handle = unsafe {
+            (descriptor.instantiate.unwrap())(
+                descriptor,
+                b"urn:lv2_ui:plugin\0".as_ptr() as *const c_char,
+                b"/\0".as_ptr() as *const c_char,
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                features.as_ptr(),
+            )
+        };
+        assert!(!handle.is_null());
+        handle
+    };
+    let is_poisoned = |handle: lv2_sys::LV2UI_Handle| {
+        unsafe { (handle as *const PluginUIInstance<Panicking>).as_ref() }
+            .unwrap()
+            .is_poisoned()
+    };
+
+    // A panic in one of the extensions poisons the instance.
+    unsafe extern "C" fn resize(handle: lv2_sys::LV2UI_Handle) -> i32 {
+        ResizeDescriptor::<Panicking>::extern_resize(handle, 640, 480)
+    }
+    let extensions: [unsafe extern "C" fn(lv2_sys::LV2UI_Handle) -> i32; 4] = [
+        IdleDescriptor::<Panicking>::extern_idle,
+        ShowDescriptor::<Panicking>::extern_show,
+        ShowDescriptor::<Panicking>::extern_hide,
+        resize,
+    ];
+    for extension in extensions.iter() {
+        let handle = instantiate();
+        assert_eq!(1, unsafe { (extension)(handle) });
+        assert!(is_poisoned(handle));
+        unsafe { (descriptor.cleanup.unwrap())(handle) };
+    }
+
+    // A panic in `port_event` poisons the instance and the instance isn't called anymore.
+    let handle = instantiate();
+    let value: f32 = 21.0;
+    unsafe {
+        (descriptor.port_event.unwrap())(
+            handle,
+            0,
+            std::mem::size_of::<f32>() as u32,
+            0,
+            &value as *const f32 as *const c_void,
+        )
+    };
+    assert!(is_poisoned(handle));
+    assert_eq!(1, unsafe {
+        IdleDescriptor::<Panicking>::extern_idle(handle)
+    });
+    unsafe { (descriptor.cleanup.unwrap())(handle) };
+
+    // A panic in `extension_data` is reported as a missing extension.
+    assert!(unsafe {
+        (descriptor.extension_data.unwrap())(
+            lv2_sys::LV2_UI__idleInterface.as_ptr() as *const c_char
+        )
+    }
+    .is_null());
+}
@@ -114,7 +114,8 @@
 
 use lv2_core::extension::ExtensionDescriptor;
 use lv2_core::feature::*;
-use lv2_core::plugin::{Plugin, PluginInstance};
+use lv2_core::plugin::{catch_panic, Plugin, PluginInstance};
+use lv2_core::rt_check;
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
@@ -389,6 +390,8 @@ unsafe impl<P: Worker> UriBound for WorkerDescriptor<P> {
 
 impl<P: Worker> WorkerDescriptor<P> {
     /// Extern unsafe version of `work` method actually called by the host
+    ///
+    /// If the worker panics, `LV2_WORKER_ERR_UNKNOWN` is returned.
     unsafe extern "C" fn extern_work(
         _handle: lv2_sys::LV2_Handle,
         response_function: lv2_sys::LV2_Worker_Respond_Function,
@@ -396,27 +399,28 @@ impl<P: Worker> WorkerDescriptor<P> {
         size: u32,
         data: *const c_void,
     ) -> lv2_sys::LV2_Worker_Status {
-        //build response handler
-        let response_handler = ResponseHandler {
-            response_function,
-            respond_handle,
-            phantom: PhantomData::<P>,
-        };
-        //build ref to worker data from raw pointer
-        let worker_data =
-            ptr::read_unaligned(data as *const mem::ManuallyDrop<<P as Worker>::WorkData>);
-        let worker_data = mem::ManuallyDrop::into_inner(worker_data);
-        if size as usize != mem::size_of_val(&worker_data) {
-            return lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
-        }
-        match P::work(&response_handler, worker_data) {
-            Ok(()) => lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
-            Err(WorkerError::Unknown) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
-            Err(WorkerError::NoSpace) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE,
-        }
+        catch_panic("work", || {
+            //build response handler
+            let response_handler = ResponseHandler {
+                response_function,
+                respond_handle,
+                phantom: PhantomData::<P>,
+            };
+            //build ref to worker data from raw pointer
+            let worker_data =
+                ptr::read_unaligned(data as *const mem::ManuallyDrop<<P as Worker>::WorkData>);
+            let worker_data = mem::ManuallyDrop::into_inner(worker_data);
+            if size as usize != mem::size_of_val(&worker_data) {
+                return lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+            }
+            worker_status(P::work(&response_handler, worker_data))
+        })
+        .unwrap_or(lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN)
     }
 
     /// Extern unsafe version of `work_response` method actually called by the host
+    ///
+    /// If the plugin panics, the plugin instance is poisoned and `LV2_WORKER_ERR_UNKNOWN` is returned.
     unsafe extern "C" fn extern_work_response(
         handle: lv2_sys::LV2_Handle,
         size: u32,
@@ -433,33 +437,45 @@ impl<P: Worker> WorkerDescriptor<P> {
         let response_data =
             ptr::read_unaligned(body as *const mem::ManuallyDrop<<P as Worker>::ResponseData>);
         let response_data = mem::ManuallyDrop::into_inner(response_data);
-        if size as usize != mem::size_of_val(&response_data) {
-            return lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
-        }
-
-        let (instance, features) = plugin_instance.audio_class_handle();
-        match instance.work_response(response_data, features) {
-            Ok(()) => lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
-            Err(WorkerError::Unknown) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
-            Err(WorkerError::NoSpace) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE,
-        }
+        plugin_instance
+            .call_guarded("work_response", |plugin_instance| {
+                if size as usize != mem::size_of_val(&response_data) {
+                    return lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+                }
+                let (instance, features) = plugin_instance.audio_class_handle();
+                rt_check::audio_class(|| {
+                    worker_status(instance.work_response(response_data, features))
+                })
+            })
+            .unwrap_or(lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN)
     }
 
     /// Extern unsafe version of `end_run` method actually called by the host
+    ///
+    /// If the plugin panics, the plugin instance is poisoned and `LV2_WORKER_ERR_UNKNOWN` is returned.
     unsafe extern "C" fn extern_end_run(handle: lv2_sys::LV2_Handle) -> lv2_sys::LV2_Worker_Status {
         if let Some(plugin_instance) = (handle as *mut PluginInstance<P>).as_mut() {
-            let (instance, features) = plugin_instance.audio_class_handle();
-            match instance.end_run(features) {
-                Ok(()) => lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
-                Err(WorkerError::Unknown) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
-                Err(WorkerError::NoSpace) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE,
-            }
+            plugin_instance
+                .call_guarded("end_run", |plugin_instance| {
+                    let (instance, features) = plugin_instance.audio_class_handle();
+                    rt_check::audio_class(|| worker_status(instance.end_run(features)))
+                })
+                .unwrap_or(lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN)
         } else {
             lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN
         }
     }
 }
 
+/// Convert the result of a worker method to a status for the host.
+fn worker_status(result: Result<(), WorkerError>) -> lv2_sys::LV2_Worker_Status {
+    match result {
+        Ok(()) => lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
+        Err(WorkerError::Unknown) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+        Err(WorkerError::NoSpace) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE,
+    }
+}
+
 // Implementing the trait that contains the interface.
 impl<P: Worker> ExtensionDescriptor for WorkerDescriptor<P> {
     type ExtensionInterface = lv2_sys::LV2_Worker_Interface;
@@ -638,25 +654,47 @@ mod tests {
         let _ = respond.respond(hd);
     }
 
+    fn instantiate_test_drop_worker() -> LV2_Handle {
+        let descriptor = LV2_Descriptor {
+            URI: TestDropWorker::URI.as_ptr() as *const c_char,
+            instantiate: None,
+            connect_port: None,
+            activate: None,
+            run: None,
+            deactivate: None,
+            cleanup: None,
+            extension_data: None,
+        };
+        let features: [*const LV2_Feature; 1] = [ptr::null()];
+        let handle = unsafe {
+            PluginInstance::<TestDropWorker>::instantiate(
+                &descriptor,
+                44100.0,
+                b"/\0".as_ptr() as *const c_char,
+                features.as_ptr(),
+            )
+        };
+        assert!(!handle.is_null());
+        handle
+    }
+
     #[test]
-    #[should_panic(expected = "Dropped")]
     fn extern_work_should_drop() {
         let hd = mem::ManuallyDrop::new(HasDrop::new(0));
         let ptr_hd = &hd as *const _ as *const c_void;
         let size = mem::size_of_val(&hd) as u32;
-        let mut tdw = TestDropWorker {};
 
-        let ptr_tdw = &mut tdw as *mut _ as *mut c_void;
-        //trash trick i use Plugin ptr insteas of Pluginstance ptr
-        unsafe {
+        // The drop panics, which is caught and reported as an error.
+        let status = unsafe {
             WorkerDescriptor::<TestDropWorker>::extern_work(
-                ptr_tdw,
+                ptr::null_mut(),
                 Some(extern_respond),
                 ptr::null_mut(),
                 size,
                 ptr_hd,
-            );
-        }
+            )
+        };
+        assert_eq!(LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, status);
     }
 
     #[test]
@@ -664,33 +702,34 @@ mod tests {
         let hd = mem::ManuallyDrop::new(HasDrop::new(1));
         let ptr_hd = &hd as *const _ as *const c_void;
         let size = mem::size_of_val(&hd) as u32;
-        let mut tdw = TestDropWorker {};
 
-        let ptr_tdw = &mut tdw as *mut _ as *mut c_void;
-        //trash trick i use Plugin ptr insteas of Pluginstance ptr
-        unsafe {
+        let status = unsafe {
             WorkerDescriptor::<TestDropWorker>::extern_work(
-                ptr_tdw,
+                ptr::null_mut(),
                 Some(extern_respond),
                 ptr::null_mut(),
                 size,
                 ptr_hd,
-            );
-        }
+            )
+        };
+        assert_eq!(LV2_Worker_Status_LV2_WORKER_SUCCESS, status);
     }
 
     #[test]
-    #[should_panic(expected = "Dropped")]
     fn extern_work_response_should_drop() {
         let hd = mem::ManuallyDrop::new(HasDrop::new(0));
         let ptr_hd = &hd as *const _ as *const c_void;
         let size = mem::size_of_val(&hd) as u32;
-        let mut tdw = TestDropWorker {};
+        let handle = instantiate_test_drop_worker();
 
-        let ptr_tdw = &mut tdw as *mut _ as *mut c_void;
-        //trash trick i use Plugin ptr insteas of Pluginstance ptr
+        // The drop panics, which poisons the instance.
         unsafe {
-            WorkerDescriptor::<TestDropWorker>::extern_work_response(ptr_tdw, size, ptr_hd);
+            let status =
+                WorkerDescriptor::<TestDropWorker>::extern_work_response(handle, size, ptr_hd);
+            assert_eq!(LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, status);
+            let instance = (handle as *mut PluginInstance<TestDropWorker>).as_ref();
+            assert!(instance.unwrap().is_poisoned());
+            PluginInstance::<TestDropWorker>::cleanup(handle);
         }
     }
 
@@ -699,12 +738,15 @@ mod tests {
         let hd = mem::ManuallyDrop::new(HasDrop::new(1));
         let ptr_hd = &hd as *const _ as *const c_void;
         let size = mem::size_of_val(&hd) as u32;
-        let mut tdw = TestDropWorker {};
+        let handle = instantiate_test_drop_worker();
 
-        let ptr_tdw = &mut tdw as *mut _ as *mut c_void;
-        //trash trick i use Plugin ptr insteas of Pluginstance ptr
         unsafe {
-            WorkerDescriptor::<TestDropWorker>::extern_work_response(ptr_tdw, size, ptr_hd);
+            let status =
+                WorkerDescriptor::<TestDropWorker>::extern_work_response(handle, size, ptr_hd);
+            assert_eq!(LV2_Worker_Status_LV2_WORKER_SUCCESS, status);
+            let instance = (handle as *mut PluginInstance<TestDropWorker>).as_ref();
+            assert!(!instance.unwrap().is_poisoned());
+            PluginInstance::<TestDropWorker>::cleanup(handle);
         }
     }
 }